@@ -0,0 +1,208 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::effects::Effect;
+use crate::json::{self, Value};
+use crate::{data_dir, Controller, GameScreen};
+
+/// How a run ended, as far as achievements care.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Death {
+    Wall,
+    SelfCollision,
+    Obstacle,
+    Starved,
+    Poisoned,
+    Crushed,
+    Rival,
+}
+
+impl Death {
+    pub fn description(self) -> &'static str {
+        match self {
+            Death::Wall => "Hit the wall",
+            Death::SelfCollision => "Bit yourself",
+            Death::Obstacle => "Hit an obstacle",
+            Death::Starved => "Starved",
+            Death::Poisoned => "Ate poison",
+            Death::Crushed => "Caught by the border",
+            Death::Rival => "Ran into the other snake",
+        }
+    }
+}
+
+/// Counters the game logic keeps about the current run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunStats {
+    // Ticks the snake spent moving. Waiting on the start screen doesn't count.
+    pub ticks: u64,
+    // Time those ticks took, summed at the tick length in effect for each one so that speed-ups
+    // and slow-downs are accounted for.
+    pub elapsed: Duration,
+    pub apples_eaten: u32,
+    pub ticks_since_apple: u64,
+    pub time_since_apple: Duration,
+    pub best_combo: u32,
+    pub death: Option<Death>,
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub unlocked_by: fn(&Controller) -> bool,
+}
+
+/// Every achievement there is. Adding one only takes a new entry here.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first-blood",
+        name: "First Blood",
+        description: "Eat an apple",
+        unlocked_by: |controller| controller.stats.apples_eaten >= 1,
+    },
+    Achievement {
+        id: "centurion",
+        name: "Centurion",
+        description: "Grow to a length of 100",
+        unlocked_by: |controller| controller.snake.elements.len() >= 100,
+    },
+    Achievement {
+        id: "speed-demon",
+        name: "Speed Demon",
+        description: "Reach a score of 20 while sped up",
+        unlocked_by: |controller| {
+            controller.score >= 20
+                && controller
+                    .active_effects
+                    .iter()
+                    .any(|active| active.effect == Effect::SpeedUp)
+        },
+    },
+    Achievement {
+        id: "pacifist",
+        name: "Pacifist",
+        description: "Survive 60 seconds without eating",
+        unlocked_by: |controller| {
+            controller.stats.time_since_apple >= Duration::from_secs(60)
+                && controller.screen != GameScreen::GameOver
+        },
+    },
+    Achievement {
+        id: "ouroboros",
+        name: "Ouroboros",
+        description: "Bite yourself at a length of 30 or more",
+        unlocked_by: |controller| {
+            controller.stats.death == Some(Death::SelfCollision)
+                && controller.snake.elements.len() >= 30
+        },
+    },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unlock {
+    pub id: String,
+    pub date: String,
+}
+
+/// The achievements unlocked so far, across all runs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    pub unlocked: Vec<Unlock>,
+}
+
+impl Progress {
+    /// Reads the progress at `path`. A missing file means nothing has been unlocked yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Progress::from_json(&json)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Progress::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_json())
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.iter().any(|unlock| unlock.id == id)
+    }
+
+    /// Unlocks every achievement `controller` now satisfies and returns the ones that weren't
+    /// unlocked before.
+    pub fn unlock_new(&mut self, controller: &Controller, date: &str) -> Vec<&'static Achievement> {
+        let new: Vec<&'static Achievement> = ACHIEVEMENTS
+            .iter()
+            .filter(|achievement| !self.is_unlocked(achievement.id))
+            .filter(|achievement| (achievement.unlocked_by)(controller))
+            .collect();
+
+        for achievement in &new {
+            self.unlocked.push(Unlock {
+                id: String::from(achievement.id),
+                date: String::from(date),
+            });
+        }
+
+        new
+    }
+
+    pub fn to_json(&self) -> String {
+        let records: Vec<String> = self
+            .unlocked
+            .iter()
+            .map(|unlock| {
+                format!(
+                    "  {{\"id\": {}, \"date\": {}}}",
+                    json::quote(&unlock.id),
+                    json::quote(&unlock.date)
+                )
+            })
+            .collect();
+
+        if records.is_empty() {
+            String::from("[]\n")
+        } else {
+            format!("[\n{}\n]\n", records.join(",\n"))
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let records = json::parse(json)?;
+        let records = records
+            .as_array()
+            .ok_or("Achievements must be an array of unlocks.")?;
+
+        let unlocked = records
+            .iter()
+            .map(|record| {
+                Ok(Unlock {
+                    id: record
+                        .get("id")
+                        .and_then(Value::as_str)
+                        .ok_or("Unlock is missing its id.")?
+                        .to_string(),
+                    date: record
+                        .get("date")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Progress { unlocked })
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("achievements.json"))
+}
@@ -0,0 +1,205 @@
+// Pathfinding for the bots: the computer opponent of --ai and the greedy controller behind --bot
+// and --demo. They head for the nearest apple along the shortest path around walls, obstacles
+// and both snakes, and steer like a player would, by asking for a direction that the usual
+// movement rules then apply.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::bot::BoardView;
+use crate::{compute_new_head, wrapped_head, AppleType, CanvasSpace, Controller, Direction, Snake};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Skill {
+    // Often wanders off in a random direction.
+    Easy,
+    // Now and then.
+    Normal,
+    // Never, and without a way to an apple it turns to where there is the most room.
+    Hard,
+}
+
+impl FromStr for Skill {
+    type Err = String;
+
+    fn from_str(skill: &str) -> Result<Self, Self::Err> {
+        match skill {
+            "easy" => Ok(Skill::Easy),
+            "normal" => Ok(Skill::Normal),
+            "hard" => Ok(Skill::Hard),
+            _ => Err(format!(
+                "Unknown skill `{}`, expected easy, normal or hard.",
+                skill
+            )),
+        }
+    }
+}
+
+impl Skill {
+    // One in this many turns is picked at random among the safe ones.
+    fn blunder_chance(self) -> Option<u32> {
+        match self {
+            Skill::Easy => Some(4),
+            Skill::Normal => Some(15),
+            Skill::Hard => None,
+        }
+    }
+}
+
+/// The direction the second snake of `controller` takes this tick, if it is computer controlled
+/// and has anywhere to go.
+pub fn turn(controller: &mut Controller) -> Option<Direction> {
+    let skill = controller.second.as_ref()?.ai?;
+    let blunder = skill
+        .blunder_chance()
+        .is_some_and(|chance| controller.rng.gen_ratio(1, chance));
+
+    let second = controller.second.as_ref()?;
+    let view = BoardView::new(controller, &second.snake);
+    let safe = safe_directions(&view);
+    let planned = towards_apple(&view).or_else(|| match skill {
+        Skill::Hard => roomiest(&view),
+        _ => safe.first().copied(),
+    });
+
+    if blunder && !safe.is_empty() {
+        Some(safe[controller.rng.gen_range(0..safe.len())])
+    } else {
+        planned
+    }
+}
+
+/// The directions the snake can move in without dying right away.
+pub fn safe_directions(view: &BoardView) -> Vec<Direction> {
+    let head = &view.snake().elements[0];
+
+    DIRECTIONS
+        .iter()
+        .copied()
+        .filter(|&direction| neighbour(view, head, direction).is_some())
+        .collect()
+}
+
+/// Of the safe directions, the one that leaves the most cells to reach.
+pub fn roomiest(view: &BoardView) -> Option<Direction> {
+    safe_directions(view)
+        .into_iter()
+        .max_by_key(|&direction| room(view, direction))
+}
+
+/// The first step on the shortest path from the head to an apple worth eating.
+pub fn towards_apple(view: &BoardView) -> Option<Direction> {
+    let targets: Vec<&CanvasSpace> = view
+        .apples()
+        .iter()
+        .filter(|apple| apple.flash_ticks == 0 && apple.apple_type != AppleType::Poison)
+        .map(|apple| &apple.position)
+        .collect();
+
+    let head = view.snake().elements.front()?;
+    let mut seen = Seen::new(view);
+    seen.insert(head);
+    let mut queue: VecDeque<(CanvasSpace, Direction)> = VecDeque::new();
+
+    for &direction in &DIRECTIONS {
+        if let Some(cell) = neighbour(view, head, direction) {
+            seen.insert(&cell);
+            queue.push_back((cell, direction));
+        }
+    }
+
+    while let Some((cell, first)) = queue.pop_front() {
+        if targets.contains(&&cell) {
+            return Some(first);
+        }
+
+        for &direction in &DIRECTIONS {
+            if let Some(next) = neighbour(view, &cell, direction) {
+                if seen.insert(&next) {
+                    queue.push_back((next, first));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// How many cells can still be reached after stepping towards `direction`.
+fn room(view: &BoardView, direction: Direction) -> usize {
+    let start = match neighbour(view, &view.snake().elements[0], direction) {
+        Some(start) => start,
+        None => return 0,
+    };
+
+    let mut seen = Seen::new(view);
+    seen.insert(&start);
+    let mut queue = VecDeque::from(vec![start]);
+    let mut count = 1;
+
+    while let Some(cell) = queue.pop_front() {
+        for &direction in &DIRECTIONS {
+            if let Some(next) = neighbour(view, &cell, direction) {
+                if seen.insert(&next) {
+                    queue.push_back(next);
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    count
+}
+
+// The cells a search has already been to, one flag per cell of the board.
+struct Seen {
+    width: usize,
+    cells: Vec<bool>,
+}
+
+impl Seen {
+    fn new(view: &BoardView) -> Self {
+        let (width, height) = (
+            view.bounds().width() as usize,
+            view.bounds().height() as usize,
+        );
+
+        Seen {
+            width,
+            cells: vec![false; width * height],
+        }
+    }
+
+    // Marks `cell`, returning whether it is new. Only cells on the board are ever passed in.
+    fn insert(&mut self, cell: &CanvasSpace) -> bool {
+        let (x, y) = cell.0;
+        let index = y as usize * self.width + x as usize;
+
+        !std::mem::replace(&mut self.cells[index], true)
+    }
+}
+
+// The cell next to `cell` towards `direction`, unless it is off the board or taken by a wall,
+// an obstacle or either snake.
+fn neighbour(view: &BoardView, cell: &CanvasSpace, direction: Direction) -> Option<CanvasSpace> {
+    let probe = Snake {
+        elements: VecDeque::from(vec![cell.clone()]),
+        current_direction: direction,
+    };
+    let next = if view.wraps() {
+        wrapped_head(&probe, view.bounds())
+    } else {
+        compute_new_head(&probe, view.bounds())
+    }?;
+
+    Some(next).filter(|next| !view.is_blocked(next))
+}
@@ -0,0 +1,149 @@
+// Benchmark for `--bench`: runs the game logic as fast as it goes, without drawing, and times
+// every tick. A simple autopilot keeps the snake alive, and a fresh run starts whenever it dies.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::{
+    compute_new_head, continue_game_logic, CanvasSpace, Controller, Direction, GameScreen, Snake,
+};
+
+pub const BENCH_DURATION: Duration = Duration::from_secs(30);
+pub const BENCH_SNAKE_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub ticks: u64,
+    pub min: Duration,
+    pub max: Duration,
+    // Time spent inside the game logic, which is what the tick rate is worked out from.
+    pub total: Duration,
+}
+
+impl Report {
+    pub fn avg(&self) -> Duration {
+        match self.ticks {
+            0 => Duration::from_secs(0),
+            ticks => self.total / ticks as u32,
+        }
+    }
+
+    pub fn ticks_per_sec(&self) -> f64 {
+        match self.total.as_secs_f64() {
+            secs if secs > 0.0 => self.ticks as f64 / secs,
+            _ => 0.0,
+        }
+    }
+}
+
+pub fn report_line(report: &Report) -> String {
+    format!(
+        "{} ticks, min/avg/max {}/{}/{} µs per tick, {:.0} ticks/sec",
+        report.ticks,
+        report.min.as_micros(),
+        report.avg().as_micros(),
+        report.max.as_micros(),
+        report.ticks_per_sec()
+    )
+}
+
+/// Runs the game logic for `duration` of wall-clock time with a snake of `length`, using
+/// `new_controller` for every run.
+pub fn run(duration: Duration, length: usize, new_controller: impl Fn() -> Controller) -> Report {
+    let mut report = Report {
+        ticks: 0,
+        min: Duration::from_secs(u64::MAX),
+        max: Duration::from_secs(0),
+        total: Duration::from_secs(0),
+    };
+
+    let started = Instant::now();
+    let mut controller = bench_controller(&new_controller, length);
+
+    while started.elapsed() < duration {
+        if controller.screen == GameScreen::GameOver {
+            controller = bench_controller(&new_controller, length);
+        }
+
+        steer(&mut controller);
+
+        let tick_started = Instant::now();
+        continue_game_logic(&mut controller);
+        let tick = tick_started.elapsed();
+
+        // Nobody plays these back, so they would only pile up.
+        controller.cues.clear();
+
+        report.ticks += 1;
+        report.min = report.min.min(tick);
+        report.max = report.max.max(tick);
+        report.total += tick;
+    }
+
+    if report.ticks == 0 {
+        report.min = Duration::from_secs(0);
+    }
+
+    report
+}
+
+fn bench_controller(new_controller: &impl Fn() -> Controller, length: usize) -> Controller {
+    let mut controller = new_controller();
+    controller.snake.elements = coiled_snake(&controller, length);
+    controller.snake.current_direction = Direction::Right;
+    controller.apples.clear();
+    controller.spawn_apple_next_tick = true;
+    controller
+}
+
+// A straight snake of 50 doesn't fit on the default board, so it is folded back and forth
+// through the top rows instead, with its head at the end of the fold.
+fn coiled_snake(controller: &Controller, length: usize) -> VecDeque<CanvasSpace> {
+    let width = controller.bounds.width() as usize;
+
+    (0..length.min(width * controller.bounds.height() as usize))
+        .map(|i| {
+            let (row, column) = (i / width, i % width);
+            let x = if row % 2 == 0 {
+                column
+            } else {
+                width - 1 - column
+            };
+            CanvasSpace((x as i32, row as i32))
+        })
+        .rev()
+        .collect()
+}
+
+// Keeps going straight while that is safe and otherwise turns to any free neighbour.
+fn steer(controller: &mut Controller) {
+    let current = controller.snake.current_direction;
+
+    let safe = |direction: Direction| {
+        let snake = Snake {
+            elements: controller.snake.elements.iter().take(1).cloned().collect(),
+            current_direction: direction,
+        };
+
+        compute_new_head(&snake, &controller.bounds).is_some_and(|head| {
+            !controller.snake.elements.contains(&head) && !controller.obstacles.contains(&head)
+        })
+    };
+
+    if safe(current) {
+        return;
+    }
+
+    if let Some(direction) = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ]
+    .iter()
+    .copied()
+    .find(|direction| safe(*direction))
+    {
+        controller.snake.current_direction = direction;
+    }
+}
@@ -0,0 +1,105 @@
+// Built-in arrangements of walls for --layout. A board is the arena's bounds plus the walls on
+// it, and walls are plain obstacles: the snake dies on them and nothing spawns on them.
+
+use std::str::FromStr;
+
+use rand::seq::IteratorRandom;
+
+use crate::rng::GameRng;
+use crate::{CanvasBounds, CanvasSpace, Controller};
+
+// Walls stay at least this many cells away from every segment of the starting snake, so that a
+// layout never boxes it in.
+const START_CLEARANCE: i32 = 2;
+
+// Scatter puts a wall on one in this many cells.
+const SCATTER_DENSITY: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    // A bar across the middle of the board each way.
+    Cross,
+    // A 2x2 block in each quarter of the board.
+    Pillars,
+    // Single walls on random cells.
+    Scatter,
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        match layout {
+            "cross" => Ok(Layout::Cross),
+            "pillars" => Ok(Layout::Pillars),
+            "scatter" => Ok(Layout::Scatter),
+            _ => Err(format!(
+                "Unknown layout `{}`, expected cross, pillars or scatter.",
+                layout
+            )),
+        }
+    }
+}
+
+impl Layout {
+    /// The walls of this layout on a board of `bounds`. Only where scattered walls go depends on
+    /// `rng`, never how many there are.
+    pub fn walls(self, bounds: &CanvasBounds, rng: &mut GameRng) -> Vec<CanvasSpace> {
+        let (width, height) = (bounds.width() as i32, bounds.height() as i32);
+
+        let walls: Vec<CanvasSpace> = match self {
+            Layout::Cross => {
+                let across = (width / 4..width - width / 4).map(|x| CanvasSpace((x, height / 2)));
+                let down = (height / 4..height - height / 4).map(|y| CanvasSpace((width / 2, y)));
+
+                across.chain(down).collect()
+            }
+            Layout::Pillars => {
+                let corners = [
+                    (width / 4, height / 4),
+                    (width - width / 4 - 2, height / 4),
+                    (width / 4, height - height / 4 - 2),
+                    (width - width / 4 - 2, height - height / 4 - 2),
+                ];
+
+                corners
+                    .iter()
+                    .flat_map(|&(x, y)| {
+                        [(0, 0), (1, 0), (0, 1), (1, 1)]
+                            .iter()
+                            .map(move |(dx, dy)| CanvasSpace((x + dx, y + dy)))
+                    })
+                    .collect()
+            }
+            Layout::Scatter => {
+                let count = bounds.iter_cells().count() / SCATTER_DENSITY;
+                bounds.iter_cells().choose_multiple(rng, count)
+            }
+        };
+
+        let mut unique: Vec<CanvasSpace> = Vec::with_capacity(walls.len());
+        for wall in walls {
+            if bounds.contains(&wall) && !unique.contains(&wall) {
+                unique.push(wall);
+            }
+        }
+        unique
+    }
+}
+
+/// Adds the walls of `layout` to the obstacles, leaving out those too close to the snake. Call
+/// it after placing the snake and before anything else goes on the board.
+pub fn place_layout(controller: &mut Controller, layout: Layout) {
+    let walls = layout.walls(&controller.bounds, &mut controller.rng);
+
+    for wall in walls {
+        let near_snake = controller.snake.elements.iter().any(|segment| {
+            (segment.0 .0 - wall.0 .0).abs() <= START_CLEARANCE
+                && (segment.0 .1 - wall.0 .1).abs() <= START_CLEARANCE
+        });
+
+        if !near_snake && !controller.obstacles.contains(&wall) {
+            controller.obstacles.push(wall);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Whatever decides where the player's snake goes next. The keyboard is one such controller,
+// the bots of --bot and --demo are others, and programs using this crate can register their own.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::ai;
+use crate::keys::Action;
+use crate::{action_direction, Apple, CanvasBounds, CanvasSpace, Controller, Direction, Snake};
+
+/// The board as one snake sees it when it has to make a move.
+pub struct BoardView<'a> {
+    controller: &'a Controller,
+    snake: &'a Snake,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(controller: &'a Controller, snake: &'a Snake) -> Self {
+        BoardView { controller, snake }
+    }
+
+    /// The snake that is about to move.
+    pub fn snake(&self) -> &Snake {
+        self.snake
+    }
+
+    pub fn bounds(&self) -> &CanvasBounds {
+        &self.controller.bounds
+    }
+
+    pub fn apples(&self) -> &[Apple] {
+        &self.controller.apples
+    }
+
+    /// Whether the snake wraps around at the edges instead of dying there.
+    pub fn wraps(&self) -> bool {
+        self.controller.wrap
+    }
+
+    /// What the last key press asked for, if it was a turn.
+    pub fn requested(&self) -> Option<Action> {
+        match self.controller.last_event {
+            Some(crossterm::event::Event::Key(key)) => {
+                self.controller.keys.playing_action(key.code)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether moving onto `cell` would be deadly: it is off the board, an obstacle or part of
+    /// either snake.
+    pub fn is_blocked(&self, cell: &CanvasSpace) -> bool {
+        !self.bounds().contains(cell)
+            || self.controller.obstacles.contains(cell)
+            || self.controller.snake.elements.contains(cell)
+            || self
+                .controller
+                .second
+                .as_ref()
+                .is_some_and(|second| second.snake.elements.contains(cell))
+    }
+}
+
+/// Decides the direction of a snake once per tick. Turning back onto the neck is ignored, like
+/// it is for key presses, and `Direction::Stop` keeps the snake waiting.
+pub trait SnakeController {
+    fn next_move(&mut self, view: &BoardView) -> Direction;
+}
+
+/// Turns where the arrow keys say, and otherwise keeps going.
+pub struct Keyboard;
+
+impl SnakeController for Keyboard {
+    fn next_move(&mut self, view: &BoardView) -> Direction {
+        view.requested()
+            .and_then(action_direction)
+            .unwrap_or(view.snake().current_direction)
+    }
+}
+
+/// Takes the shortest way to the nearest apple, and without one turns to where there is the
+/// most room.
+pub struct Greedy;
+
+impl SnakeController for Greedy {
+    fn next_move(&mut self, view: &BoardView) -> Direction {
+        ai::towards_apple(view)
+            .or_else(|| ai::roomiest(view))
+            .unwrap_or(view.snake().current_direction)
+    }
+}
+
+/// A controller that a `Controller` can hold on to, compared by identity.
+#[derive(Clone)]
+pub struct Bot {
+    name: &'static str,
+    controller: Arc<Mutex<Box<dyn SnakeController + Send>>>,
+}
+
+impl Bot {
+    pub fn new(name: &'static str, controller: Box<dyn SnakeController + Send>) -> Self {
+        Bot {
+            name,
+            controller: Arc::new(Mutex::new(controller)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn next_move(&self, view: &BoardView) -> Direction {
+        match self.controller.lock() {
+            Ok(mut controller) => controller.next_move(view),
+            // A bot that panicked earlier doesn't get another go.
+            Err(_) => view.snake().current_direction,
+        }
+    }
+}
+
+impl fmt::Debug for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bot({})", self.name)
+    }
+}
+
+impl PartialEq for Bot {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.controller, &other.controller)
+    }
+}
+
+/// Builds a fresh controller for each run that asks for it.
+pub type MakeController = fn() -> Box<dyn SnakeController + Send>;
+
+/// The controllers --bot can pick by name.
+pub struct Registry {
+    bots: Vec<(&'static str, MakeController)>,
+}
+
+impl Registry {
+    /// Adds `make` under `name`, replacing a controller registered under it before.
+    pub fn register(&mut self, name: &'static str, make: MakeController) {
+        self.bots.retain(|(registered, _)| *registered != name);
+        self.bots.push((name, make));
+    }
+
+    pub fn create(&self, name: &str) -> Result<Bot, String> {
+        self.bots
+            .iter()
+            .find(|(registered, _)| *registered == name)
+            .map(|(registered, make)| Bot::new(registered, make()))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown bot `{}`, expected {}.",
+                    name,
+                    self.names().join(", ")
+                )
+            })
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.bots.iter().map(|(name, _)| *name).collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry { bots: Vec::new() };
+        registry.register("keyboard", || Box::new(Keyboard));
+        registry.register("greedy", || Box::new(Greedy));
+        registry
+    }
+}
@@ -0,0 +1,307 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{
+    Apple, AppleType, CanvasBounds, CanvasSpace, Controller, GameScreen, Player, CANVAS_HEIGHT,
+    CANVAS_WIDTH,
+};
+
+/// How many frames may queue up for a single spectator before new ones are dropped.
+pub const CLIENT_BACKLOG: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSnapshot {
+    pub tick: u64,
+    pub score: u32,
+    pub game_over: bool,
+    pub snake: Vec<CanvasSpace>,
+    pub apples: Vec<(CanvasSpace, char)>,
+    pub bounds: CanvasBounds,
+    pub obstacles: Vec<CanvasSpace>,
+    // Empty unless two snakes are playing.
+    pub second: Vec<CanvasSpace>,
+}
+
+impl FrameSnapshot {
+    pub fn capture(tick: u64, controller: &Controller) -> Self {
+        FrameSnapshot {
+            tick,
+            score: controller.score,
+            game_over: controller.screen == GameScreen::GameOver,
+            snake: controller.snake.elements.iter().cloned().collect(),
+            apples: controller
+                .apples
+                .iter()
+                .map(|apple| (apple.position.clone(), apple.apple_type.glyph()))
+                .collect(),
+            bounds: controller.bounds,
+            obstacles: controller.obstacles.clone(),
+            second: controller.second.as_ref().map_or(Vec::new(), |second| {
+                second.snake.elements.iter().cloned().collect()
+            }),
+        }
+    }
+
+    pub fn apply(&self, controller: &mut Controller) {
+        controller.score = self.score;
+
+        // Quitting is up to the spectator, so only whether the round is over is taken over.
+        if controller.screen != GameScreen::Closed {
+            controller.screen = if self.game_over {
+                GameScreen::GameOver
+            } else {
+                GameScreen::Playing
+            };
+        }
+
+        controller.bounds = self.bounds;
+        controller.obstacles = self.obstacles.clone();
+        controller.snake.elements = self.snake.iter().cloned().collect();
+
+        if self.second.is_empty() {
+            controller.second = None;
+        } else {
+            controller
+                .second
+                .get_or_insert_with(|| Player::new(Default::default()))
+                .snake
+                .elements = self.second.iter().cloned().collect();
+        }
+
+        controller.apples = self
+            .apples
+            .iter()
+            .map(|(position, apple_char)| {
+                Apple::new(position.clone(), AppleType::from_glyph(*apple_char))
+            })
+            .collect();
+    }
+}
+
+// Frames travel as one line each:
+// `<tick> <score> <game over> <apples> <snake> <width>,<height>,<inset> <walls> <second snake>`,
+// where the apples are a `;`-separated list of `x,y,char` and the others `;`-separated lists of
+// `x,y` cells, with `-` for an empty list. Ghosts recorded before the board was sent along are
+// just the first five fields and are read as played on the default board.
+impl fmt::Display for FrameSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} ", self.tick, self.score, self.game_over as u8)?;
+
+        let apples: Vec<String> = self
+            .apples
+            .iter()
+            .map(|(position, apple_char)| {
+                format!("{},{},{}", position.0 .0, position.0 .1, apple_char)
+            })
+            .collect();
+
+        if apples.is_empty() {
+            write!(f, "- ")?;
+        } else {
+            write!(f, "{} ", apples.join(";"))?;
+        }
+
+        write!(
+            f,
+            "{} {},{},{} {} {}",
+            cells(&self.snake),
+            self.bounds.width(),
+            self.bounds.height(),
+            self.bounds.inset(),
+            cells(&self.obstacles),
+            cells(&self.second),
+        )
+    }
+}
+
+fn cells(cells: &[CanvasSpace]) -> String {
+    if cells.is_empty() {
+        return String::from("-");
+    }
+
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| format!("{},{}", cell.0 .0, cell.0 .1))
+        .collect();
+
+    cells.join(";")
+}
+
+pub(crate) fn parse_cells(cells: &str) -> Result<Vec<CanvasSpace>, String> {
+    if cells == "-" {
+        return Ok(Vec::new());
+    }
+
+    cells.split(';').map(parse_cell).collect()
+}
+
+pub(crate) fn parse_bounds(bounds: &str) -> Result<CanvasBounds, String> {
+    let bounds: Vec<u32> = bounds
+        .split(',')
+        .map(|n| n.parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid board size: {}", e))?;
+
+    match bounds.as_slice() {
+        [width, height, inset] => Ok(CanvasBounds::new(*width, *height).with_inset(*inset)),
+        _ => Err(String::from("Expected a width, height and inset.")),
+    }
+}
+
+impl FromStr for FrameSnapshot {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.trim_end().split(' ').collect();
+
+        let (bounds, obstacles, second) = match fields.len() {
+            5 => (
+                CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT),
+                Vec::new(),
+                Vec::new(),
+            ),
+            8 => (
+                parse_bounds(fields[5])?,
+                parse_cells(fields[6])?,
+                parse_cells(fields[7])?,
+            ),
+            count => return Err(format!("Expected 8 fields, got {}.", count)),
+        };
+
+        let apples = if fields[3] == "-" {
+            Vec::new()
+        } else {
+            fields[3]
+                .split(';')
+                .map(|apple| {
+                    let parts: Vec<&str> = apple.splitn(3, ',').collect();
+                    let apple_char = parts
+                        .get(2)
+                        .and_then(|part| part.chars().next())
+                        .ok_or("Apple is missing its glyph.")?;
+
+                    Ok((parse_cell(&parts[..2].join(","))?, apple_char))
+                })
+                .collect::<Result<_, String>>()?
+        };
+
+        let snake = parse_cells(fields[4])?;
+
+        Ok(FrameSnapshot {
+            tick: fields[0]
+                .parse()
+                .map_err(|e| format!("Invalid tick: {}", e))?,
+            score: fields[1]
+                .parse()
+                .map_err(|e| format!("Invalid score: {}", e))?,
+            game_over: fields[2] == "1",
+            snake,
+            apples,
+            bounds,
+            obstacles,
+            second,
+        })
+    }
+}
+
+pub(crate) fn parse_cell(cell: &str) -> Result<CanvasSpace, String> {
+    let mut coordinates = cell.split(',').map(|c| c.parse::<i32>());
+
+    match (coordinates.next(), coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok(CanvasSpace((x, y))),
+        _ => Err(format!("Invalid cell `{}`.", cell)),
+    }
+}
+
+/// Accepts spectator connections and fans each frame out to them without ever blocking the
+/// caller. Every client gets its own writer thread, so a slow client only loses frames.
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<SyncSender<String>>>>,
+}
+
+impl Broadcaster {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted_clients = Arc::clone(&clients);
+        let _ = thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let frame_rx = subscribe(&accepted_clients);
+                let _ = thread::spawn(move || stream_frames(stream, frame_rx));
+            }
+        });
+
+        Ok(Broadcaster { clients })
+    }
+
+    /// Adds a spectator that reads the frame lines from the returned receiver instead of a
+    /// socket. It gets the same backlog as one that connected.
+    pub fn subscribe(&self) -> Receiver<String> {
+        subscribe(&self.clients)
+    }
+
+    pub fn send(&self, frame: &FrameSnapshot) {
+        let line = format!("{}\n", frame);
+
+        if let Ok(ref mut clients) = self.clients.as_ref().lock() {
+            // A full queue means the client is lagging behind, so this frame is skipped for it.
+            // Disconnected clients are forgotten.
+            clients.retain(|client| {
+                !matches!(
+                    client.try_send(line.clone()),
+                    Err(TrySendError::Disconnected(_))
+                )
+            });
+        }
+    }
+}
+
+fn subscribe(clients: &Mutex<Vec<SyncSender<String>>>) -> Receiver<String> {
+    let (frame_tx, frame_rx) = mpsc::sync_channel(CLIENT_BACKLOG);
+
+    if let Ok(ref mut clients) = clients.lock() {
+        clients.push(frame_tx);
+    }
+
+    frame_rx
+}
+
+fn stream_frames(mut stream: TcpStream, frames: Receiver<String>) {
+    stream.set_nodelay(true).ok();
+
+    for line in frames {
+        if stream.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+/// Connects to a broadcasting game. The returned receiver disconnects once the broadcaster
+/// goes away.
+pub fn spectate(addr: impl ToSocketAddrs) -> io::Result<Receiver<FrameSnapshot>> {
+    let stream = TcpStream::connect(addr)?;
+    let (frame_tx, frame_rx) = mpsc::channel();
+
+    let _ = thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let frame = match line {
+                Ok(line) => line.parse::<FrameSnapshot>(),
+                Err(_) => break,
+            };
+
+            if let Ok(frame) = frame {
+                if frame_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(frame_rx)
+}
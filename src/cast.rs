@@ -0,0 +1,84 @@
+use std::io::{self, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::json;
+
+/// Tees everything written to `inner` into an asciicast v2 recording. Output is collected until
+/// the next flush and then stored as one event, so a frame becomes one line of the cast and
+/// the terminal sees exactly the same writes and flushes as without recording.
+pub struct CastWriter<W: Write, F: Write> {
+    inner: W,
+    cast: F,
+    start: Instant,
+    pending: Vec<u8>,
+}
+
+impl<W: Write, F: Write> CastWriter<W, F> {
+    /// Starts the recording by writing the header for a terminal of the given size.
+    pub fn new(
+        inner: W,
+        mut cast: F,
+        (width, height): (u16, u16),
+        title: &str,
+    ) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+
+        writeln!(
+            cast,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": {}}}",
+            width,
+            height,
+            timestamp,
+            json::quote(title)
+        )?;
+
+        Ok(CastWriter {
+            inner,
+            cast,
+            start: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    fn record_pending(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let output = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+
+        writeln!(
+            self.cast,
+            "[{:.6}, \"o\", {}]",
+            self.start.elapsed().as_secs_f64(),
+            json::quote(&output)
+        )?;
+
+        self.cast.flush()
+    }
+}
+
+impl<W: Write, F: Write> Write for CastWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.pending.extend_from_slice(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.record_pending()
+    }
+}
+
+// Output that was never flushed still ends up in the recording, e.g. when the game bails out
+// with an error.
+impl<W: Write, F: Write> Drop for CastWriter<W, F> {
+    fn drop(&mut self) {
+        let _ = self.record_pending();
+    }
+}
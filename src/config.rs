@@ -0,0 +1,163 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+
+use crate::json::Value;
+use crate::keys::{parse_key, Action};
+use crate::online::Endpoint;
+use crate::toml;
+
+/// Settings from `config.toml`. Everything is optional: what the file leaves out keeps its
+/// default, and command line flags override what it sets.
+///
+/// ```toml
+/// width = 60
+/// height = 30
+/// speed = 15
+/// snake-char = "██"
+/// apple-char = "🍒"
+/// color = false
+/// theme = "matrix"
+/// colorblind = true
+/// sound = true
+/// online-leaderboard = "http://scores.example.com/snake"
+///
+/// [keys]
+/// up = ["w", "k"]
+/// quit = "Esc"
+/// ```
+///
+/// The key table may also be called `[keybindings]`. Besides the names `Action` parses,
+/// `boost` is another name for `sprint`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    pub speed: Option<u16>,
+    pub snake_char: Option<String>,
+    pub apple_char: Option<String>,
+    // `false` draws without colors, like --no-color.
+    pub color: Option<bool>,
+    // A built-in theme or a theme file, like --theme.
+    pub theme: Option<String>,
+    // Like --colorblind.
+    pub colorblind: Option<bool>,
+    // Like --sound.
+    pub sound: Option<bool>,
+    // Where finished runs are submitted to, see `online`. Nothing is sent without it.
+    pub online_leaderboard: Option<Endpoint>,
+    // Bound on top of the default keys, in file order.
+    pub keys: Vec<(KeyCode, Action)>,
+}
+
+impl Config {
+    /// Reads the config at `path`. Without a file everything keeps its default.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(toml) => {
+                Config::from_toml(&toml).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        let document = toml::parse(toml)?;
+        let mut config = Config::default();
+
+        if let Value::Object(fields) = &document {
+            for (key, value) in fields {
+                match key.as_str() {
+                    "width" => config.width = Some(size(key, value)?),
+                    "height" => config.height = Some(size(key, value)?),
+                    "speed" => config.speed = Some(size(key, value)?),
+                    "snake-char" => config.snake_char = Some(text(key, value)?),
+                    "apple-char" => config.apple_char = Some(text(key, value)?),
+                    "color" => match value {
+                        Value::Bool(color) => config.color = Some(*color),
+                        _ => return Err(String::from("`color` must be true or false.")),
+                    },
+                    "theme" => config.theme = Some(text(key, value)?),
+                    "colorblind" => match value {
+                        Value::Bool(colorblind) => config.colorblind = Some(*colorblind),
+                        _ => return Err(String::from("`colorblind` must be true or false.")),
+                    },
+                    "sound" => match value {
+                        Value::Bool(sound) => config.sound = Some(*sound),
+                        _ => return Err(String::from("`sound` must be true or false.")),
+                    },
+                    "online-leaderboard" => {
+                        config.online_leaderboard = Some(text(key, value)?.parse()?)
+                    }
+                    "keys" | "keybindings" => config.keys = keys(value)?,
+                    _ => return Err(format!("Unknown setting `{}`.", key)),
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rusty-snake/config.toml`, falling back to `~/.config/rusty-snake`.
+pub fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// Where theme files go, see `theme`: `themes` next to `config.toml`.
+pub fn themes_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("themes"))
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+    Some(config_home.join("rusty-snake"))
+}
+
+fn size(key: &str, value: &Value) -> Result<u16, String> {
+    value
+        .as_u32()
+        .filter(|size| (1..=u32::from(u16::MAX)).contains(size))
+        .map(|size| size as u16)
+        .ok_or(format!("`{}` must be a positive number.", key))
+}
+
+fn text(key: &str, value: &Value) -> Result<String, String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or(format!("`{}` must be a string.", key))
+}
+
+// Every action takes a key or a list of keys.
+fn keys(table: &Value) -> Result<Vec<(KeyCode, Action)>, String> {
+    let fields = match table {
+        Value::Object(fields) => fields,
+        _ => return Err(String::from("`keys` and `keybindings` must be a table.")),
+    };
+
+    let mut bindings = Vec::new();
+
+    for (action, keys) in fields {
+        let action: Action = action.parse()?;
+        let keys = match keys {
+            Value::Array(keys) => keys.iter().collect(),
+            key => vec![key],
+        };
+
+        for key in keys {
+            let key = key
+                .as_str()
+                .ok_or(format!("The keys of `{:?}` must be strings.", action))?;
+            bindings.push((parse_key(key)?, action));
+        }
+    }
+
+    Ok(bindings)
+}
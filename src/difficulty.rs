@@ -0,0 +1,98 @@
+// Presets for --difficulty. Each one is a bundle of settings that the individual flags can still
+// override.
+
+use std::str::FromStr;
+
+use crate::speed::{Curve, Ramp};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Normal,
+    Difficulty::Hard,
+    Difficulty::Insane,
+];
+
+/// The settings a difficulty stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preset {
+    pub ticks_per_sec: u16,
+    // In terminal columns and rows, like --width and --height.
+    pub canvas_size: (u16, u16),
+    pub wrap: bool,
+    pub apple_count: usize,
+    pub ramp: Option<Ramp>,
+}
+
+impl Difficulty {
+    pub fn preset(self) -> Preset {
+        match self {
+            Difficulty::Easy => Preset {
+                ticks_per_sec: 6,
+                canvas_size: (60, 40),
+                wrap: true,
+                apple_count: 3,
+                ramp: None,
+            },
+            Difficulty::Normal => Preset {
+                ticks_per_sec: 10,
+                canvas_size: (46, 46),
+                wrap: false,
+                apple_count: 1,
+                ramp: None,
+            },
+            Difficulty::Hard => Preset {
+                ticks_per_sec: 14,
+                canvas_size: (40, 30),
+                wrap: false,
+                apple_count: 1,
+                ramp: Some(Ramp::new(Curve::Linear)),
+            },
+            Difficulty::Insane => Preset {
+                ticks_per_sec: 20,
+                canvas_size: (30, 24),
+                wrap: false,
+                apple_count: 1,
+                ramp: Some(Ramp {
+                    curve: Curve::Exponential,
+                    cap: 40,
+                }),
+            },
+        }
+    }
+
+    /// The difficulty `step` places further along `DIFFICULTIES`, stopping at either end.
+    pub fn step(self, step: i8) -> Self {
+        let index = DIFFICULTIES
+            .iter()
+            .position(|&difficulty| difficulty == self)
+            .unwrap_or(0) as i32;
+        let index = (index + i32::from(step)).clamp(0, DIFFICULTIES.len() as i32 - 1);
+
+        DIFFICULTIES[index as usize]
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(difficulty: &str) -> Result<Self, Self::Err> {
+        match difficulty {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            "insane" => Ok(Difficulty::Insane),
+            _ => Err(format!(
+                "Unknown difficulty `{}`, expected easy, normal, hard or insane.",
+                difficulty
+            )),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// The level editor behind --edit: a cursor moved around the board with the arrow keys, placing
+// the tiles of a level file under it.
+
+use crossterm::event::KeyCode;
+
+use crate::level::{Level, Tile};
+use crate::CanvasSpace;
+
+/// What a key press in the editor asks the frontend for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorInput {
+    Save,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Editor {
+    pub level: Level,
+    pub cursor: CanvasSpace,
+    // Whether the level changed since it was loaded or last saved.
+    pub modified: bool,
+    // Quitting with unsaved changes takes a second `q`.
+    pub confirm_quit: bool,
+}
+
+impl Editor {
+    pub fn new(level: Level) -> Self {
+        Editor {
+            cursor: level.bounds.center(),
+            level,
+            modified: false,
+            confirm_quit: false,
+        }
+    }
+
+    /// Arrows move the cursor, space toggles a wall, `x` a cell without apples and `s` puts the
+    /// start under the cursor. Saving and quitting are up to the caller.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<EditorInput> {
+        if code != KeyCode::Char('q') && code != KeyCode::Esc {
+            self.confirm_quit = false;
+        }
+
+        let (x, y) = self.cursor.0;
+        let moved = match code {
+            KeyCode::Up => Some(CanvasSpace((x, y - 1))),
+            KeyCode::Down => Some(CanvasSpace((x, y + 1))),
+            KeyCode::Left => Some(CanvasSpace((x - 1, y))),
+            KeyCode::Right => Some(CanvasSpace((x + 1, y))),
+            _ => None,
+        };
+        if let Some(cell) = moved {
+            if self.level.bounds.contains(&cell) {
+                self.cursor = cell;
+            }
+            return None;
+        }
+
+        let tile = match code {
+            KeyCode::Char(' ') => Tile::Wall,
+            KeyCode::Char('x') => Tile::NoApples,
+            KeyCode::Char('s') => Tile::Spawn,
+            KeyCode::Char('w') => return Some(EditorInput::Save),
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.modified && !self.confirm_quit {
+                    self.confirm_quit = true;
+                    return None;
+                }
+                return Some(EditorInput::Quit);
+            }
+            _ => return None,
+        };
+
+        // Placing a wall or a cell without apples where there already is one takes it away.
+        let current = self.level.tile(&self.cursor);
+        let tile = if current == tile && tile != Tile::Spawn {
+            Tile::Empty
+        } else {
+            tile
+        };
+
+        if tile != current {
+            self.level.set(&self.cursor, tile);
+            self.modified = true;
+        }
+        None
+    }
+}
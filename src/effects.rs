@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+// How long a picked up speed change lasts, in wall-clock seconds.
+pub const EFFECT_SECONDS: u32 = 10;
+
+// Invincibility is over sooner, it would make for a dull game otherwise.
+pub const INVINCIBLE_SECONDS: u32 = 5;
+
+// Roughly one in this many eaten apples leaves a pickup behind.
+pub const PICKUP_CHANCE: u32 = 4;
+
+pub const PICKUPS: [Effect; 3] = [Effect::SpeedUp, Effect::SlowDown, Effect::Invincible];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    SpeedUp,
+    SlowDown,
+    // The snake passes through its own body and through obstacles, but not the walls.
+    Invincible,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    pub remaining_ticks: u32,
+}
+
+impl Effect {
+    pub fn glyph(self) -> char {
+        match self {
+            Effect::SpeedUp => '⚡',
+            Effect::SlowDown => '🐌',
+            Effect::Invincible => '⭐',
+        }
+    }
+
+    // For terminals that can't show emoji, see `glyphs::ASCII`.
+    pub fn ascii_glyph(self) -> char {
+        match self {
+            Effect::SpeedUp => '>',
+            Effect::SlowDown => '<',
+            Effect::Invincible => '*',
+        }
+    }
+
+    // Factor the tick rate is multiplied with while the effect is active.
+    pub fn speed_factor(self) -> f64 {
+        match self {
+            Effect::SpeedUp => 1.5,
+            Effect::SlowDown => 0.75,
+            Effect::Invincible => 1.0,
+        }
+    }
+
+    pub fn apple_points(self) -> u32 {
+        match self {
+            Effect::SpeedUp => 2,
+            Effect::SlowDown | Effect::Invincible => 1,
+        }
+    }
+
+    pub fn seconds(self) -> u32 {
+        match self {
+            Effect::SpeedUp | Effect::SlowDown => EFFECT_SECONDS,
+            Effect::Invincible => INVINCIBLE_SECONDS,
+        }
+    }
+
+    // The game runs faster or slower during the effect, so the tick count is scaled to keep
+    // the duration at `seconds` for a game running at `ticks_per_sec`.
+    pub fn duration_ticks(self, ticks_per_sec: u16) -> u32 {
+        (self.seconds() as f64 * f64::from(ticks_per_sec) * self.speed_factor()).round() as u32
+    }
+}
+
+impl ActiveEffect {
+    pub fn new(effect: Effect, ticks_per_sec: u16) -> Self {
+        ActiveEffect {
+            effect,
+            remaining_ticks: effect.duration_ticks(ticks_per_sec),
+        }
+    }
+
+    pub fn remaining_secs(&self, ticks_per_sec: u16) -> u32 {
+        let ticks_per_sec = f64::from(ticks_per_sec) * self.effect.speed_factor();
+        (self.remaining_ticks as f64 / ticks_per_sec).ceil() as u32
+    }
+}
+
+/// Starts `effect`. Effects don't stack, a new one replaces whatever was active before.
+pub fn apply_effect(effects: &mut Vec<ActiveEffect>, effect: Effect, ticks_per_sec: u16) {
+    effects.clear();
+    effects.push(ActiveEffect::new(effect, ticks_per_sec));
+}
+
+/// Counts down every active effect by one tick and drops the ones that ran out.
+pub fn tick_effects(effects: &mut Vec<ActiveEffect>) {
+    for active in effects.iter_mut() {
+        active.remaining_ticks = active.remaining_ticks.saturating_sub(1);
+    }
+
+    effects.retain(|active| active.remaining_ticks > 0);
+}
+
+pub fn is_active(effects: &[ActiveEffect], effect: Effect) -> bool {
+    effects.iter().any(|active| active.effect == effect)
+}
+
+pub fn apple_points(effects: &[ActiveEffect]) -> u32 {
+    effects
+        .iter()
+        .map(|active| active.effect.apple_points())
+        .product()
+}
+
+/// How long a tick lasts for a game running at `ticks_per_sec` while `effects` are active.
+pub fn tick_interval(ticks_per_sec: u16, effects: &[ActiveEffect]) -> Duration {
+    let speed_factor: f64 = effects
+        .iter()
+        .map(|active| active.effect.speed_factor())
+        .product();
+
+    Duration::from_secs_f64(1.0 / (f64::from(ticks_per_sec) * speed_factor))
+}
@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use crate::TICKS_PER_SEC;
+
+const BELL: &[u8] = b"\x07";
+
+/// Something noteworthy that happened during a tick. The game logic only raises cues, a
+/// `Feedback` implementation decides what they sound like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cue {
+    AppleEaten,
+    BonusCollected,
+    GameOver,
+    Victory,
+}
+
+pub trait Feedback {
+    fn cue(&mut self, cue: Cue);
+
+    // Called once per tick with the frame writer, so anything emitted is queued together with
+    // the frame instead of interleaving with its escape sequences.
+    fn tick(&mut self, writer: &mut dyn Write) -> io::Result<()>;
+}
+
+/// Ignores every cue.
+pub struct Silent;
+
+impl Feedback for Silent {
+    fn cue(&mut self, _cue: Cue) {}
+
+    fn tick(&mut self, _writer: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Rings the terminal bell. Patterns are scheduled in ticks, so nothing ever sleeps.
+#[derive(Debug)]
+pub struct TerminalBell {
+    // Ticks left until each pending bell rings.
+    pending: Vec<u32>,
+    ticks_per_sec: u16,
+}
+
+impl TerminalBell {
+    pub fn new() -> Self {
+        TerminalBell::with_tick_rate(TICKS_PER_SEC)
+    }
+
+    // For games running at another speed, so that the patterns keep their timing.
+    pub fn with_tick_rate(ticks_per_sec: u16) -> Self {
+        TerminalBell {
+            pending: Vec::new(),
+            ticks_per_sec,
+        }
+    }
+
+    // Offsets of the individual bells of a cue, in milliseconds.
+    fn pattern(cue: Cue) -> &'static [u32] {
+        match cue {
+            Cue::AppleEaten => &[0],
+            Cue::BonusCollected => &[0, 100],
+            Cue::GameOver => &[0, 150, 300],
+            Cue::Victory => &[0, 100, 200, 300, 400],
+        }
+    }
+}
+
+impl Default for TerminalBell {
+    fn default() -> Self {
+        TerminalBell::new()
+    }
+}
+
+impl Feedback for TerminalBell {
+    fn cue(&mut self, cue: Cue) {
+        let ticks_per_sec = u32::from(self.ticks_per_sec);
+        let ticks = |millis: u32| (millis * ticks_per_sec + 500) / 1000;
+
+        self.pending.extend(
+            TerminalBell::pattern(cue)
+                .iter()
+                .map(|&millis| ticks(millis)),
+        );
+    }
+
+    fn tick(&mut self, writer: &mut dyn Write) -> io::Result<()> {
+        let due = self.pending.iter().filter(|&&ticks| ticks == 0).count();
+
+        // Bells due on the same tick would blur into one anyway.
+        if due > 0 {
+            writer.write_all(BELL)?;
+        }
+
+        self.pending.retain(|&ticks| ticks > 0);
+        for ticks in self.pending.iter_mut() {
+            *ticks -= 1;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::broadcast::FrameSnapshot;
+use crate::{data_dir, CanvasSpace, Controller};
+
+/// The recorded positions of a past run, one frame per tick the snake moved. Stored in the
+/// same line format the broadcaster sends.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ghost {
+    pub frames: Vec<FrameSnapshot>,
+}
+
+impl Ghost {
+    /// Reads a recording. A missing file means there is no ghost yet.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let frames = text
+            .lines()
+            .map(|line| line.parse::<FrameSnapshot>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(Some(Ghost { frames }))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let lines: String = self
+            .frames
+            .iter()
+            .map(|frame| format!("{}\n", frame))
+            .collect();
+
+        fs::write(path, lines)
+    }
+
+    /// Records `controller` if the snake moved since the last recorded frame.
+    pub fn record(&mut self, controller: &Controller) {
+        if controller.stats.ticks > self.frames.len() as u64 {
+            self.frames
+                .push(FrameSnapshot::capture(controller.stats.ticks, controller));
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.frames.last().map_or(0, |frame| frame.score)
+    }
+
+    /// Where the ghost's snake was after the same number of moves as `controller`. Once the
+    /// recording is over, the ghost stays where it ended.
+    pub fn snake_at(&self, controller: &Controller) -> Option<&[CanvasSpace]> {
+        let index = (controller.stats.ticks as usize).checked_sub(1)?;
+
+        self.frames
+            .get(index)
+            .or_else(|| self.frames.last())
+            .map(|frame| frame.snake.as_slice())
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("ghost.txt"))
+}
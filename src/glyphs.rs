@@ -0,0 +1,277 @@
+use crate::effects::Effect;
+use crate::theme::Theme;
+use crate::{AppleType, CanvasSpace, Direction};
+
+// Every canvas cell is two terminal columns wide, so every glyph has to be as well.
+pub const CELL_WIDTH: usize = 2;
+
+pub const DEFAULT_SNAKE: &str = "██";
+
+// The two shades of the optional checkerboard background, one per canvas cell.
+pub const GRID_SHADES: [&str; 2] = ["░░", "  "];
+
+/// What the board around the snake is drawn with, for terminals that show Unicode and for
+/// those that only show ASCII.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Charset {
+    // The vertical and the horizontal line, then the corners from top left to bottom right.
+    pub border: [char; 6],
+    // The snake unless --snake-char says otherwise.
+    pub snake: &'static str,
+    pub obstacle: &'static str,
+    // A cell apples never spawn on, only shown in the level editor.
+    pub no_apples: &'static str,
+    pub portal: &'static str,
+    pub sprint: &'static str,
+    pub grid: [&'static str; 2],
+    // The filled and the empty part of a bar.
+    pub bar: [char; 2],
+    pub logo: &'static str,
+    // For --colorblind, which tells things apart by shape and brightness rather than by color:
+    // each player's snake alternates between two glyphs of its own, and the apple types are
+    // fresh, poison and golden.
+    pub patterns: [[&'static str; 2]; 2],
+    pub marked_apples: [&'static str; 3],
+    // The head of a moving snake, pointing up, down, left and right.
+    pub heads: [&'static str; 4],
+    // Whether apples and pickups are drawn as emoji and --smooth draws half blocks. Without
+    // Unicode they are ASCII characters, and the snake moves a whole cell at a time.
+    pub unicode: bool,
+}
+
+pub const UNICODE: Charset = Charset {
+    border: ['│', '─', '╭', '╮', '╰', '╯'],
+    snake: DEFAULT_SNAKE,
+    obstacle: "▓▓",
+    no_apples: "··",
+    portal: "◙◙",
+    sprint: "»»",
+    grid: GRID_SHADES,
+    bar: ['█', '░'],
+    logo: include_str!("logo.txt"),
+    patterns: [[DEFAULT_SNAKE, "▓▓"], ["▚▚", "▞▞"]],
+    marked_apples: ["()", "xx", "**"],
+    heads: ["▲▲", "▼▼", "◀█", "█▶"],
+    unicode: true,
+};
+
+pub const ASCII: Charset = Charset {
+    border: ['|', '-', '+', '+', '+', '+'],
+    snake: "##",
+    obstacle: "XX",
+    no_apples: "..",
+    portal: "()",
+    sprint: ">>",
+    grid: [". ", "  "],
+    bar: ['#', '-'],
+    logo: include_str!("logo_ascii.txt"),
+    patterns: [["##", "=="], ["%%", "::"]],
+    marked_apples: ["()", "xx", "**"],
+    heads: ["^^", "vv", "<#", "#>"],
+    unicode: false,
+};
+
+impl Default for Charset {
+    fn default() -> Self {
+        UNICODE
+    }
+}
+
+impl Charset {
+    /// What a cell holding an `apple_type` apple shows.
+    pub fn apple(&self, apple_type: AppleType) -> String {
+        self.item(apple_type.glyph(), apple_type.ascii_glyph())
+    }
+
+    /// What a cell holding an `effect` pickup shows.
+    pub fn effect(&self, effect: Effect) -> String {
+        self.item(effect.glyph(), effect.ascii_glyph())
+    }
+
+    /// The head of a snake heading `direction`, if it is moving.
+    pub fn head(&self, direction: Direction) -> Option<&'static str> {
+        match direction {
+            Direction::Up => Some(self.heads[0]),
+            Direction::Down => Some(self.heads[1]),
+            Direction::Left => Some(self.heads[2]),
+            Direction::Right => Some(self.heads[3]),
+            Direction::Stop => None,
+        }
+    }
+
+    /// What a cell holding an `apple_type` apple shows with --colorblind.
+    pub fn marked_apple(&self, apple_type: AppleType) -> &'static str {
+        match apple_type {
+            AppleType::Fresh(_) => self.marked_apples[0],
+            AppleType::Poison => self.marked_apples[1],
+            AppleType::Golden => self.marked_apples[2],
+        }
+    }
+
+    // Emoji are as wide as a cell, ASCII characters only fill half of it.
+    fn item(&self, emoji: char, ascii: char) -> String {
+        if self.unicode {
+            emoji.to_string()
+        } else {
+            format!("{} ", ascii)
+        }
+    }
+
+    /// Background shade of `cell` on the checkerboard. Shades alternate per canvas cell, so the
+    /// snake always covers whole squares.
+    pub fn grid_shade(&self, cell: &CanvasSpace) -> &'static str {
+        let (x, y) = cell.0;
+        self.grid[((x + y) % 2) as usize]
+    }
+
+    /// A bar `width` characters long, filled to `fraction`.
+    pub fn bar(&self, fraction: f64, width: usize) -> String {
+        let filled = ((fraction.clamp(0.0, 1.0) * width as f64).ceil() as usize).min(width);
+        let [full, empty] = self.bar;
+        format!(
+            "{}{}",
+            full.to_string().repeat(filled),
+            empty.to_string().repeat(width - filled)
+        )
+    }
+}
+
+/// Whether a terminal in this locale shows Unicode, given `$LC_ALL`, `$LC_CTYPE` and `$LANG`.
+/// The first one that is set decides, and only a UTF-8 locale shows Unicode. Without any, the
+/// terminal is taken to show it, which is the case on Windows.
+pub fn unicode_locale(lc_all: Option<&str>, lc_ctype: Option<&str>, lang: Option<&str>) -> bool {
+    match [lc_all, lc_ctype, lang]
+        .iter()
+        .flatten()
+        .find(|value| !value.is_empty())
+    {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => true,
+    }
+}
+
+/// Glyphs and colors used to draw the board, resolved once from the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderConfig {
+    pub snake: String,
+    // Overrides the glyph of every apple. Without it, each apple is drawn as its own type.
+    pub apple: Option<String>,
+    // Fills the empty board with a checkerboard, which makes distances easier to judge.
+    pub grid: bool,
+    // Tells snakes and apples apart without colors, see `Charset::patterns`.
+    pub colorblind: bool,
+    // Draws everything without colors.
+    pub no_color: bool,
+    pub theme: Theme,
+    pub charset: Charset,
+    // Shades snakes from head to tail, see `theme::gradient`. Only for terminals that show
+    // 24-bit color, the others get them in a single color.
+    pub truecolor: bool,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            snake: String::from(DEFAULT_SNAKE),
+            apple: None,
+            grid: false,
+            colorblind: false,
+            no_color: false,
+            theme: Theme::default(),
+            charset: Charset::default(),
+            truecolor: false,
+        }
+    }
+}
+
+impl RenderConfig {
+    pub fn new(snake: Option<String>, apple: Option<String>) -> Result<Self, String> {
+        let snake = snake.unwrap_or_else(|| String::from(DEFAULT_SNAKE));
+
+        validate_glyph("--snake-char", &snake)?;
+        if let Some(apple) = &apple {
+            validate_glyph("--apple-char", apple)?;
+        }
+
+        Ok(RenderConfig {
+            snake,
+            apple,
+            grid: false,
+            colorblind: false,
+            no_color: false,
+            theme: Theme::default(),
+            charset: Charset::default(),
+            truecolor: false,
+        })
+    }
+}
+
+fn validate_glyph(option: &str, glyph: &str) -> Result<(), String> {
+    match display_width(glyph) {
+        CELL_WIDTH => Ok(()),
+        width => Err(format!(
+            "{} must be exactly {} columns wide, but `{}` is {}.",
+            option, CELL_WIDTH, glyph, width
+        )),
+    }
+}
+
+/// Number of terminal columns `text` occupies. This covers control and combining characters,
+/// CJK and the common emoji blocks, which is what glyph validation needs.
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+pub fn char_width(c: char) -> usize {
+    let code = c as u32;
+
+    let zero_width = code < 0x20
+        || (0x7f..0xa0).contains(&code)
+        || (0x0300..=0x036f).contains(&code)
+        || (0x200b..=0x200f).contains(&code)
+        || (0x20d0..=0x20ff).contains(&code)
+        || (0xfe00..=0xfe0f).contains(&code);
+
+    if zero_width {
+        0
+    } else if WIDE_RANGES.iter().any(|range| range.contains(&code)) {
+        2
+    } else {
+        1
+    }
+}
+
+const WIDE_RANGES: [std::ops::RangeInclusive<u32>; 29] = [
+    0x1100..=0x115f,
+    0x231a..=0x231b,
+    0x23e9..=0x23ec,
+    0x23f0..=0x23f3,
+    0x25fd..=0x25fe,
+    0x2614..=0x2615,
+    0x2648..=0x2653,
+    0x26a1..=0x26a1,
+    0x26aa..=0x26ab,
+    0x26bd..=0x26be,
+    0x26c4..=0x26c5,
+    0x2705..=0x2705,
+    0x270a..=0x270b,
+    0x2728..=0x2728,
+    0x274c..=0x274c,
+    0x2753..=0x2757,
+    0x2b1b..=0x2b1c,
+    0x2b50..=0x2b55,
+    0x2e80..=0x303e,
+    0x3041..=0x33ff,
+    0x3400..=0x4dbf,
+    0x4e00..=0x9fff,
+    0xa000..=0xa4cf,
+    0xac00..=0xd7a3,
+    0xf900..=0xfaff,
+    0xff00..=0xff60,
+    0xffe0..=0xffe6,
+    0x1f300..=0x1faff,
+    0x20000..=0x3fffd,
+];
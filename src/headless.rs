@@ -0,0 +1,64 @@
+// Drives the game one tick at a time without a terminal, threads or wall-clock timing, for bots
+// and statistics over many simulated runs.
+
+use crossterm::event;
+
+use crate::achievements::Death;
+use crate::{continue_game_logic, direction_key, Controller, Direction, GameScreen};
+
+/// What a single tick did.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepResult {
+    Moved,
+    AteApple,
+    Won,
+    // `None` for runs that were already lost before they were handed over.
+    Lost(Option<Death>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameState {
+    pub controller: Controller,
+}
+
+impl GameState {
+    pub fn new(controller: Controller) -> Self {
+        GameState { controller }
+    }
+
+    /// Advances the game by one tick, turning towards `input` first if it's a legal turn. Once
+    /// the run is over, further steps leave it untouched and report how it ended.
+    pub fn step(&mut self, input: Option<Direction>) -> StepResult {
+        let controller = &mut self.controller;
+
+        if controller.screen != GameScreen::GameOver {
+            let apples_eaten = controller.stats.apples_eaten;
+
+            controller.last_event = input
+                .and_then(direction_key)
+                .map(|code| event::Event::Key(code.into()));
+            continue_game_logic(controller);
+
+            // Nobody listens for them here, so they would only pile up.
+            controller.cues.clear();
+
+            if controller.screen != GameScreen::GameOver
+                && controller.stats.apples_eaten > apples_eaten
+            {
+                return StepResult::AteApple;
+            }
+        }
+
+        match (controller.screen == GameScreen::GameOver, controller.won) {
+            (false, _) => StepResult::Moved,
+            (true, true) => StepResult::Won,
+            (true, false) => StepResult::Lost(controller.stats.death),
+        }
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new(Controller::new())
+    }
+}
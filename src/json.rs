@@ -0,0 +1,257 @@
+use std::fmt::Write as _;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A parsed JSON document. Objects keep their keys in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    // Only whole numbers that fit, so a hand-edited `12.5` or `-1` is rejected rather than mangled.
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_f64()
+            .filter(|number| number.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(number))
+            .map(|number| number as u32)
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// Quotes `text` as a JSON string literal.
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Parses a complete JSON document.
+pub fn parse(json: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        chars: json.chars().peekable(),
+    };
+
+    let value = parser.value()?;
+
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err(String::from("Unexpected data after the document."));
+    }
+
+    Ok(value)
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected `{}`, found `{}`.", expected, c)),
+            None => Err(format!("Expected `{}`, found the end.", expected)),
+        }
+    }
+
+    // Consumes the `,` between two items and returns false once `closing` ends the list.
+    fn next_item(&mut self, closing: char) -> Result<bool, String> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(',') => Ok(true),
+            Some(c) if c == closing => Ok(false),
+            _ => Err(format!("Expected `,` or `{}`.", closing)),
+        }
+    }
+
+    // Handles the empty `[]` and `{}` cases, which have no items to separate.
+    fn closes_immediately(&mut self, closing: char) -> bool {
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&closing) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some('"') => self.string().map(Value::String),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('n') => self.literal("null", Value::Null),
+            Some(_) => self.number(),
+            None => Err(String::from("Expected a value, found the end.")),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+
+        let mut items = Vec::new();
+
+        if self.closes_immediately(']') {
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            if !self.next_item(']')? {
+                return Ok(Value::Array(items));
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+
+        let mut fields = Vec::new();
+
+        if self.closes_immediately('}') {
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+
+            if !self.next_item('}')? {
+                return Ok(Value::Object(fields));
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, String> {
+        for expected in literal.chars() {
+            if self.chars.next() != Some(expected) {
+                return Err(format!("Expected `{}`.", literal));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.chars.next() != Some('"') {
+            return Err(String::from("Expected a string."));
+        }
+
+        let mut text = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape()?),
+                Some(c) => text.push(c),
+                None => return Err(String::from("Unterminated string.")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        match self.chars.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('u') => {
+                let hex: String = self.chars.by_ref().take(4).collect();
+
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(format!("Invalid escape `\\u{}`.", hex))
+            }
+            _ => Err(String::from("Invalid escape in string.")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut number = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                break;
+            }
+            number.push(c);
+            self.chars.next();
+        }
+
+        number
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| format!("Invalid value `{}`.", number))
+    }
+}
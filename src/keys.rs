@@ -0,0 +1,218 @@
+use std::str::FromStr;
+
+use crossterm::event::KeyCode;
+
+/// Everything a key can do in the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Sprint,
+    ToggleGhost,
+    Snapshot,
+    Restart,
+    Help,
+    Pause,
+    Quit,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    // The names the config file uses for the actions.
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action {
+            "up" => Ok(Action::Up),
+            "down" => Ok(Action::Down),
+            "left" => Ok(Action::Left),
+            "right" => Ok(Action::Right),
+            "sprint" | "boost" => Ok(Action::Sprint),
+            "ghost" => Ok(Action::ToggleGhost),
+            "snapshot" => Ok(Action::Snapshot),
+            "restart" => Ok(Action::Restart),
+            "help" => Ok(Action::Help),
+            "pause" => Ok(Action::Pause),
+            "quit" => Ok(Action::Quit),
+            _ => Err(format!("Unknown action `{}`.", action)),
+        }
+    }
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Up => "Turn up",
+            Action::Down => "Turn down",
+            Action::Left => "Turn left",
+            Action::Right => "Turn right",
+            Action::Sprint => "Sprint",
+            Action::ToggleGhost => "Show or hide the ghost",
+            Action::Snapshot => "Save a snapshot (end screen)",
+            Action::Restart => "Play again (end screen)",
+            Action::Help => "Show this help",
+            Action::Pause => "Pause or resume",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// Which key triggers which action. The help overlay is generated from it, so it lists exactly
+/// the keys that work.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyCode, Action)>,
+    // Turn the snake while it is played, before the bindings are looked at, see
+    // `MOVEMENT_KEYS`.
+    movement: Vec<(KeyCode, Action)>,
+}
+
+impl KeyBindings {
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        find(&self.bindings, code)
+    }
+
+    /// What `code` does while the snake is played, when the movement keys turn it whatever else
+    /// they are bound to.
+    pub fn playing_action(&self, code: KeyCode) -> Option<Action> {
+        find(&self.movement, code).or_else(|| self.action(code))
+    }
+
+    /// Makes `code` trigger `action`, taking it away from whatever it did before. The other keys
+    /// of `action` keep working.
+    pub fn bind(&mut self, code: KeyCode, action: Action) {
+        self.bindings.retain(|(bound, _)| *bound != code);
+        self.movement.retain(|(bound, _)| *bound != code);
+        self.bindings.push((code, action));
+    }
+
+    /// Every key that triggers `action`, the bound ones first.
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        self.bindings
+            .iter()
+            .chain(&self.movement)
+            .filter(|(_, bound)| *bound == action)
+            .map(|(code, _)| *code)
+            .collect()
+    }
+
+    /// One line per action, in the order they were first bound, with all of its keys.
+    pub fn help_lines(&self) -> Vec<String> {
+        let mut actions: Vec<(Action, Vec<String>)> = Vec::new();
+
+        for (code, action) in self.bindings.iter().chain(&self.movement) {
+            match actions.iter_mut().find(|(listed, _)| listed == action) {
+                Some((_, keys)) => keys.push(key_label(*code)),
+                None => actions.push((*action, vec![key_label(*code)])),
+            }
+        }
+
+        let key_width = actions
+            .iter()
+            .map(|(_, keys)| keys.join("/").chars().count())
+            .max()
+            .unwrap_or(0);
+
+        actions
+            .iter()
+            .map(|(action, keys)| {
+                format!(
+                    "{:<width$}  {}",
+                    keys.join("/"),
+                    action.description(),
+                    width = key_width
+                )
+            })
+            .collect()
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: vec![
+                (KeyCode::Up, Action::Up),
+                (KeyCode::Down, Action::Down),
+                (KeyCode::Left, Action::Left),
+                (KeyCode::Right, Action::Right),
+                (KeyCode::Char(' '), Action::Sprint),
+                (KeyCode::Char('g'), Action::ToggleGhost),
+                (KeyCode::Char('s'), Action::Snapshot),
+                (KeyCode::Char('r'), Action::Restart),
+                (KeyCode::Char('?'), Action::Help),
+                (KeyCode::Char('h'), Action::Help),
+                (KeyCode::Char('p'), Action::Pause),
+                (KeyCode::Esc, Action::Pause),
+                (KeyCode::Char('q'), Action::Quit),
+            ],
+            movement: MOVEMENT_KEYS.to_vec(),
+        }
+    }
+}
+
+fn find(bindings: &[(KeyCode, Action)], code: KeyCode) -> Option<Action> {
+    bindings
+        .iter()
+        .find(|(bound, _)| *bound == code)
+        .map(|(_, action)| *action)
+}
+
+// WASD and vim's hjkl, for keyboards and terminals where the arrow keys are awkward. They only
+// turn the snake while it is played, so `s` and `h` still save a snapshot and show the help
+// everywhere else. Binding one of them in the config file takes it over entirely.
+pub const MOVEMENT_KEYS: [(KeyCode, Action); 8] = [
+    (KeyCode::Char('w'), Action::Up),
+    (KeyCode::Char('a'), Action::Left),
+    (KeyCode::Char('s'), Action::Down),
+    (KeyCode::Char('d'), Action::Right),
+    (KeyCode::Char('k'), Action::Up),
+    (KeyCode::Char('h'), Action::Left),
+    (KeyCode::Char('j'), Action::Down),
+    (KeyCode::Char('l'), Action::Right),
+];
+
+// In a two player game these steer the second snake and do nothing else while it is played.
+pub const SECOND_PLAYER_KEYS: [(KeyCode, Action); 4] = [
+    (KeyCode::Char('w'), Action::Up),
+    (KeyCode::Char('a'), Action::Left),
+    (KeyCode::Char('s'), Action::Down),
+    (KeyCode::Char('d'), Action::Right),
+];
+
+pub fn second_player_action(code: KeyCode) -> Option<Action> {
+    find(&SECOND_PLAYER_KEYS, code)
+}
+
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Up => String::from("↑"),
+        KeyCode::Down => String::from("↓"),
+        KeyCode::Left => String::from("←"),
+        KeyCode::Right => String::from("→"),
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Esc => String::from("Esc"),
+        KeyCode::Enter => String::from("Enter"),
+        other => format!("{:?}", other),
+    }
+}
+
+/// The reverse of `key_label`, plus the names of the arrow keys: `w`, `Space`, `Up`, `Esc`...
+pub fn parse_key(key: &str) -> Result<KeyCode, String> {
+    let mut chars = key.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(KeyCode::Char(c)),
+        _ => match key {
+            "Up" | "↑" => Ok(KeyCode::Up),
+            "Down" | "↓" => Ok(KeyCode::Down),
+            "Left" | "←" => Ok(KeyCode::Left),
+            "Right" | "→" => Ok(KeyCode::Right),
+            "Space" => Ok(KeyCode::Char(' ')),
+            "Esc" => Ok(KeyCode::Esc),
+            "Enter" => Ok(KeyCode::Enter),
+            _ => Err(format!("Unknown key `{}`.", key)),
+        },
+    }
+}
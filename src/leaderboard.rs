@@ -0,0 +1,293 @@
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+
+use crate::json::{self, Value};
+use crate::{data_dir, score_label};
+
+pub const MAX_ENTRIES: usize = 10;
+
+// Longest name the prompt accepts, so the table stays aligned.
+pub const MAX_NAME_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+    // `YYYY-MM-DD HH:MM` in UTC. Older files only recorded the day.
+    pub date: String,
+    // Width and height of the board the run was played on, unknown for older entries.
+    pub board: Option<(u32, u32)>,
+    // The run ended with the snake filling the whole board.
+    pub completed: bool,
+}
+
+impl Entry {
+    // The entry's fields as they go between the braces of a JSON record.
+    pub(crate) fn json_fields(&self) -> String {
+        let board = self
+            .board
+            .map(|(width, height)| format!(", \"board\": [{}, {}]", width, height))
+            .unwrap_or_default();
+
+        format!(
+            "\"name\": {}, \"score\": {}, \"date\": {}{}, \"completed\": {}",
+            json::quote(&self.name),
+            self.score,
+            json::quote(&self.date),
+            board,
+            self.completed
+        )
+    }
+}
+
+/// The best runs, highest score first. Stored as a JSON array of `{name, score, date, board}` records.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Leaderboard {
+    pub entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /// Reads the leaderboard at `path`. A missing file is an empty leaderboard.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Leaderboard::default()),
+            Err(e) => return Err(e),
+        };
+
+        file.lock_shared()?;
+        read_locked(&mut file)
+    }
+
+    /// Adds `entry` to the leaderboard at `path` and returns the updated table. The file stays
+    /// locked from reading to writing, so two games ending at once don't drop each other's runs.
+    pub fn record(path: &Path, entry: Entry) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.lock()?;
+
+        let mut leaderboard = read_locked(&mut file)?;
+        leaderboard.insert(entry);
+
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(leaderboard.to_json().as_bytes())?;
+
+        Ok(leaderboard)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_json())
+    }
+
+    // Runs that didn't score at all never make it on.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_none_or(|last| score > last.score))
+    }
+
+    // Ties keep the older entry in front.
+    pub fn insert(&mut self, entry: Entry) {
+        let index = self
+            .entries
+            .iter()
+            .position(|existing| existing.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn to_json(&self) -> String {
+        let records: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| format!("  {{{}}}", entry.json_fields()))
+            .collect();
+
+        if records.is_empty() {
+            String::from("[]\n")
+        } else {
+            format!("[\n{}\n]\n", records.join(",\n"))
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let records = json::parse(json)?;
+        let records = records
+            .as_array()
+            .ok_or("The leaderboard must be an array of entries.")?;
+
+        let mut leaderboard = Leaderboard::default();
+        for record in records {
+            leaderboard.insert(parse_entry(record)?);
+        }
+
+        Ok(leaderboard)
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("leaderboard.json"))
+}
+
+// An empty file is one `record` has only just created.
+fn read_locked(file: &mut File) -> io::Result<Leaderboard> {
+    let mut json = String::new();
+    file.read_to_string(&mut json)?;
+
+    if json.trim().is_empty() {
+        return Ok(Leaderboard::default());
+    }
+
+    Leaderboard::from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Renders the leaderboard as a plain-text table for printing after the game.
+pub fn leaderboard_table(leaderboard: &Leaderboard) -> String {
+    let mut table = format!(
+        "{:>3}  {:<width$}  {:>10}  {:<7}  {}\n",
+        "#",
+        "Name",
+        "Score",
+        "Board",
+        "Date",
+        width = MAX_NAME_LEN
+    );
+
+    if leaderboard.entries.is_empty() {
+        table.push_str("     No entries yet.\n");
+    }
+
+    for (rank, entry) in leaderboard.entries.iter().enumerate() {
+        let board = entry
+            .board
+            .map(|(width, height)| format!("{}x{}", width, height))
+            .unwrap_or_default();
+
+        let _ = writeln!(
+            table,
+            "{:>3}  {:<width$}  {:>10}  {:<7}  {}{}",
+            rank + 1,
+            entry.name,
+            score_label(entry.score),
+            board,
+            entry.date,
+            if entry.completed {
+                "  board cleared"
+            } else {
+                ""
+            },
+            width = MAX_NAME_LEN
+        );
+    }
+
+    table
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptState {
+    Editing,
+    Submitted,
+    Cancelled,
+}
+
+/// Minimal line editor for entering a name: printable characters are appended, Backspace
+/// deletes, Enter submits and Esc gives up on the entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamePrompt {
+    pub text: String,
+    pub state: PromptState,
+}
+
+impl Default for NamePrompt {
+    fn default() -> Self {
+        NamePrompt {
+            text: String::new(),
+            state: PromptState::Editing,
+        }
+    }
+}
+
+impl NamePrompt {
+    pub fn new() -> Self {
+        NamePrompt::default()
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) {
+        if self.state != PromptState::Editing {
+            return;
+        }
+
+        match code {
+            KeyCode::Char(c) if !c.is_control() && self.text.chars().count() < MAX_NAME_LEN => {
+                self.text.push(c)
+            }
+            KeyCode::Backspace => {
+                self.text.pop();
+            }
+            KeyCode::Enter => self.state = PromptState::Submitted,
+            KeyCode::Esc => self.state = PromptState::Cancelled,
+            _ => (),
+        }
+    }
+
+    // Blank names are recorded as anonymous rather than as an empty column.
+    pub fn name(&self) -> String {
+        match self.text.trim() {
+            "" => String::from("anonymous"),
+            name => String::from(name),
+        }
+    }
+}
+
+fn parse_entry(record: &Value) -> Result<Entry, String> {
+    let field = |key: &str| {
+        record
+            .get(key)
+            .ok_or(format!("Entry is missing its {}.", key))
+    };
+
+    Ok(Entry {
+        name: field("name")?
+            .as_str()
+            .ok_or("Name must be a string.")?
+            .to_string(),
+        score: field("score")?
+            .as_u32()
+            .ok_or("Score must be a whole, non-negative number.")?,
+        // Entries without a date are still worth keeping.
+        date: record
+            .get("date")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        board: match record.get("board") {
+            None => None,
+            Some(board) => match board.as_array() {
+                Some([width, height]) => width.as_u32().zip(height.as_u32()),
+                _ => None,
+            }
+            .map(Some)
+            .ok_or("Board must be [width, height].")?,
+        },
+        completed: record.get("completed") == Some(&Value::Bool(true)),
+    })
+}
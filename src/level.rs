@@ -0,0 +1,168 @@
+// Levels for --level: a text map of the board with one line per row and one character per cell.
+//
+//     #  a wall
+//     .  an empty cell, as is a space
+//     S  an empty cell where the snake's head starts
+//     x  an empty cell that apples never spawn on
+//
+// Lines starting with `;` are comments. The board is as wide as the longest row, and shorter
+// rows are filled up with empty cells.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{CanvasBounds, CanvasSpace};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub bounds: CanvasBounds,
+    pub walls: Vec<CanvasSpace>,
+    pub no_apples: Vec<CanvasSpace>,
+    pub spawn: Option<CanvasSpace>,
+}
+
+/// What a cell of a level holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    NoApples,
+    Spawn,
+}
+
+impl Level {
+    /// A level without walls.
+    pub fn empty(bounds: CanvasBounds) -> Self {
+        Level {
+            bounds,
+            walls: Vec::new(),
+            no_apples: Vec::new(),
+            spawn: None,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        text.parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_string())
+    }
+
+    pub fn tile(&self, cell: &CanvasSpace) -> Tile {
+        if self.walls.contains(cell) {
+            Tile::Wall
+        } else if self.no_apples.contains(cell) {
+            Tile::NoApples
+        } else if self.spawn.as_ref() == Some(cell) {
+            Tile::Spawn
+        } else {
+            Tile::Empty
+        }
+    }
+
+    /// Puts `tile` on `cell`, replacing what was there. There is only one spawn, so setting it
+    /// moves it.
+    pub fn set(&mut self, cell: &CanvasSpace, tile: Tile) {
+        self.walls.retain(|wall| wall != cell);
+        self.no_apples.retain(|no_apples| no_apples != cell);
+        if self.spawn.as_ref() == Some(cell) {
+            self.spawn = None;
+        }
+
+        match tile {
+            Tile::Empty => (),
+            Tile::Wall => self.walls.push(cell.clone()),
+            Tile::NoApples => self.no_apples.push(cell.clone()),
+            Tile::Spawn => self.spawn = Some(cell.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for y in 0..self.bounds.height() as i32 {
+            let row: String = (0..self.bounds.width() as i32)
+                .map(|x| match self.tile(&CanvasSpace((x, y))) {
+                    Tile::Empty => '.',
+                    Tile::Wall => '#',
+                    Tile::NoApples => 'x',
+                    Tile::Spawn => 'S',
+                })
+                .collect();
+
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut walls = Vec::new();
+        let mut no_apples = Vec::new();
+        let mut spawn: Option<(CanvasSpace, usize)> = None;
+        let mut width: u32 = 0;
+        let mut height: i32 = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            if line.starts_with(';') {
+                continue;
+            }
+
+            let y = height;
+            height += 1;
+
+            for (x, c) in line.trim_end_matches('\r').chars().enumerate() {
+                let cell = CanvasSpace((x as i32, y));
+                width = width.max(x as u32 + 1);
+
+                match c {
+                    '#' => walls.push(cell),
+                    '.' | ' ' => (),
+                    'x' => no_apples.push(cell),
+                    'S' => {
+                        if let Some((_, first)) = spawn {
+                            return Err(format!(
+                                "Line {}: A second start, the first one is on line {}.",
+                                index + 1,
+                                first
+                            ));
+                        }
+                        spawn = Some((cell, index + 1));
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Line {}, column {}: Unknown cell `{}`, expected #, ., S or x.",
+                            index + 1,
+                            x + 1,
+                            c
+                        ))
+                    }
+                }
+            }
+        }
+
+        if width == 0 {
+            return Err(String::from("The level has no cells."));
+        }
+
+        Ok(Level {
+            bounds: CanvasBounds::new(width, height as u32),
+            walls,
+            no_apples,
+            spawn: spawn.map(|(cell, _)| cell),
+        })
+    }
+}
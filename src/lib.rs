@@ -0,0 +1,1851 @@
+pub mod achievements;
+pub mod ai;
+pub mod bench;
+pub mod board;
+pub mod bot;
+pub mod broadcast;
+pub mod cast;
+pub mod config;
+pub mod difficulty;
+pub mod editor;
+pub mod effects;
+pub mod feedback;
+pub mod ghost;
+pub mod glyphs;
+pub mod headless;
+pub mod json;
+pub mod keys;
+pub mod leaderboard;
+pub mod level;
+pub mod menu;
+pub mod net;
+pub mod online;
+pub mod render;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod server;
+pub mod smooth;
+pub mod snapshot;
+pub mod speed;
+pub mod telnet;
+pub mod theme;
+pub mod timing;
+pub mod toml;
+pub mod web;
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crossterm::event;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use achievements::{Death, RunStats};
+use board::Layout;
+use bot::{BoardView, Keyboard, SnakeController};
+use effects::{ActiveEffect, Effect};
+use feedback::Cue;
+use keys::{Action, KeyBindings};
+use leaderboard::{NamePrompt, PromptState};
+use rng::GameRng;
+
+pub const CANVAS_WIDTH: u16 = 46;
+pub const CANVAS_HEIGHT: u16 = 46;
+
+// Rows kept free around the board for the score above it and messages below it.
+pub const HUD_ROWS: u16 = 2;
+
+pub const TICKS_PER_SEC: u16 = 10;
+
+// Terminals don't report key releases, so each space press keeps the sprint going for this many
+// ticks. Holding the key refreshes it through key repeat.
+pub const SPRINT_TICKS: u8 = 5;
+
+// How many turns pressed faster than the snake moves are kept for the ticks after, so that two
+// quick turns make a tight bend instead of losing the first one.
+pub const TURN_BUFFER: usize = 3;
+
+// Obstacles stop spawning once snake and obstacles cover this much of the board, in percent,
+// so that the maze never becomes unsolvable.
+pub const MAX_OBSTACLE_COVERAGE: u32 = 80;
+
+// How many ticks an eaten apple keeps flashing before the next one spawns.
+pub const FLASH_TICKS: u8 = 1;
+
+// An apple eaten within this long of the previous one raises the combo multiplier, up to
+// `MAX_COMBO`. Measured in time rather than ticks so that it doesn't shrink when sped up.
+pub const COMBO_WINDOW: Duration = Duration::from_millis(30 * 1000 / TICKS_PER_SEC as u64);
+pub const MAX_COMBO: u32 = 5;
+
+// Longest snake --start-length accepts.
+pub const MAX_START_LENGTH: usize = 20;
+
+// In hunger mode the snake loses a segment and a point every this many ticks without eating.
+pub const HUNGER_TICKS: u64 = 80;
+
+// How long the countdown before a round lasts, see `start_countdown`.
+pub const COUNTDOWN_SECS: u32 = 3;
+
+// In shrink mode the border closes in by a cell on every side this often, and every second
+// survived is worth a point.
+pub const SHRINK_SECONDS: u64 = 10;
+
+// The border stops closing in once the open part of the board is this many cells across.
+pub const MIN_ARENA: u32 = 5;
+
+pub const APPLE: [char; 2] = ['🍎', '🍏'];
+
+pub const POISON_APPLE: char = '🍄';
+
+// Roughly one in this many apples is poisonous.
+pub const POISON_CHANCE: u32 = 8;
+
+// Eating poison costs this many segments off the tail and this many points.
+pub const POISON_SEGMENTS: usize = 2;
+pub const POISON_PENALTY: u32 = 3;
+
+// A poison apple that is left alone rots away after this many ticks, making room for a fresh one.
+pub const POISON_TICKS: u32 = 60;
+
+pub const GOLDEN_APPLE: char = '🌟';
+
+// Every this many apples eaten, the next one is golden.
+pub const GOLDEN_EVERY: u32 = 5;
+
+// A golden apple is worth this many apples, but only stays for this many seconds.
+pub const GOLDEN_POINTS: u32 = 5;
+pub const GOLDEN_SECONDS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppleType {
+    // Grows the snake and scores, drawn as one of `APPLE`.
+    Fresh(char),
+    // Shrinks the snake and costs points, and kills a snake that is only a head.
+    Poison,
+    // Worth `GOLDEN_POINTS`, if it is eaten before it disappears.
+    Golden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Stop,
+}
+
+/// Which part of the game is showing. `handle_events` reads keys according to it, and the
+/// frontend draws it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameScreen {
+    // The frontend runs its menus itself, so no keys reach the game here.
+    Menu,
+    // Counts down to the start of the round, see `start_countdown`. Only turns and quitting
+    // are read.
+    Countdown,
+    Playing,
+    // Stops the clock until the pause key is pressed again. Only a running game can be paused.
+    Paused,
+    // The round has ended, by losing or by winning.
+    GameOver,
+    // The player quit, and the frontend shuts down.
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Controller {
+    pub screen: GameScreen,
+    pub last_event: Option<event::Event>,
+    // Turns pressed since the last tick, oldest first. See `take_buffered_turn`.
+    pub turns: VecDeque<Direction>,
+    pub snake: Snake,
+    // With --demo the game plays itself and any key ends it.
+    pub demo: bool,
+    // With --bot or --demo, what steers `snake` instead of the arrow keys.
+    pub pilot: Option<bot::Bot>,
+    // With --two-players, the snake steered with WASD. `snake` and `score` belong to the first
+    // player.
+    pub second: Option<Player>,
+    pub apples: Vec<Apple>,
+    // How many apples are on the board at once, see --apples.
+    pub apple_count: usize,
+    pub score: u32,
+    // Set together with the game over screen when the snake filled the whole board.
+    pub won: bool,
+    pub bounds: CanvasBounds,
+    pub sprint_ticks: u8,
+    pub pickup: Option<(CanvasSpace, Effect)>,
+    pub active_effects: Vec<ActiveEffect>,
+    // Set when every `GOLDEN_EVERY`th apple is eaten, so that the next one is golden.
+    pub golden_due: bool,
+    // New apples are placed at the start of the tick after one disappeared, once the snake has
+    // finished moving and growing, so that they never spawn on a fresh tail segment.
+    pub spawn_apple_next_tick: bool,
+    // Cues raised by the game logic since the frontend last drained them.
+    pub cues: Vec<Cue>,
+    // Set by pressing `s` on the end screen. The snapshot is written once the terminal is back.
+    pub snapshot_requested: bool,
+    // Set by pressing `r` on the end screen, for the frontend to start a new round.
+    pub restart_requested: bool,
+    // Ticks left on the countdown before the round starts.
+    pub countdown_ticks: u32,
+    // Set by ctrl+l, for the frontend to repaint the whole screen.
+    pub redraw_requested: bool,
+    // While a name is being entered for the leaderboard, it receives every key press.
+    pub name_prompt: Option<NamePrompt>,
+    pub stats: RunStats,
+    // Toggled with `g`.
+    pub show_ghost: bool,
+    // Every this many points a new obstacle appears, turning the board into a growing maze.
+    pub obstacles_every: Option<u32>,
+    pub obstacles: Vec<CanvasSpace>,
+    // Cells that apples never spawn on, from a --level file.
+    pub no_apples: Vec<CanvasSpace>,
+    // With --wrap the snake leaves the board on one side and comes back in on the other,
+    // instead of dying on the edge.
+    pub wrap: bool,
+    // A head moving onto one mouth of the pair comes out of the other.
+    pub portals: Option<(CanvasSpace, CanvasSpace)>,
+    // With --max-length the snake never grows beyond this many segments.
+    pub max_length: Option<usize>,
+    // Multiplies the points of the next apple if it is eaten within `COMBO_WINDOW`.
+    pub combo: u32,
+    // With --mouse, where the frontend last drew the canvas. Clicks steer the snake towards the
+    // clicked position; without it the mouse is ignored.
+    pub mouse_origin: Option<TerminalSpace>,
+    // With --mouse-follow the snake also heads for the mouse cursor, which is at `cursor` as far
+    // as the terminal has told.
+    pub follow_cursor: bool,
+    pub cursor: Option<(u16, u16)>,
+    pub keys: KeyBindings,
+    pub game_mode: GameMode,
+    // The game stands still while the help overlay is open. Any key closes it.
+    pub show_help: bool,
+    // Apples, pickups, obstacles and random starts all draw from this, see --seed.
+    pub rng: GameRng,
+    // How many ticks make a second when no effect is active, see --speed.
+    pub ticks_per_sec: u16,
+    // With --ramp the game gets faster as the score grows, see `speed::ticks_per_sec`.
+    pub ramp: Option<speed::Ramp>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snake {
+    pub elements: VecDeque<CanvasSpace>,
+    pub current_direction: Direction,
+}
+
+/// The second player of a hot-seat game, with the parts of the state that aren't shared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub snake: Snake,
+    pub score: u32,
+    // The last turn asked for, applied at the start of the next tick.
+    pub turn: Option<Direction>,
+    pub death: Option<Death>,
+    // With --ai the computer steers this snake instead of the WASD keys.
+    pub ai: Option<ai::Skill>,
+    // With --host the player who joined steers it, see `net::Host`.
+    pub remote: bool,
+}
+
+impl Player {
+    pub fn new(elements: VecDeque<CanvasSpace>) -> Self {
+        Player {
+            snake: Snake {
+                elements,
+                current_direction: Direction::Stop,
+            },
+            score: 0,
+            turn: None,
+            death: None,
+            ai: None,
+            remote: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+// Signed, so that a step off the board is a negative coordinate instead of wrapping around.
+pub struct CanvasSpace(pub (i32, i32));
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalSpace(pub (u32, u32));
+
+/// The playable area, measured in canvas cells. Movement, apple placement and the drawn border
+/// are all derived from it, so they can't disagree about where the walls are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasBounds {
+    width: u32,
+    height: u32,
+    // Rings of cells along the edges that the border has closed off, see `GameMode::Shrink`.
+    // Width and height stay those of the whole board, so that it doesn't move on screen.
+    inset: u32,
+}
+
+impl CanvasBounds {
+    // Both dimensions are at least one cell.
+    pub fn new(width: u32, height: u32) -> Self {
+        CanvasBounds {
+            width: width.max(1),
+            height: height.max(1),
+            inset: 0,
+        }
+    }
+
+    pub fn inset(&self) -> u32 {
+        self.inset
+    }
+
+    // The same board with the border moved in by another cell on every side.
+    pub fn shrunk(&self) -> Self {
+        CanvasBounds {
+            inset: self.inset + 1,
+            ..*self
+        }
+    }
+
+    // The same board closed in by `inset` rings, as long as at least one cell stays open.
+    pub fn with_inset(&self, inset: u32) -> Self {
+        CanvasBounds {
+            inset: inset.min((self.width.min(self.height) - 1) / 2),
+            ..*self
+        }
+    }
+
+    // Columns and rows inside the border.
+    pub fn open_size(&self) -> (u32, u32) {
+        (self.width - 2 * self.inset, self.height - 2 * self.inset)
+    }
+
+    fn columns(&self) -> std::ops::Range<i32> {
+        self.inset as i32..(self.width - self.inset) as i32
+    }
+
+    fn rows(&self) -> std::ops::Range<i32> {
+        self.inset as i32..(self.height - self.inset) as i32
+    }
+
+    // Bounds for a canvas of the given size in terminal columns and half-rows.
+    pub fn from_canvas_size(width: u16, height: u16) -> Self {
+        CanvasBounds::new(canvas_max_x(width) + 1, canvas_max_y(height) + 1)
+    }
+
+    // The largest board that fits a terminal of the given size, along with its border and the
+    // rows of the HUD. See `TerminalSpace::canvas_origin` for where it ends up.
+    pub fn fit_terminal((columns, rows): (u16, u16)) -> Self {
+        CanvasBounds::new(
+            u32::from(columns.saturating_sub(2) / 2),
+            u32::from(rows.saturating_sub(2 + HUD_ROWS)),
+        )
+    }
+
+    // The smallest terminal that shows all of the board and its border, and the score in the
+    // row above. Messages below the board may be cut off on it. The board is centered, see
+    // `TerminalSpace::canvas_origin`, so the upper half of the terminal has to hold the upper
+    // half of the board and the score.
+    pub fn terminal_size(&self) -> (u16, u16) {
+        let columns = self.width.min(u32::from(u16::MAX / 2)) as u16;
+        let rows = self.height.min(u32::from(u16::MAX / 2 - 4)) as u16;
+
+        (columns * 2 + 2, ((rows + 2) / 2 + 1) * 2)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn contains(&self, cell: &CanvasSpace) -> bool {
+        let (x, y) = cell.0;
+        self.columns().contains(&x) && self.rows().contains(&y)
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = CanvasSpace> {
+        let columns = self.columns();
+        self.rows()
+            .flat_map(move |y| columns.clone().map(move |x| CanvasSpace((x, y))))
+    }
+
+    // Where the snake starts by default, just above the middle of the board.
+    pub fn center(&self) -> CanvasSpace {
+        CanvasSpace((
+            (self.width / 2) as i32,
+            ((self.height / 2) as i32 - 1).max(0),
+        ))
+    }
+
+    pub fn random_cell(&self, rng: &mut GameRng) -> CanvasSpace {
+        CanvasSpace((rng.gen_range(self.columns()), rng.gen_range(self.rows())))
+    }
+}
+
+impl TerminalSpace {
+    // Top left border corner of the canvas when it is centered in a terminal of the given size.
+    pub fn canvas_origin(
+        (terminal_width, terminal_height): (u16, u16),
+        bounds: &CanvasBounds,
+    ) -> Self {
+        TerminalSpace((
+            u32::from(terminal_width / 2).saturating_sub(bounds.width() + 1),
+            u32::from(terminal_height / 2).saturating_sub((bounds.height() + 2) / 2),
+        ))
+    }
+
+    // Every canvas cell is two terminal columns wide and sits inside the border. Only cells on
+    // the board are ever drawn, so neither coordinate is negative.
+    pub fn from_canvas(canvas_space: &CanvasSpace, origin: &TerminalSpace) -> Self {
+        let (x, y) = canvas_space.0;
+
+        TerminalSpace((
+            origin.0 .0 + x.max(0) as u32 * 2 + 1,
+            origin.0 .1 + y.max(0) as u32 + 1,
+        ))
+    }
+}
+
+/// The direction from the head towards a clicked terminal position: along whichever axis the
+/// click is further away on, horizontally on a tie. Clicking the head itself picks nothing.
+pub fn click_direction(
+    head: &CanvasSpace,
+    position: (u16, u16),
+    origin: &TerminalSpace,
+) -> Option<Direction> {
+    directions_towards(head, position, origin).first().copied()
+}
+
+// The ways from the head towards a terminal position, the one along the axis it is further away
+// on first.
+fn directions_towards(
+    head: &CanvasSpace,
+    (column, row): (u16, u16),
+    origin: &TerminalSpace,
+) -> Vec<Direction> {
+    let head = TerminalSpace::from_canvas(head, origin);
+
+    // Columns are halved since every cell is two of them wide.
+    let dx = (i64::from(column) - i64::from(head.0 .0)).div_euclid(2);
+    let dy = i64::from(row) - i64::from(head.0 .1);
+    let horizontal = match dx {
+        0 => None,
+        dx if dx > 0 => Some(Direction::Right),
+        _ => Some(Direction::Left),
+    };
+    let vertical = match dy {
+        0 => None,
+        dy if dy > 0 => Some(Direction::Down),
+        _ => Some(Direction::Up),
+    };
+
+    if dx.abs() >= dy.abs() {
+        horizontal.into_iter().chain(vertical).collect()
+    } else {
+        vertical.into_iter().chain(horizontal).collect()
+    }
+}
+
+/// Extra rules on top of the classic game, applied once per tick after the snake has moved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    Classic,
+    // The snake shrinks every `HUNGER_TICKS` without eating and starves once nothing is left.
+    Hunger,
+    // The border closes in every `SHRINK_SECONDS`, and surviving scores.
+    Shrink,
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "classic" => Ok(GameMode::Classic),
+            "hunger" => Ok(GameMode::Hunger),
+            "shrink" => Ok(GameMode::Shrink),
+            _ => Err(format!(
+                "Unknown mode `{}`, expected classic, hunger or shrink.",
+                mode
+            )),
+        }
+    }
+}
+
+// `interval` is how long the tick that just ran lasted.
+fn apply_mode_rules(controller: &mut Controller, interval: Duration) {
+    match controller.game_mode {
+        GameMode::Classic => (),
+        GameMode::Hunger => {
+            let hungry = controller.stats.ticks_since_apple > 0
+                && controller
+                    .stats
+                    .ticks_since_apple
+                    .is_multiple_of(HUNGER_TICKS);
+
+            if hungry {
+                controller.score = controller.score.saturating_sub(1);
+
+                if !shrink_tail(controller) {
+                    controller.screen = GameScreen::GameOver;
+                    controller.stats.death = Some(Death::Starved);
+                }
+            }
+        }
+        GameMode::Shrink => {
+            // Only ticks the snake moved on count, not those spent waiting for the first key.
+            if controller.snake.current_direction == Direction::Stop {
+                return;
+            }
+
+            // Goes by game time, so that faster ticks don't close the border sooner.
+            let now = controller.stats.elapsed.as_secs();
+            let before = controller.stats.elapsed.saturating_sub(interval).as_secs();
+
+            if now > before {
+                add_score(controller, 1);
+            }
+            if now / SHRINK_SECONDS > before / SHRINK_SECONDS {
+                close_border(controller);
+            }
+        }
+    }
+}
+
+// Moves the border in by a cell on every side. Apples, the pickup and portals caught outside
+// are gone, and so is a snake that still had a segment there.
+fn close_border(controller: &mut Controller) {
+    let (width, height) = controller.bounds.open_size();
+    if width.min(height) < MIN_ARENA + 2 {
+        return;
+    }
+
+    controller.bounds = controller.bounds.shrunk();
+    let bounds = controller.bounds;
+
+    if !controller
+        .snake
+        .elements
+        .iter()
+        .all(|segment| bounds.contains(segment))
+    {
+        controller.screen = GameScreen::GameOver;
+        controller.stats.death = Some(Death::Crushed);
+    }
+
+    let apples = controller.apples.len();
+    controller
+        .apples
+        .retain(|apple| bounds.contains(&apple.position));
+    if controller.apples.len() < apples {
+        controller.spawn_apple_next_tick = true;
+    }
+
+    if matches!(&controller.pickup, Some((cell, _)) if !bounds.contains(cell)) {
+        controller.pickup = None;
+    }
+    if matches!(&controller.portals, Some((a, b)) if !bounds.contains(a) || !bounds.contains(b)) {
+        controller.portals = None;
+    }
+}
+
+/// Ticks left until a hungry snake loses its next segment.
+pub fn ticks_until_hungry(controller: &Controller) -> u64 {
+    HUNGER_TICKS - controller.stats.ticks_since_apple % HUNGER_TICKS
+}
+
+/// Drops the last segment of the snake. The head is never removed, so a snake that is only a
+/// head can't shrink any further.
+pub fn shrink_tail(controller: &mut Controller) -> bool {
+    if controller.snake.elements.len() > 1 {
+        controller.snake.elements.pop_back();
+        true
+    } else {
+        false
+    }
+}
+
+/// Where the snake's head is placed at the start of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartPosition {
+    Center,
+    Random,
+    At(CanvasSpace),
+}
+
+impl FromStr for StartPosition {
+    type Err = String;
+
+    fn from_str(start: &str) -> Result<Self, Self::Err> {
+        match start {
+            "center" => Ok(StartPosition::Center),
+            "random" => Ok(StartPosition::Random),
+            _ => {
+                let mut coordinates = start.split(',').map(|c| c.trim().parse::<i32>());
+
+                match (coordinates.next(), coordinates.next(), coordinates.next()) {
+                    (Some(Ok(x)), Some(Ok(y)), None) => Ok(StartPosition::At(CanvasSpace((x, y)))),
+                    _ => Err(format!(
+                        "Invalid start `{}`, expected center, random or x,y.",
+                        start
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// The segments of a snake of `length` with its head on `head`, laid out in a straight line
+/// behind it. The body trails to the left if there is room, so that the snake faces into the
+/// board, and otherwise to the right, up or down. `None` if no direction fits inside `bounds`
+/// without touching an obstacle.
+pub fn starting_snake(
+    bounds: &CanvasBounds,
+    obstacles: &[CanvasSpace],
+    head: &CanvasSpace,
+    length: usize,
+) -> Option<VecDeque<CanvasSpace>> {
+    let (x, y) = head.0;
+
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .iter()
+        .map(|(dx, dy)| {
+            (0..length as i32)
+                .map(|i| {
+                    Some(CanvasSpace((x + dx * i, y + dy * i)))
+                        .filter(|cell| bounds.contains(cell) && !obstacles.contains(cell))
+                })
+                .collect::<Option<VecDeque<_>>>()
+        })
+        .find_map(|layout| layout)
+}
+
+/// Replaces the snake with one of `length` segments starting at `start`. Fails if the start
+/// lies outside the board or the snake doesn't fit there.
+pub fn place_snake(
+    controller: &mut Controller,
+    start: &StartPosition,
+    length: usize,
+) -> Result<(), String> {
+    let bounds = controller.bounds;
+    let fits = |head: &CanvasSpace| starting_snake(&bounds, &controller.obstacles, head, length);
+
+    let elements = match start {
+        StartPosition::Center => fits(&bounds.center()),
+        StartPosition::At(head) => {
+            if !bounds.contains(head) {
+                return Err(format!(
+                    "Start {},{} is outside the board, which is {}x{} cells.",
+                    head.0 .0,
+                    head.0 .1,
+                    bounds.width(),
+                    bounds.height()
+                ));
+            }
+
+            fits(head)
+        }
+        StartPosition::Random => {
+            let layouts: Vec<VecDeque<CanvasSpace>> =
+                bounds.iter_cells().filter_map(|head| fits(&head)).collect();
+
+            layouts.choose(&mut controller.rng).cloned()
+        }
+    };
+
+    controller.snake.elements =
+        elements.ok_or(format!("A snake of length {} doesn't fit there.", length))?;
+
+    Ok(())
+}
+
+/// What a run starts out with, fixed before the first tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    pub bounds: CanvasBounds,
+    pub ticks_per_sec: u16,
+    pub start_length: usize,
+    pub obstacles: Vec<CanvasSpace>,
+    pub no_apples: Vec<CanvasSpace>,
+    // Walls added on top of the obstacles, see `board::place_layout`.
+    pub layout: Option<Layout>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            bounds: CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT),
+            ticks_per_sec: TICKS_PER_SEC,
+            start_length: 1,
+            obstacles: Vec::new(),
+            no_apples: Vec::new(),
+            layout: None,
+        }
+    }
+}
+
+/// Rejects boards on which the game can't be played: there has to be room for the snake, an
+/// apple and at least one more cell to move into.
+pub fn validate_game_config(config: &GameConfig) -> Result<(), String> {
+    let cells = config.bounds.width() as usize * config.bounds.height() as usize;
+    // The seed doesn't matter, a layout always has the same number of walls.
+    let layout = config.layout.map_or_else(Vec::new, |layout| {
+        layout.walls(&config.bounds, &mut rng::GameRng::seed_from_u64(0))
+    });
+    let mut obstacles: Vec<&CanvasSpace> = config
+        .obstacles
+        .iter()
+        .chain(&layout)
+        .filter(|obstacle| config.bounds.contains(obstacle))
+        .collect();
+    obstacles.sort_by_key(|obstacle| obstacle.0);
+    obstacles.dedup();
+
+    let usable_cells = cells.saturating_sub(obstacles.len());
+
+    if usable_cells <= config.start_length + 1 && !obstacles.is_empty() {
+        Err(format!(
+            "{} obstacles leave {} free cells, too few for a snake of {} and an apple.",
+            obstacles.len(),
+            usable_cells,
+            config.start_length
+        ))
+    } else if usable_cells <= config.start_length + 1 {
+        Err(format!(
+            "A {}x{} board is too small for a snake of {} and an apple.",
+            config.bounds.width(),
+            config.bounds.height(),
+            config.start_length
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// The direction the neck lies in, seen from the head. Turning that way would bite the body
+// straight away, except on a snake of two where the neck is the tail moving out of the way.
+fn neck_direction(snake: &Snake) -> Option<Direction> {
+    if snake.elements.len() <= 2 {
+        return None;
+    }
+
+    let ((head_x, head_y), (neck_x, neck_y)) = (snake.elements[0].0, snake.elements[1].0);
+
+    if neck_y == head_y && neck_x + 1 == head_x {
+        Some(Direction::Left)
+    } else if neck_y == head_y && neck_x == head_x + 1 {
+        Some(Direction::Right)
+    } else if neck_x == head_x && neck_y + 1 == head_y {
+        Some(Direction::Up)
+    } else if neck_x == head_x && neck_y == head_y + 1 {
+        Some(Direction::Down)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Apple {
+    pub position: CanvasSpace,
+    pub apple_type: AppleType,
+    // Ticks since it spawned, for apples that disappear on their own.
+    pub age: u32,
+    // An eaten apple stays on the board, drawn inverted, until its flash is over.
+    pub flash_ticks: u8,
+}
+
+impl Apple {
+    pub fn new(position: CanvasSpace, apple_type: AppleType) -> Self {
+        Apple {
+            position,
+            apple_type,
+            age: 0,
+            flash_ticks: 0,
+        }
+    }
+}
+
+impl AppleType {
+    pub fn glyph(self) -> char {
+        match self {
+            AppleType::Fresh(glyph) => glyph,
+            AppleType::Poison => POISON_APPLE,
+            AppleType::Golden => GOLDEN_APPLE,
+        }
+    }
+
+    // For terminals that can't show emoji, see `glyphs::ASCII`.
+    pub fn ascii_glyph(self) -> char {
+        match self {
+            AppleType::Fresh(_) => '@',
+            AppleType::Poison => '%',
+            AppleType::Golden => '$',
+        }
+    }
+
+    // Points before effects and the combo multiply them.
+    pub fn points(self) -> u32 {
+        match self {
+            AppleType::Fresh(_) => 1,
+            AppleType::Poison => 0,
+            AppleType::Golden => GOLDEN_POINTS,
+        }
+    }
+
+    /// How many ticks the apple stays on the board, if it doesn't stay until it is eaten.
+    pub fn lifetime_ticks(self, ticks_per_sec: u16) -> Option<u32> {
+        match self {
+            AppleType::Fresh(_) => None,
+            AppleType::Poison => Some(POISON_TICKS),
+            AppleType::Golden => Some(GOLDEN_SECONDS * u32::from(ticks_per_sec)),
+        }
+    }
+
+    // The inverse of `glyph`, for formats that only store the glyph.
+    pub fn from_glyph(glyph: char) -> Self {
+        if glyph == POISON_APPLE {
+            AppleType::Poison
+        } else if glyph == GOLDEN_APPLE {
+            AppleType::Golden
+        } else {
+            AppleType::Fresh(glyph)
+        }
+    }
+}
+
+impl Controller {
+    pub fn new() -> Self {
+        Controller {
+            screen: GameScreen::Playing,
+            last_event: None,
+            turns: VecDeque::new(),
+            demo: false,
+            pilot: None,
+            second: None,
+            snake: Snake {
+                elements: VecDeque::from(vec![CanvasBounds::from_canvas_size(
+                    CANVAS_WIDTH,
+                    CANVAS_HEIGHT,
+                )
+                .center()]),
+                current_direction: Direction::Stop,
+            },
+            apples: Vec::new(),
+            apple_count: 1,
+            score: 0,
+            won: false,
+            bounds: CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT),
+            sprint_ticks: 0,
+            pickup: None,
+            active_effects: Vec::new(),
+            golden_due: false,
+            spawn_apple_next_tick: true,
+            cues: Vec::new(),
+            snapshot_requested: false,
+            restart_requested: false,
+            countdown_ticks: 0,
+            redraw_requested: false,
+            name_prompt: None,
+            stats: RunStats::default(),
+            show_ghost: true,
+            obstacles_every: None,
+            obstacles: Vec::new(),
+            no_apples: Vec::new(),
+            wrap: false,
+            portals: None,
+            max_length: None,
+            combo: 1,
+            mouse_origin: None,
+            follow_cursor: false,
+            cursor: None,
+            keys: KeyBindings::default(),
+            game_mode: GameMode::Classic,
+            show_help: false,
+            rng: GameRng::from_entropy(),
+            ticks_per_sec: TICKS_PER_SEC,
+            ramp: None,
+        }
+    }
+}
+
+// Plain text dump of the board for debugging and tests, one row per line:
+//
+// - `S` is the snake and `s` the second one
+// - `#` is an obstacle and `P` one end of a portal
+// - `A` is an apple of any kind and `*` a pickup
+// - `.` is an empty cell
+impl fmt::Display for Controller {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for cell in self.bounds.iter_cells() {
+            let symbol = if self.snake.elements.contains(&cell) {
+                'S'
+            } else if self
+                .second
+                .as_ref()
+                .is_some_and(|second| second.snake.elements.contains(&cell))
+            {
+                's'
+            } else if self.obstacles.contains(&cell) {
+                '#'
+            } else if is_portal(&self.portals, &cell) {
+                'P'
+            } else if self.apples.iter().any(|apple| apple.position == cell) {
+                'A'
+            } else if self
+                .pickup
+                .as_ref()
+                .is_some_and(|(position, _)| *position == cell)
+            {
+                '*'
+            } else {
+                '.'
+            };
+
+            write!(f, "{}", symbol)?;
+
+            if cell.0 .0 + 1 == (self.bounds.width() - self.bounds.inset()) as i32 {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Largest x coordinate the snake's head can occupy on a canvas of the given width. Canvases too
+// small to hold even a single column still get one at x = 0.
+pub fn canvas_max_x(width: u16) -> u32 {
+    u32::from(width / 2).saturating_sub(2)
+}
+
+// Largest y coordinate the snake's head can occupy on a canvas of the given height.
+pub fn canvas_max_y(height: u16) -> u32 {
+    u32::from(height / 2).saturating_sub(3)
+}
+
+// Uniformly picks any cell the head could move to on a canvas of the given size.
+pub fn random_cell(width: u16, height: u16, rng: &mut GameRng) -> CanvasSpace {
+    CanvasBounds::from_canvas_size(width, height).random_cell(rng)
+}
+
+/// Where the leaderboard and achievements are kept: `$XDG_DATA_HOME/rusty-snake`, falling back
+/// to `~/.local/share/rusty-snake`.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+
+    Some(data_home.join("rusty-snake"))
+}
+
+// Scores stop at u32::MAX instead of overflowing.
+pub fn add_score(controller: &mut Controller, points: u32) {
+    controller.score = controller.score.saturating_add(points);
+}
+
+pub fn score_label(score: u32) -> String {
+    if score == u32::MAX {
+        String::from("MAX!")
+    } else {
+        score.to_string()
+    }
+}
+
+/// `elapsed` as `mm:ss`. Runs that take an hour or more just keep counting minutes.
+pub fn time_label(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Holds the round back for `COUNTDOWN_SECS`, see `count_down`.
+pub fn start_countdown(controller: &mut Controller) {
+    controller.screen = GameScreen::Countdown;
+    controller.countdown_ticks = COUNTDOWN_SECS * u32::from(controller.ticks_per_sec);
+}
+
+/// Runs a tick of the countdown. Once it is over the round starts. A snake that is waiting for a
+/// key sets off the way the first turn pressed during the countdown points, or on its own
+/// without one, see `starting_direction`.
+pub fn count_down(controller: &mut Controller) {
+    controller.countdown_ticks = controller.countdown_ticks.saturating_sub(1);
+    if controller.countdown_ticks > 0 {
+        return;
+    }
+
+    controller.screen = GameScreen::Playing;
+    if controller.snake.current_direction == Direction::Stop {
+        // Taken out of the buffer here, since once the snake moves a turn back the way it
+        // came would be thrown out.
+        let mut pressed = None;
+        while let Some(direction) = controller.turns.pop_front() {
+            if can_turn(&controller.snake, direction) {
+                pressed = Some(direction);
+                break;
+            }
+        }
+
+        controller.snake.current_direction = match pressed {
+            Some(direction) => direction,
+            None => starting_direction(controller, &controller.snake),
+        };
+    }
+    if let Some(mut second) = controller.second.take() {
+        if second.snake.current_direction == Direction::Stop {
+            second.snake.current_direction = starting_direction(controller, &second.snake);
+        }
+        controller.second = Some(second);
+    }
+}
+
+/// The seconds left on the countdown, rounded up.
+pub fn countdown_secs(controller: &Controller) -> u32 {
+    let ticks_per_sec = u32::from(controller.ticks_per_sec).max(1);
+    controller.countdown_ticks.div_ceil(ticks_per_sec)
+}
+
+/// Where `snake` goes without being told: straight on away from its tail, or right for a snake
+/// of a single segment. If that runs into the border or an obstacle right away, any other way
+/// that doesn't does, as long as it doesn't turn back onto the neck.
+pub fn starting_direction(controller: &Controller, snake: &Snake) -> Direction {
+    let head = snake.elements.front().map(|head| head.0);
+    let neck = snake.elements.get(1).map(|neck| neck.0);
+    let ahead = match (head, neck) {
+        (Some((x, y)), Some((neck_x, neck_y))) => match (x - neck_x, y - neck_y) {
+            (0, dy) if dy < 0 => Direction::Up,
+            (0, _) => Direction::Down,
+            (dx, _) if dx < 0 => Direction::Left,
+            _ => Direction::Right,
+        },
+        _ => Direction::Right,
+    };
+
+    let free = |direction: &Direction| {
+        let probe = Snake {
+            elements: snake.elements.clone(),
+            current_direction: *direction,
+        };
+        let next = if controller.wrap {
+            wrapped_head(&probe, &controller.bounds)
+        } else {
+            compute_new_head(&probe, &controller.bounds)
+        };
+
+        next.is_some_and(|next| {
+            Some(next.0) != neck
+                && !controller.obstacles.contains(&next)
+                && !snake.elements.contains(&next)
+        })
+    };
+
+    std::iter::once(ahead)
+        .chain([
+            Direction::Right,
+            Direction::Left,
+            Direction::Up,
+            Direction::Down,
+        ])
+        .find(free)
+        .unwrap_or(ahead)
+}
+
+/// Seconds until a golden apple disappears, if there is one on the board. With several, the
+/// one that goes first.
+pub fn golden_remaining_secs(controller: &Controller) -> Option<u32> {
+    let lifetime = AppleType::Golden.lifetime_ticks(controller.ticks_per_sec)?;
+
+    controller
+        .apples
+        .iter()
+        .filter(|apple| apple.apple_type == AppleType::Golden && apple.flash_ticks == 0)
+        .map(|apple| {
+            lifetime
+                .saturating_sub(apple.age)
+                .div_ceil(u32::from(controller.ticks_per_sec))
+        })
+        .min()
+}
+
+/// How much of the combo window is left before the multiplier drops back to x1.
+pub fn combo_remaining(controller: &Controller) -> Duration {
+    if controller.combo > 1 {
+        COMBO_WINDOW.saturating_sub(controller.stats.time_since_apple)
+    } else {
+        Duration::ZERO
+    }
+}
+
+/// The arrow key that turns the snake towards `direction`, for input that doesn't come from
+/// the keyboard.
+pub fn direction_key(direction: Direction) -> Option<event::KeyCode> {
+    match direction {
+        Direction::Up => Some(event::KeyCode::Up),
+        Direction::Down => Some(event::KeyCode::Down),
+        Direction::Left => Some(event::KeyCode::Left),
+        Direction::Right => Some(event::KeyCode::Right),
+        Direction::Stop => None,
+    }
+}
+
+/// The direction a turning action asks for.
+pub fn action_direction(action: Action) -> Option<Direction> {
+    match action {
+        Action::Up => Some(Direction::Up),
+        Action::Down => Some(Direction::Down),
+        Action::Left => Some(Direction::Left),
+        Action::Right => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+pub fn handle_events(controller: &mut Controller, events: &mpsc::Receiver<event::Event>) {
+    for e in events.try_iter() {
+        match e {
+            event::Event::Key(event) => {
+                // Raw mode turns ctrl+c into a plain key press instead of SIGINT. It quits from
+                // anywhere, so that the terminal is always restored on the way out.
+                if event.code == event::KeyCode::Char('c')
+                    && event.modifiers.contains(event::KeyModifiers::CONTROL)
+                {
+                    controller.screen = GameScreen::Closed;
+                    continue;
+                }
+
+                // Like in most terminal programs, ctrl+l repaints a screen that got garbled.
+                if event.code == event::KeyCode::Char('l')
+                    && event.modifiers.contains(event::KeyModifiers::CONTROL)
+                {
+                    controller.redraw_requested = true;
+                    continue;
+                }
+
+                if let Some(prompt) = &mut controller.name_prompt {
+                    if prompt.state == PromptState::Editing {
+                        prompt.handle_key(event.code);
+                        continue;
+                    }
+                }
+
+                // Neither opening nor closing the help counts as input, so the logo screen stays
+                // up behind it.
+                if controller.show_help {
+                    controller.show_help = false;
+                    continue;
+                }
+
+                // A demo is a screensaver, so any key wakes the terminal back up.
+                if controller.demo {
+                    controller.screen = GameScreen::Closed;
+                    continue;
+                }
+
+                let action = match controller.screen {
+                    GameScreen::Countdown | GameScreen::Playing => {
+                        controller.keys.playing_action(event.code)
+                    }
+                    _ => controller.keys.action(event.code),
+                };
+
+                match controller.screen {
+                    GameScreen::Menu | GameScreen::Closed => (),
+                    GameScreen::Playing => {
+                        if let Some(second) = controller
+                            .second
+                            .as_mut()
+                            .filter(|s| s.ai.is_none() && !s.remote)
+                        {
+                            if let Some(action) = keys::second_player_action(event.code) {
+                                second.turn = action_direction(action);
+                                continue;
+                            }
+                        }
+
+                        match action {
+                            Some(Action::Quit) => controller.screen = GameScreen::Closed,
+                            Some(Action::Sprint) => controller.sprint_ticks = SPRINT_TICKS,
+                            Some(Action::Up | Action::Down | Action::Left | Action::Right) => {
+                                buffer_turn(controller, action.and_then(action_direction))
+                            }
+                            Some(Action::ToggleGhost) => {
+                                controller.show_ghost = !controller.show_ghost
+                            }
+                            Some(Action::Help) => {
+                                controller.show_help = true;
+                                continue;
+                            }
+                            // Like the help, pausing doesn't count as input.
+                            Some(Action::Pause) => {
+                                controller.screen = GameScreen::Paused;
+                                controller.turns.clear();
+                                continue;
+                            }
+                            _ => (),
+                        }
+                        controller.last_event = Some(event::Event::Key(event));
+                    }
+                    // A turn is remembered for the first tick, like any other.
+                    GameScreen::Countdown => match action {
+                        Some(Action::Quit) => controller.screen = GameScreen::Closed,
+                        Some(Action::Up | Action::Down | Action::Left | Action::Right) => {
+                            buffer_turn(controller, action.and_then(action_direction));
+                            controller.last_event = Some(event::Event::Key(event))
+                        }
+                        _ => (),
+                    },
+                    // Turning while paused would only take effect on resuming, so only unpausing
+                    // and quitting do anything.
+                    GameScreen::Paused => match action {
+                        Some(Action::Pause) => controller.screen = GameScreen::Playing,
+                        Some(Action::Quit) => controller.screen = GameScreen::Closed,
+                        _ => (),
+                    },
+                    GameScreen::GameOver => match action {
+                        Some(Action::Quit) => controller.screen = GameScreen::Closed,
+                        Some(Action::Snapshot) => controller.snapshot_requested = true,
+                        Some(Action::Restart) => controller.restart_requested = true,
+                        Some(Action::Help) => controller.show_help = true,
+                        Some(Action::ToggleGhost) => controller.show_ghost = !controller.show_ghost,
+                        _ => (),
+                    },
+                }
+            }
+            // A click acts like the arrow key pointing towards it, so the same turning rules apply.
+            event::Event::Mouse(event::MouseEvent {
+                kind: event::MouseEventKind::Down(_),
+                column,
+                row,
+                ..
+            }) if controller.screen == GameScreen::Playing => {
+                let direction = match (&controller.mouse_origin, controller.snake.elements.front())
+                {
+                    (Some(origin), Some(head)) => click_direction(head, (column, row), origin),
+                    _ => None,
+                };
+
+                let code = match direction.and_then(direction_key) {
+                    Some(code) => code,
+                    None => continue,
+                };
+
+                buffer_turn(controller, direction);
+                controller.last_event = Some(event::Event::Key(code.into()));
+            }
+            event::Event::Mouse(event::MouseEvent {
+                kind: event::MouseEventKind::Moved | event::MouseEventKind::Drag(_),
+                column,
+                row,
+                ..
+            }) if controller.follow_cursor => controller.cursor = Some((column, row)),
+            _ => (),
+        }
+    }
+}
+
+// Keeps a turn for a coming tick, unless the buffer is full or the turn repeats the last one.
+fn buffer_turn(controller: &mut Controller, direction: Option<Direction>) {
+    let direction = match direction {
+        Some(direction) if direction != Direction::Stop => direction,
+        _ => return,
+    };
+
+    if controller.turns.len() < TURN_BUFFER && controller.turns.back() != Some(&direction) {
+        controller.turns.push_back(direction);
+    }
+}
+
+/// Makes the oldest buffered turn that still changes where the snake goes the key press the
+/// next tick acts on. The ones before it, which would go straight on or back onto the neck, are
+/// dropped and the ones after it wait for the ticks after. Without any, a snake following the
+/// mouse cursor turns towards it. Called right before each tick, ahead of anything that looks at
+/// `last_event` for it, like recording the replay.
+pub fn take_buffered_turn(controller: &mut Controller) {
+    let snake = &controller.snake;
+    let turn = |direction: &Direction| {
+        *direction != snake.current_direction && can_turn(snake, *direction)
+    };
+
+    let direction = loop {
+        match controller.turns.pop_front() {
+            Some(direction) if turn(&direction) => break Some(direction),
+            Some(_) => (),
+            None => break follow_directions(controller).into_iter().find(turn),
+        }
+    };
+
+    if let Some(code) = direction.and_then(direction_key) {
+        controller.last_event = Some(event::Event::Key(code.into()));
+    }
+}
+
+// The ways towards the mouse cursor when following it, see `directions_towards`.
+fn follow_directions(controller: &Controller) -> Vec<Direction> {
+    match (
+        &controller.mouse_origin,
+        controller.snake.elements.front(),
+        controller.cursor,
+    ) {
+        (Some(origin), Some(head), Some(cursor)) if controller.follow_cursor => {
+            directions_towards(head, cursor, origin)
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Whether `snake` may turn towards `direction`, which it can't if that reverses it onto its own
+// neck.
+fn can_turn(snake: &Snake, direction: Direction) -> bool {
+    let reverse = match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+        Direction::Stop => return false,
+    };
+
+    snake.current_direction != reverse && neck_direction(snake) != Some(direction)
+}
+
+// Turns `snake` towards `direction`, unless that would reverse it onto its own neck.
+fn steer(snake: &mut Snake, direction: Option<Direction>) {
+    match direction {
+        Some(direction) if can_turn(snake, direction) => snake.current_direction = direction,
+        _ => (),
+    }
+}
+
+pub fn continue_game_logic(controller: &mut Controller) {
+    // What the player's snake does is up to its pilot, and without one up to the keyboard.
+    let turn = match &controller.pilot {
+        Some(pilot) => pilot.next_move(&BoardView::new(controller, &controller.snake)),
+        None => Keyboard.next_move(&BoardView::new(controller, &controller.snake)),
+    };
+    steer(&mut controller.snake, Some(turn));
+    // The computer only starts once the player has.
+    if controller.snake.current_direction != Direction::Stop {
+        if let Some(turn) = ai::turn(controller) {
+            if let Some(second) = &mut controller.second {
+                second.turn = Some(turn);
+            }
+        }
+    }
+    if let Some(second) = &mut controller.second {
+        steer(&mut second.snake, second.turn.take());
+    }
+
+    // The length of this tick, taken before the effects that set it run out.
+    let interval =
+        effects::tick_interval(speed::ticks_per_sec(controller), &controller.active_effects);
+    effects::tick_effects(&mut controller.active_effects);
+
+    if controller.snake.current_direction != Direction::Stop {
+        controller.stats.ticks += 1;
+        controller.stats.elapsed += interval;
+        controller.stats.time_since_apple += interval;
+
+        if controller.stats.time_since_apple > COMBO_WINDOW {
+            controller.combo = 1;
+        }
+        controller.stats.ticks_since_apple += 1;
+        for apple in &mut controller.apples {
+            apple.age += 1;
+        }
+    }
+
+    // Eaten apples go once their flash is over, the ones that disappear on their own once their
+    // time is up.
+    let ticks_per_sec = controller.ticks_per_sec;
+    let apples = controller.apples.len();
+    controller.apples.retain_mut(|apple| {
+        if apple.flash_ticks > 0 {
+            apple.flash_ticks -= 1;
+            return apple.flash_ticks > 0;
+        }
+
+        apple
+            .apple_type
+            .lifetime_ticks(ticks_per_sec)
+            .is_none_or(|lifetime| apple.age < lifetime)
+    });
+    if controller.apples.len() < apples {
+        controller.spawn_apple_next_tick = true;
+    }
+
+    // Retried every tick until there is room for all apples.
+    if controller.spawn_apple_next_tick {
+        controller.spawn_apple_next_tick = !spawn_apples(controller);
+    }
+
+    // Sprinting moves the snake twice per tick, but apples eaten on the way don't score.
+    let sprinting = controller.sprint_ticks > 0;
+    controller.sprint_ticks = controller.sprint_ticks.saturating_sub(1);
+
+    for _ in 0..if sprinting { 2 } else { 1 } {
+        move_snake(controller, !sprinting);
+
+        if controller.screen == GameScreen::GameOver {
+            controller.cues.push(if controller.won {
+                Cue::Victory
+            } else {
+                Cue::GameOver
+            });
+            break;
+        }
+    }
+
+    if controller.second.is_some() {
+        let was_over = controller.screen == GameScreen::GameOver;
+        move_second_player(controller);
+
+        if !was_over && controller.screen == GameScreen::GameOver {
+            controller.cues.push(Cue::GameOver);
+        }
+    }
+
+    if controller.screen != GameScreen::GameOver {
+        apply_mode_rules(controller, interval);
+
+        if controller.screen == GameScreen::GameOver {
+            controller.cues.push(Cue::GameOver);
+        }
+    }
+}
+
+// Picks a random cell that is covered by neither the snake, an obstacle, an apple nor the
+// pickup, nor `avoid`. On a board with no such cell left there is nothing to pick.
+fn random_free_cell(controller: &mut Controller, avoid: &[CanvasSpace]) -> Option<CanvasSpace> {
+    let free: Vec<CanvasSpace> = controller
+        .bounds
+        .iter_cells()
+        .filter(|cell| !avoid.contains(cell))
+        .filter(|cell| {
+            let on_apple = controller
+                .apples
+                .iter()
+                .any(|apple| apple.position == *cell);
+            let on_pickup =
+                matches!(&controller.pickup, Some((pickup_pos, _)) if pickup_pos == cell);
+
+            let on_second = controller
+                .second
+                .as_ref()
+                .is_some_and(|second| second.snake.elements.contains(cell));
+
+            !on_apple
+                && !on_pickup
+                && !on_second
+                && !controller.snake.elements.contains(cell)
+                && !controller.obstacles.contains(cell)
+                && !is_portal(&controller.portals, cell)
+        })
+        .collect();
+
+    free.choose(&mut controller.rng).cloned()
+}
+
+// Places new apples on free cells until there are `apple_count` of them, and reports whether
+// there was room for all. The cell right in front of the head is left out, since an apple there
+// would be eaten before it was ever drawn.
+fn spawn_apples(controller: &mut Controller) -> bool {
+    let mut avoid = controller.no_apples.clone();
+    if controller.snake.current_direction != Direction::Stop {
+        avoid.extend(next_head(controller));
+    }
+
+    while controller.apples.len() < controller.apple_count {
+        let apple_type = if controller.golden_due {
+            AppleType::Golden
+        } else if controller.rng.gen_ratio(1, POISON_CHANCE) {
+            AppleType::Poison
+        } else {
+            AppleType::Fresh(APPLE[controller.rng.gen_range(0..APPLE.len())])
+        };
+
+        match random_free_cell(controller, &avoid) {
+            Some(cell) => {
+                if apple_type == AppleType::Golden {
+                    controller.golden_due = false;
+                }
+                controller.apples.push(Apple::new(cell, apple_type));
+            }
+            None => return false,
+        }
+    }
+
+    true
+}
+
+/// Adds an obstacle at a random free cell whenever the score reaches another multiple of
+/// `obstacles_every`, unless the board is already crowded.
+pub fn maybe_spawn_obstacle(controller: &mut Controller) {
+    let every = match controller.obstacles_every {
+        Some(every) if every > 0 => every,
+        _ => return,
+    };
+
+    if controller.score == 0 || !controller.score.is_multiple_of(every) {
+        return;
+    }
+
+    let cells = u64::from(controller.bounds.width()) * u64::from(controller.bounds.height());
+    let covered = (controller.snake.elements.len() + controller.obstacles.len()) as u64;
+
+    if covered * 100 >= cells * u64::from(MAX_OBSTACLE_COVERAGE) {
+        return;
+    }
+
+    if let Some(cell) = random_free_cell(controller, &[]) {
+        controller.obstacles.push(cell);
+    }
+}
+
+/// The cell the head moves into on this tick: one step from the current head in the current
+/// direction. `None` means the step would leave `bounds`, i.e. the snake hits a wall, or the
+/// snake isn't moving at all. The snake itself is left untouched; the caller pushes the new head
+/// to the front of the body and drops the tail.
+pub fn compute_new_head(snake: &Snake, bounds: &CanvasBounds) -> Option<CanvasSpace> {
+    step(snake).filter(|new_head| bounds.contains(new_head))
+}
+
+/// Like `compute_new_head`, but a step off the board comes back in on the opposite edge.
+pub fn wrapped_head(snake: &Snake, bounds: &CanvasBounds) -> Option<CanvasSpace> {
+    let (x, y) = step(snake)?.0;
+    let inset = bounds.inset() as i32;
+    let (width, height) = bounds.open_size();
+
+    Some(CanvasSpace((
+        inset + (x - inset).rem_euclid(width as i32),
+        inset + (y - inset).rem_euclid(height as i32),
+    )))
+}
+
+/// Where the head goes on the next tick, if it moves and stays alive on the board.
+pub fn next_head(controller: &Controller) -> Option<CanvasSpace> {
+    if controller.wrap {
+        wrapped_head(&controller.snake, &controller.bounds)
+    } else {
+        compute_new_head(&controller.snake, &controller.bounds)
+    }
+}
+
+fn step(snake: &Snake) -> Option<CanvasSpace> {
+    let (x, y) = snake.elements.front()?.0;
+
+    match snake.current_direction {
+        Direction::Left => Some((x - 1, y)),
+        Direction::Right => Some((x + 1, y)),
+        Direction::Up => Some((x, y - 1)),
+        Direction::Down => Some((x, y + 1)),
+        Direction::Stop => None,
+    }
+    .map(CanvasSpace)
+}
+
+pub fn at_max_length(controller: &Controller) -> bool {
+    controller
+        .max_length
+        .is_some_and(|max_length| controller.snake.elements.len() >= max_length)
+}
+
+pub fn is_portal(portals: &Option<(CanvasSpace, CanvasSpace)>, cell: &CanvasSpace) -> bool {
+    matches!(portals, Some((a, b)) if a == cell || b == cell)
+}
+
+/// Where a head stepping onto `cell` ends up: the other mouth if `cell` is a portal, otherwise
+/// `cell` itself. The body follows cell by cell, so for a few ticks it is split across both
+/// mouths without any gap in the list of segments.
+pub fn through_portal(
+    portals: &Option<(CanvasSpace, CanvasSpace)>,
+    cell: CanvasSpace,
+) -> CanvasSpace {
+    match portals {
+        Some((a, b)) if *a == cell => b.clone(),
+        Some((a, b)) if *b == cell => a.clone(),
+        _ => cell,
+    }
+}
+
+/// Places a pair of portals on random free cells, if there is room for two.
+pub fn place_portals(controller: &mut Controller) {
+    if let Some(a) = random_free_cell(controller, &[]) {
+        if let Some(b) = random_free_cell(controller, std::slice::from_ref(&a)) {
+            controller.portals = Some((a, b));
+        }
+    }
+}
+
+// Swaps the snake and score of the first player with those of the second, see
+// `move_second_player`.
+fn swap_players(controller: &mut Controller) {
+    if let Some(second) = &mut controller.second {
+        std::mem::swap(&mut controller.snake, &mut second.snake);
+        std::mem::swap(&mut controller.score, &mut second.score);
+    }
+}
+
+// The second snake goes through the same rules as the first by briefly taking its place, so that
+// walls, apples and pickups treat both alike. Its death is kept apart from the first player's.
+// Afterwards either snake running into the other dies, both on a head-on collision, and the game
+// is over as soon as one of them is dead.
+fn move_second_player(controller: &mut Controller) {
+    let screen = controller.screen;
+    let death = controller.stats.death.take();
+    controller.screen = GameScreen::Playing;
+
+    swap_players(controller);
+    move_snake(controller, true);
+    swap_players(controller);
+
+    let second_death = controller.stats.death.take();
+    controller.stats.death = death;
+    controller.screen = screen;
+
+    let second = match &mut controller.second {
+        Some(second) => second,
+        None => return,
+    };
+    second.death = second.death.or(second_death);
+
+    let first = &controller.snake.elements;
+    let first_moving = controller.snake.current_direction != Direction::Stop;
+    let second_moving = second.snake.current_direction != Direction::Stop;
+
+    if first_moving
+        && first
+            .front()
+            .is_some_and(|head| second.snake.elements.contains(head))
+    {
+        controller.stats.death = controller.stats.death.or(Some(Death::Rival));
+    }
+    if second_moving
+        && second
+            .snake
+            .elements
+            .front()
+            .is_some_and(|head| first.contains(head))
+    {
+        second.death = second.death.or(Some(Death::Rival));
+    }
+
+    if controller.stats.death.is_some() || second.death.is_some() {
+        controller.screen = GameScreen::GameOver;
+    }
+}
+
+/// Which player won a finished two player game: the one still alive, or the one with more points
+/// if both died on the same tick. `None` on a draw and in single player games.
+pub fn winner(controller: &Controller) -> Option<u8> {
+    let second = controller.second.as_ref()?;
+
+    match (controller.stats.death.is_some(), second.death.is_some()) {
+        (false, true) => Some(1),
+        (true, false) => Some(2),
+        _ => match controller.score.cmp(&second.score) {
+            std::cmp::Ordering::Greater => Some(1),
+            std::cmp::Ordering::Less => Some(2),
+            std::cmp::Ordering::Equal => None,
+        },
+    }
+}
+
+/// Puts the second player's snake of `length` segments in the lower half of the board, clear of
+/// the first one. Fails if it doesn't fit there.
+pub fn place_second_player(controller: &mut Controller, length: usize) -> Result<(), String> {
+    let bounds = controller.bounds;
+    let center = bounds.center();
+    let head = CanvasSpace((center.0 .0, (bounds.height() * 3 / 4) as i32));
+
+    let mut taken = controller.obstacles.clone();
+    taken.extend(controller.snake.elements.iter().cloned());
+
+    let elements = starting_snake(&bounds, &taken, &head, length).ok_or(format!(
+        "There is no room for a second snake of length {}.",
+        length
+    ))?;
+    controller.second = Some(Player::new(elements));
+
+    Ok(())
+}
+
+fn move_snake(controller: &mut Controller, score_apples: bool) {
+    let bounds = controller.bounds;
+    let portals = &controller.portals;
+    let snake = &mut controller.snake;
+
+    // Collisions are only checked once the head has actually moved, otherwise an apple spawning
+    // on the resting head would be collected for free.
+    let moving = snake.current_direction != Direction::Stop;
+
+    // The body is a ring buffer with the head at the front: moving pushes the new head and drops
+    // the tail. Growing puts the tail back, so it simply doesn't move for one tick.
+    let mut old_tail = None;
+
+    if moving {
+        // An exit blocked by the body is caught by the self-collision check below.
+        let new_head = if controller.wrap {
+            wrapped_head(snake, &bounds)
+        } else {
+            compute_new_head(snake, &bounds)
+        }
+        .map(|new_head| through_portal(portals, new_head));
+
+        match new_head {
+            Some(new_head) => {
+                snake.elements.push_front(new_head);
+                old_tail = snake.elements.pop_back();
+            }
+            None => {
+                controller.screen = GameScreen::GameOver;
+                controller.stats.death = Some(Death::Wall);
+            }
+        }
+    }
+
+    // Check if snake collides with an apple. One that is already flashing has been eaten.
+    let head = snake.elements.front().expect("First element should exist.");
+    let eaten = controller
+        .apples
+        .iter()
+        .position(|apple| moving && apple.flash_ticks == 0 && apple.position == *head);
+
+    if let Some(index) = eaten {
+        controller.apples[index].flash_ticks = FLASH_TICKS;
+    }
+    let eaten = eaten.map(|index| controller.apples[index].apple_type);
+
+    if let Some(apple_type) = eaten.filter(|&apple_type| apple_type != AppleType::Poison) {
+        // Golden apples are a bonus, so they ring like a pickup.
+        controller.cues.push(if apple_type == AppleType::Golden {
+            Cue::BonusCollected
+        } else {
+            Cue::AppleEaten
+        });
+        controller.combo = if controller.stats.apples_eaten > 0
+            && controller.stats.time_since_apple <= COMBO_WINDOW
+        {
+            (controller.combo + 1).min(MAX_COMBO)
+        } else {
+            1
+        };
+        controller.stats.best_combo = controller.stats.best_combo.max(controller.combo);
+        controller.stats.apples_eaten += 1;
+        if controller.stats.apples_eaten.is_multiple_of(GOLDEN_EVERY) {
+            controller.golden_due = true;
+        }
+        controller.stats.ticks_since_apple = 0;
+        controller.stats.time_since_apple = Duration::ZERO;
+        // A snake at its maximum length stops growing, but its apples are worth double.
+        let capped = at_max_length(controller);
+
+        if !capped {
+            if let Some(old_tail) = old_tail.take() {
+                controller.snake.elements.push_back(old_tail);
+            }
+        }
+        if score_apples {
+            add_score(
+                controller,
+                effects::apple_points(&controller.active_effects)
+                    .saturating_mul(apple_type.points())
+                    .saturating_mul(controller.combo)
+                    .saturating_mul(if capped { 2 } else { 1 }),
+            );
+            maybe_spawn_obstacle(controller);
+        }
+
+        if controller.pickup.is_none() && controller.rng.gen_ratio(1, effects::PICKUP_CHANCE) {
+            let pickup = *effects::PICKUPS
+                .choose(&mut controller.rng)
+                .expect("There is at least one pickup.");
+            controller.pickup = random_free_cell(controller, &[]).map(|cell| (cell, pickup));
+        }
+    }
+
+    // Poison takes segments off the tail instead of growing the snake.
+    if eaten == Some(AppleType::Poison) {
+        controller.cues.push(Cue::AppleEaten);
+        controller.score = controller.score.saturating_sub(POISON_PENALTY);
+
+        if controller.snake.elements.len() == 1 {
+            controller.screen = GameScreen::GameOver;
+            controller.stats.death = Some(Death::Poisoned);
+        } else {
+            for _ in 0..POISON_SEGMENTS {
+                shrink_tail(controller);
+            }
+        }
+    }
+
+    // Check if snake collides with a pickup
+    if let Some((ref pickup_pos, pickup)) = controller.pickup {
+        if moving
+            && pickup_pos
+                == controller
+                    .snake
+                    .elements
+                    .front()
+                    .expect("First element should exist.")
+        {
+            controller.pickup = None;
+            controller.cues.push(Cue::BonusCollected);
+            effects::apply_effect(
+                &mut controller.active_effects,
+                pickup,
+                controller.ticks_per_sec,
+            );
+        }
+    }
+
+    let snake = &mut controller.snake;
+
+    // Check if first element collides with an other element. Growing never duplicates a
+    // segment, so the head can be compared against the whole rest of the body. An invincible
+    // snake passes through both itself and the obstacles.
+    if moving && !effects::is_active(&controller.active_effects, Effect::Invincible) {
+        let head = snake
+            .elements
+            .front()
+            .expect("Snake has at least one element.");
+
+        if snake.elements.iter().skip(1).any(|element| element == head) {
+            controller.screen = GameScreen::GameOver;
+            controller.stats.death = Some(Death::SelfCollision);
+        } else if controller.obstacles.contains(head) {
+            controller.screen = GameScreen::GameOver;
+            controller.stats.death = Some(Death::Obstacle);
+        }
+    }
+
+    // Once the snake covers every cell there is nowhere left to go. That ends the run just like
+    // losing does, but counts as a win.
+    if controller.screen != GameScreen::GameOver
+        && controller.snake.elements.len() >= playable_cells(controller)
+    {
+        controller.screen = GameScreen::GameOver;
+        controller.won = true;
+    }
+}
+
+/// Number of cells the snake can occupy, which is every cell that isn't an obstacle.
+pub fn playable_cells(controller: &Controller) -> usize {
+    let (width, height) = controller.bounds.open_size();
+    let walls = controller
+        .obstacles
+        .iter()
+        .filter(|obstacle| controller.bounds.contains(obstacle))
+        .count();
+
+    (width as usize * height as usize).saturating_sub(walls)
+}
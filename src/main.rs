@@ -1,359 +1,3216 @@
-use std::io::{stdout, Write};
-use std::ops::Deref;
-use std::sync::{mpsc, Arc, Mutex};
+use std::fs::File;
+use std::io::{self, stdout, BufWriter, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Instant;
 use std::{thread, time};
 
 use crossterm::{
     cursor, event,
-    style::{self, Colorize},
+    style::{self, Color, ContentStyle},
     terminal, ExecutableCommand, QueueableCommand,
 };
+use rusty_snake::achievements::{self, Achievement, Progress};
+use rusty_snake::ai::Skill;
+use rusty_snake::bench;
+use rusty_snake::board;
+use rusty_snake::bot::{Bot, Registry};
+use rusty_snake::broadcast::{self, Broadcaster, FrameSnapshot};
+use rusty_snake::cast::CastWriter;
+use rusty_snake::config::{self, Config};
+use rusty_snake::difficulty::Difficulty;
+use rusty_snake::editor::{Editor, EditorInput};
+use rusty_snake::effects::{self, ActiveEffect};
+use rusty_snake::feedback::{Feedback, Silent, TerminalBell};
+use rusty_snake::ghost::{self, Ghost};
+use rusty_snake::glyphs::{self, Charset, RenderConfig};
+use rusty_snake::keys::key_label;
+use rusty_snake::keys::Action;
+use rusty_snake::keys::KeyBindings;
+use rusty_snake::leaderboard::{self, Entry, Leaderboard, NamePrompt, PromptState};
+use rusty_snake::level::{Level, Tile};
+use rusty_snake::menu::{MainMenuItem, Menu, MenuInput, MAIN_MENU};
+use rusty_snake::net::{Client, Host};
+use rusty_snake::online::{self, Endpoint, Submission};
+use rusty_snake::render::{layout_status_row, Align, Frame, Layer, Renderer, Screen, StatusField};
+use rusty_snake::replay::{Playback, Replay};
+use rusty_snake::rng::GameRng;
+use rusty_snake::save;
+use rusty_snake::server;
+use rusty_snake::smooth::{self, Motion};
+use rusty_snake::snapshot;
+use rusty_snake::speed::{self, Ramp};
+use rusty_snake::telnet;
+use rusty_snake::theme::{self, Theme};
+use rusty_snake::timing::{FixedTimestep, TickRateMeter, TimingStats};
+use rusty_snake::{
+    action_direction, at_max_length, combo_remaining, continue_game_logic, count_down,
+    countdown_secs, golden_remaining_secs, handle_events, place_portals, place_second_player,
+    place_snake, score_label, start_countdown, take_buffered_turn, ticks_until_hungry, time_label,
+    validate_game_config, winner, AppleType, CanvasBounds, CanvasSpace, Controller, Direction,
+    GameConfig, GameMode, GameScreen, Snake, StartPosition, TerminalSpace, CANVAS_HEIGHT,
+    CANVAS_WIDTH, COMBO_WINDOW, HUNGER_TICKS, MAX_START_LENGTH,
+};
 
-const CANVAS_WIDTH: u16 = 46;
-const CANVAS_HEIGHT: u16 = 46;
-
-const TICKS_PER_SEC: u16 = 10;
+const BAR_WIDTH: usize = 10;
+
+// Fastest --speed, in ticks per second. Terminals can't redraw much faster than this.
+const MAX_SPEED: u16 = 60;
+
+// Most apples --apples puts on the board at once.
+const MAX_APPLES: usize = 20;
+
+const USAGE: &str = "Usage: rusty-snake [--broadcast <addr> | --spectate <addr> | --host <addr> |
+                    --join <addr> [--room <name>] | --watch <addr> [--room <name>] |
+                    --server <addr> | --telnet <addr> | --replay <path> | --edit <path>]
+                   [--show-timing]
+                   [--fps-counter] [--no-menu] [--demo] [--bot <keyboard|greedy>]
+                   [--sound | --no-sound]
+                   [--snake-char <str>] [--apple-char <str>] [--grid] [--no-color] [--smooth]
+                   [--theme <classic|matrix|monochrome|pastel>] [--ascii] [--colorblind]
+                   [--mouse | --mouse-follow] [--start <center|random|x,y>] [--start-length <n>]
+                   [--difficulty <easy|normal|hard|insane>]
+                   [--mode <classic|hunger|shrink>] [--portals] [--wrap | --no-wrap]
+                   [--two-players | --ai <easy|normal|hard>]
+                   [--layout <cross|pillars|scatter>]
+                   [--level <path>] [--apples <n>]
+                   [--width <columns>] [--height <rows> | --fit] [--speed <ticks per second>]
+                   [--ramp <linear|exponential> [--max-speed <ticks per second>]]
+                   [--max-length <n>] [--save <path>] [--load <path>] [--bench] [--seed <n>]
+                   [--snapshot <path> [--force]] [--cast <path>] [--record <path>]
+                   [--ghost <path> | --no-ghost] [--obstacles-every <points>]";
+
+// Ticks the game over animation plays for before the end screen appears.
+const DEATH_ANIMATION_TICKS: u32 = 20;
+
+// Frames a demo shows the end screen for before playing again.
+const DEMO_END_SCREEN_FRAMES: u32 = 60;
+
+// How long a newly unlocked achievement stays on screen.
+const TOAST_SECS: u32 = 3;
+
+// With --smooth, frames are drawn at about 30 FPS. A frame that takes longer than this to draw
+// switches back to drawing once per tick.
+const SMOOTH_FRAME_BUDGET: time::Duration = time::Duration::from_millis(33);
+
+// Replays play at most this many times faster, or slower, than they were recorded.
+const MAX_REPLAY_SPEED: f64 = 8.0;
+
+const INPUT_POLL_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+// What the game draws on: the terminal it was started in, or a telnet client's.
+trait Terminal: Write {
+    fn size(&self) -> crossterm::Result<(u16, u16)>;
+}
 
-const BORDER_STYLE: [char; 6] = ['│', '─', '╭', '╮', '╰', '╯'];
+// The terminal the game was started in, recorded with --cast or not.
+struct LocalTerminal(Box<dyn Write>);
 
-const APPLE: [char; 2] = ['🍎', '🍏'];
+impl Write for LocalTerminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
 
-#[derive(Debug)]
-struct AppleType(char);
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
 
-#[derive(Debug, PartialEq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-    Stop,
+impl Terminal for LocalTerminal {
+    fn size(&self) -> crossterm::Result<(u16, u16)> {
+        terminal::size()
+    }
 }
 
-#[derive(Debug)]
-struct Controller {
-    should_close: bool,
-    event_queue: Arc<Mutex<Vec<event::Event>>>,
-    last_event: Option<event::Event>,
-    snake: Snake,
-    apple: Option<(CanvasSpace, AppleType)>,
-    score: u32,
-    losed: bool,
+impl Terminal for telnet::Connection {
+    fn size(&self) -> crossterm::Result<(u16, u16)> {
+        Ok(telnet::Connection::size(self))
+    }
 }
 
-#[derive(Debug)]
-struct Snake {
-    elements: Vec<CanvasSpace>,
-    current_direction: Direction,
+// Terminal geometry, looked up once per frame and shared by all draw functions.
+struct FrameContext {
+    terminal_width: u16,
+    terminal_height: u16,
+    left_border: u16,
+    right_border: u16,
+    upper_border: u16,
+    lower_border: u16,
+    origin: TerminalSpace,
+    no_color: bool,
+    theme: Theme,
+    charset: Charset,
+    truecolor: bool,
+    // Everything is drawn faded, like the board while paused.
+    dimmed: bool,
+    // The smallest terminal the board fits on.
+    needed: (u16, u16),
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct CanvasSpace((u32, u32));
+impl FrameContext {
+    fn new(
+        terminal: &impl Terminal,
+        bounds: &CanvasBounds,
+        render: &RenderConfig,
+    ) -> crossterm::Result<Self> {
+        let (terminal_width, terminal_height) = terminal.size()?;
+        let origin = TerminalSpace::canvas_origin((terminal_width, terminal_height), bounds);
+
+        // A shrinking board keeps its place on screen while the border moves in.
+        let inset = bounds.inset() as u16;
+        let (width, height) = bounds.open_size();
+        let left_border = origin.0 .0 as u16 + inset * 2;
+        let upper_border = origin.0 .1 as u16 + inset;
+
+        // The border sits right outside the outermost cells, which are two columns wide.
+        Ok(FrameContext {
+            terminal_width,
+            terminal_height,
+            left_border,
+            right_border: left_border.saturating_add(width as u16 * 2 + 1),
+            upper_border,
+            lower_border: upper_border.saturating_add(height as u16 + 1),
+            origin,
+            no_color: render.no_color,
+            theme: render.theme,
+            charset: render.charset,
+            truecolor: render.truecolor,
+            dimmed: false,
+            needed: bounds.terminal_size(),
+        })
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-struct TerminalSpace((u32, u32));
+    // Whether the board would be cut off.
+    fn too_small(&self) -> bool {
+        self.terminal_width < self.needed.0 || self.terminal_height < self.needed.1
+    }
+
+    fn plain(&self) -> ContentStyle {
+        if self.dimmed {
+            ContentStyle::new().attribute(style::Attribute::Dim)
+        } else {
+            ContentStyle::new()
+        }
+    }
 
-impl From<CanvasSpace> for TerminalSpace {
-    fn from(canvas_space: CanvasSpace) -> Self {
-        let (terminal_width, terminal_height) = terminal::size().unwrap();
+    // Colors are dropped entirely on monochrome terminals, see https://no-color.org/.
+    fn colored(&self, color: Color) -> ContentStyle {
+        if self.no_color {
+            self.plain()
+        } else {
+            self.plain().foreground(color)
+        }
+    }
+
+    // Segment `index` of a snake drawn in `color`, which fades towards the tail where the
+    // terminal can show it.
+    fn snake_shade(&self, color: Color, index: usize, length: usize) -> Color {
+        if self.truecolor {
+            theme::gradient(color, index, length)
+        } else {
+            color
+        }
+    }
+
+    // The column `text` starts at to be centered.
+    fn centered(&self, text: &str) -> u16 {
+        (self.terminal_width / 2).saturating_sub(text.chars().count() as u16 / 2)
+    }
+}
+
+// Draws to the terminal behind `writer` with crossterm, for one frame. Frames are drawn onto
+// `screen` and only what changed since the last one is printed.
+struct TerminalRenderer<'a, W: Write> {
+    writer: &'a mut W,
+    screen: &'a mut Screen,
+    frame: Frame,
+    origin: TerminalSpace,
+    // Set when the terminal is too small for the board, to the size it needs.
+    too_small: Option<(u16, u16)>,
+}
 
-        TerminalSpace((
-            (terminal_width / 2).saturating_sub(CANVAS_WIDTH / 2) as u32
-                + canvas_space.0 .0 as u32 * 2
-                + 1,
-            (terminal_height / 2).saturating_sub(CANVAS_HEIGHT / 4) as u32
-                + canvas_space.0 .1 as u32
-                + 1,
-        ))
+impl<'a, W: Write> TerminalRenderer<'a, W> {
+    fn new(writer: &'a mut W, screen: &'a mut Screen, ctx: &FrameContext) -> Self {
+        screen.resize(ctx.terminal_width, ctx.terminal_height);
+
+        TerminalRenderer {
+            writer,
+            screen,
+            frame: Frame::new(),
+            origin: ctx.origin.clone(),
+            too_small: Some(ctx.needed).filter(|_| ctx.too_small()),
+        }
     }
 }
 
-impl Deref for AppleType {
-    type Target = char;
+impl<W: Write> Renderer for TerminalRenderer<'_, W> {
+    fn clear(&mut self) -> crossterm::Result<()> {
+        self.frame.clear();
+        Ok(())
+    }
+
+    fn set_layer(&mut self, layer: Layer) {
+        self.frame.set_layer(layer);
+    }
+
+    fn draw_cell(
+        &mut self,
+        cell: &CanvasSpace,
+        glyph: &str,
+        style: ContentStyle,
+    ) -> crossterm::Result<()> {
+        let position = TerminalSpace::from_canvas(cell, &self.origin);
+        self.draw_text(position.0 .0 as u16, position.0 .1 as u16, glyph, style)
+    }
+
+    fn draw_text(
+        &mut self,
+        x: u16,
+        y: u16,
+        text: &str,
+        style: ContentStyle,
+    ) -> crossterm::Result<()> {
+        self.frame.draw_text(x, y, text, style);
+        Ok(())
+    }
+
+    fn present(&mut self) -> crossterm::Result<()> {
+        // Rather than a board cut off at the edges, which is all but unreadable.
+        if let Some((width, height)) = self.too_small {
+            let (columns, rows) = self.screen.next().size();
+            let lines = [
+                String::from("Terminal too small"),
+                format!(
+                    "Need at least {}x{}, got {}x{}",
+                    width, height, columns, rows
+                ),
+            ];
+
+            self.frame.clear();
+            self.frame.set_layer(Layer::Hud);
+            for (index, line) in lines.iter().enumerate() {
+                let x = (columns / 2).saturating_sub(line.len() as u16 / 2);
+                let y = (rows / 2).saturating_sub(1) + index as u16;
+                self.frame.draw_text(x, y, line, ContentStyle::new());
+            }
+        }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+        self.frame.compose(self.screen.next_mut());
+        let (from_scratch, spans) = self.screen.changes();
+        if from_scratch {
+            self.writer
+                .queue(terminal::Clear(terminal::ClearType::All))?;
+        }
+
+        // Runs of the same style go out in one piece.
+        let grid = self.screen.next();
+        for span in spans {
+            self.writer.queue(cursor::MoveTo(span.start, span.y))?;
+
+            let cells = &grid.row(span.y)[usize::from(span.start)..usize::from(span.end)];
+            for run in cells.chunk_by(|a, b| a.style == b.style) {
+                let text: String = run.iter().map(|cell| cell.symbol.as_str()).collect();
+                self.writer
+                    .queue(style::PrintStyledContent(run[0].style.apply(text)))?;
+            }
+        }
+
+        self.writer.flush()?;
+        self.screen.presented();
+        Ok(())
     }
 }
 
-fn draw(writer: &mut impl Write, controller: &Controller) -> crossterm::Result<()> {
-    writer.queue(terminal::Clear(terminal::ClearType::All))?;
+fn draw(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    controller: &Controller,
+    ghost: Option<&[CanvasSpace]>,
+    partial_cells: &[(CanvasSpace, &str)],
+    toast: Option<&str>,
+) -> crossterm::Result<()> {
+    renderer.clear()?;
+    renderer.set_layer(Layer::Board);
+
+    draw_borders(renderer, ctx)?;
+
+    if render.grid {
+        draw_grid(renderer, ctx, &controller.bounds)?;
+    }
+
+    // Everything below is drawn on top of the ghost.
+    if let Some(ghost) = ghost {
+        draw_ghost(renderer, ctx, render, ghost)?;
+    }
+
+    draw_obstacles(renderer, ctx, &controller.obstacles)?;
+
+    if let Some(portals) = &controller.portals {
+        draw_portals(renderer, ctx, portals)?;
+    }
+
+    renderer.set_layer(Layer::Entities);
+    draw_snake(renderer, ctx, render, &controller.snake, 0)?;
+    if let Some(second) = &controller.second {
+        draw_snake(renderer, ctx, render, &second.snake, 1)?;
+    }
+    draw_partial_cells(renderer, ctx, &controller.snake, partial_cells)?;
+    draw_apples(renderer, ctx, render, controller)?;
+    draw_pickup(renderer, ctx, controller)?;
+
+    renderer.set_layer(Layer::Hud);
+    draw_score(renderer, ctx, controller)?;
+    draw_status_row(renderer, ctx, controller, toast)?;
+    draw_length(renderer, ctx, controller)?;
+
+    if controller.sprint_ticks > 0 {
+        draw_sprint_indicator(renderer, ctx)?;
+    }
 
-    draw_borders(writer)?;
-    draw_snake(writer, &controller.snake)?;
-    draw_apple(writer, controller)?;
+    if let Some(active) = controller.active_effects.first() {
+        draw_effect_indicator(renderer, ctx, active, controller.ticks_per_sec)?;
+    }
 
     if let Some(_event) = controller.last_event {
         /*
         This was helpful while debugging to see which keys were pressed.
-        writer
-            .queue(cursor::MoveTo(20, 40))?
-            .queue(style::PrintStyledContent("Got: ".grey()))?
-            .queue(style::PrintStyledContent(
-                format!("{:?}", _event).dark_grey(),
-            ))?;
+        renderer.draw_text(20, 40, &format!("Got: {:?}", _event), ctx.colored(Color::Grey))?;
         */
-    } else {
-        show_logo(writer)?;
+    } else if controller.snake.current_direction == Direction::Stop
+        && controller.screen != GameScreen::Countdown
+    {
+        // Until the snake is sent off.
+        show_logo(renderer, ctx)?;
+    }
+
+    Ok(())
+}
+
+fn draw_apples(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    controller: &Controller,
+) -> crossterm::Result<()> {
+    for apple in &controller.apples {
+        let glyph = match (&render.apple, apple.apple_type) {
+            _ if render.colorblind => String::from(ctx.charset.marked_apple(apple.apple_type)),
+            // Poison always looks like poison, whatever the apples are drawn as.
+            (Some(glyph), AppleType::Fresh(_)) => glyph.clone(),
+            _ => ctx.charset.apple(apple.apple_type),
+        };
+
+        let mut style = ctx.colored(ctx.theme.apple);
+        if apple.flash_ticks > 0 {
+            style = style.attribute(style::Attribute::Reverse);
+        }
+        // Poison stays in the background, the rest stands out.
+        if render.colorblind {
+            style = style.attribute(match apple.apple_type {
+                AppleType::Poison => style::Attribute::Dim,
+                _ => style::Attribute::Bold,
+            });
+        }
+        renderer.draw_cell(&apple.position, &glyph, style)?;
+    }
+
+    Ok(())
+}
+
+// The first player's snake is red, the second one blue, unless the theme says otherwise. With
+// --colorblind each also has a pattern of its own. Heads point where the snake is going and
+// stand out in a color of their own.
+fn draw_snake(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    snake: &Snake,
+    player: usize,
+) -> crossterm::Result<()> {
+    let color = [ctx.theme.snake, ctx.theme.second_snake][player];
+
+    for (index, element) in snake.elements.iter().enumerate() {
+        let (glyph, color) = match ctx.charset.head(snake.current_direction) {
+            Some(head) if index == 0 => (head, ctx.theme.head),
+            _ if render.colorblind => (
+                ctx.charset.patterns[player][index % 2],
+                ctx.snake_shade(color, index, snake.elements.len()),
+            ),
+            _ => (
+                render.snake.as_str(),
+                ctx.snake_shade(color, index, snake.elements.len()),
+            ),
+        };
+        renderer.draw_cell(element, glyph, ctx.colored(color))?;
+    }
+
+    Ok(())
+}
+
+// The screen is cleared every frame, so cells the snake left are repainted along with the rest.
+// Overwrites whole cells, so the half of a cell that isn't covered yet is blanked out. The head
+// keeps its color while it moves in, and the cell the tail is leaving is shaded like the
+// segment next to it.
+fn draw_partial_cells(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    snake: &Snake,
+    partial_cells: &[(CanvasSpace, &str)],
+) -> crossterm::Result<()> {
+    let length = snake.elements.len();
+
+    for (cell, glyph) in partial_cells {
+        let color = if snake.elements.front() == Some(cell) {
+            ctx.theme.head
+        } else {
+            ctx.snake_shade(ctx.theme.snake, length.saturating_sub(1), length)
+        };
+        renderer.draw_cell(cell, glyph, ctx.colored(color))?;
+    }
+
+    Ok(())
+}
+
+fn draw_grid(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    bounds: &CanvasBounds,
+) -> crossterm::Result<()> {
+    for cell in bounds.iter_cells() {
+        renderer.draw_cell(
+            &cell,
+            ctx.charset.grid_shade(&cell),
+            ctx.colored(ctx.theme.grid),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn draw_obstacles(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    obstacles: &[CanvasSpace],
+) -> crossterm::Result<()> {
+    for obstacle in obstacles {
+        renderer.draw_cell(
+            obstacle,
+            ctx.charset.obstacle,
+            ctx.colored(ctx.theme.obstacle),
+        )?;
     }
 
-    writer.flush()?;
+    Ok(())
+}
+
+fn draw_portals(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    portals: &(CanvasSpace, CanvasSpace),
+) -> crossterm::Result<()> {
+    for portal in &[&portals.0, &portals.1] {
+        renderer.draw_cell(portal, ctx.charset.portal, ctx.colored(ctx.theme.portal))?;
+    }
 
     Ok(())
 }
 
-fn draw_apple(writer: &mut impl Write, controller: &Controller) -> crossterm::Result<()> {
-    if let Some(apple) = &controller.apple {
-        let rand_pos = TerminalSpace::from(apple.0.clone());
+fn draw_ghost(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    ghost: &[CanvasSpace],
+) -> crossterm::Result<()> {
+    for element in ghost {
+        renderer.draw_cell(element, &render.snake, ctx.colored(ctx.theme.ghost))?;
+    }
+
+    Ok(())
+}
 
-        writer
-            .queue(cursor::MoveTo(rand_pos.0 .0 as u16, rand_pos.0 .1 as u16))?
-            .queue(style::Print(*apple.1))?;
+fn draw_pickup(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    controller: &Controller,
+) -> crossterm::Result<()> {
+    if let Some((position, effect)) = &controller.pickup {
+        renderer.draw_cell(position, &ctx.charset.effect(*effect), ctx.plain())?;
     }
 
     Ok(())
 }
 
-fn draw_snake(writer: &mut impl Write, snake: &Snake) -> crossterm::Result<()> {
-    for element in snake.elements.clone() {
-        let position = TerminalSpace::from(element);
-        writer
-            .queue(cursor::MoveTo(position.0 .0 as u16, position.0 .1 as u16))?
-            .queue(style::PrintStyledContent("██".red()))?;
+fn draw_effect_indicator(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    active: &ActiveEffect,
+    ticks_per_sec: u16,
+) -> crossterm::Result<()> {
+    let indicator = format!(
+        "{} {:>2}s",
+        ctx.charset.effect(active.effect).trim_end(),
+        active.remaining_secs(ticks_per_sec)
+    );
+
+    renderer.draw_text(
+        ctx.right_border
+            .saturating_sub(indicator.chars().count() as u16),
+        ctx.upper_border.saturating_sub(1),
+        &indicator,
+        ctx.colored(ctx.theme.highlight),
+    )
+}
+
+fn draw_score(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    controller: &Controller,
+) -> crossterm::Result<()> {
+    let score = match &controller.second {
+        Some(second) => format!(
+            "{} {} : {} {}",
+            player_name(controller, 1),
+            score_label(controller.score),
+            score_label(second.score),
+            player_name(controller, 2)
+        ),
+        None => score_label(controller.score),
+    };
+
+    renderer.draw_text(
+        ctx.centered(&score),
+        ctx.upper_border.saturating_sub(1),
+        &score,
+        ctx.plain(),
+    )
+}
+
+// How the score line and the end screen call the players of a two player game.
+fn player_name(controller: &Controller, player: u8) -> &'static str {
+    let against_ai = controller
+        .second
+        .as_ref()
+        .is_some_and(|second| second.ai.is_some());
+
+    match (player, against_ai) {
+        (1, false) => "P1",
+        (_, false) => "P2",
+        (1, true) => "You",
+        (_, true) => "CPU",
+    }
+}
+
+// The row under the board: the time and the mode on the left, messages and the golden apple's
+// countdown in the middle and the combo on the right. Whatever matters least gives way on
+// narrow boards.
+fn draw_status_row(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    controller: &Controller,
+    toast: Option<&str>,
+) -> crossterm::Result<()> {
+    let field = |text: String, style, align, priority| StatusField {
+        text,
+        style,
+        align,
+        priority,
+        cut_off: false,
+    };
+
+    let (mode, mode_style) = mode_label(ctx, controller);
+    let mode_priority = match controller.game_mode {
+        GameMode::Hunger => 3,
+        _ => 5,
+    };
+    let mut fields = vec![
+        field(
+            time_label(controller.stats.elapsed),
+            ctx.plain(),
+            Align::Left,
+            0,
+        ),
+        field(mode, mode_style, Align::Left, mode_priority),
+    ];
+
+    if let Some(secs) = golden_remaining_secs(controller) {
+        let countdown = format!(
+            "{} {}s",
+            ctx.charset.apple(AppleType::Golden).trim_end(),
+            secs
+        );
+        fields.push(field(
+            countdown,
+            ctx.colored(ctx.theme.highlight),
+            Align::Center,
+            2,
+        ));
+    }
+
+    // Messages can be longer than the board is wide, like the path of a snapshot.
+    if let Some(text) = toast {
+        fields.push(StatusField {
+            cut_off: true,
+            ..field(
+                text.to_string(),
+                ctx.colored(ctx.theme.highlight),
+                Align::Center,
+                1,
+            )
+        });
+    }
+
+    if controller.combo > 1 {
+        fields.push(field(
+            combo_label(ctx, controller),
+            ctx.colored(ctx.theme.highlight),
+            Align::Right,
+            4,
+        ));
+    }
+
+    let width = ctx.right_border.saturating_sub(ctx.left_border) + 1;
+    for (x, field) in layout_status_row(fields, width) {
+        renderer.draw_text(
+            ctx.left_border + x,
+            ctx.lower_border.saturating_add(1),
+            &field.text,
+            field.style,
+        )?;
     }
 
     Ok(())
 }
 
-fn draw_borders(writer: &mut impl Write) -> crossterm::Result<()> {
-    let (terminal_width, terminal_height) = terminal::size()?;
+// The multiplier followed by a bar that empties as the combo window runs out.
+fn combo_label(ctx: &FrameContext, controller: &Controller) -> String {
+    format!(
+        "x{} {}",
+        controller.combo,
+        ctx.charset.bar(
+            combo_remaining(controller).as_secs_f64() / COMBO_WINDOW.as_secs_f64(),
+            BAR_WIDTH
+        )
+    )
+}
+
+// In hunger mode, a bar that empties until the snake loses its next segment.
+fn mode_label(ctx: &FrameContext, controller: &Controller) -> (String, ContentStyle) {
+    match controller.game_mode {
+        GameMode::Hunger => (
+            format!(
+                "Hunger {}",
+                ctx.charset.bar(
+                    ticks_until_hungry(controller) as f64 / HUNGER_TICKS as f64,
+                    BAR_WIDTH
+                )
+            ),
+            ctx.colored(ctx.theme.alert),
+        ),
+        mode => (format!("{:?}", mode), ctx.colored(ctx.theme.muted)),
+    }
+}
+
+// Next to the sprint indicator, out of --max-length if there is one.
+fn draw_length(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    controller: &Controller,
+) -> crossterm::Result<()> {
+    let length = controller.snake.elements.len();
+    let length = match controller.max_length {
+        Some(max_length) => format!("Length {}/{}", length, max_length),
+        None => format!("Length {}", length),
+    };
+    let left = ctx.left_border + ctx.charset.sprint.chars().count() as u16 + 1;
+    let row = ctx.upper_border.saturating_sub(1);
+
+    renderer.draw_text(left, row, &length, ctx.plain())?;
+
+    if at_max_length(controller) {
+        renderer.draw_text(
+            left + length.len() as u16,
+            row,
+            " MAX",
+            ctx.colored(ctx.theme.alert),
+        )?;
+    }
+
+    Ok(())
+}
 
-    let left_border = (terminal_width / 2).saturating_sub(CANVAS_WIDTH / 2);
-    let right_border = terminal_width / 2 + CANVAS_WIDTH / 2;
+fn draw_sprint_indicator(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+) -> crossterm::Result<()> {
+    renderer.draw_text(
+        ctx.left_border,
+        ctx.upper_border.saturating_sub(1),
+        ctx.charset.sprint,
+        ctx.colored(ctx.theme.highlight),
+    )
+}
 
-    let upper_border = (terminal_height / 2).saturating_sub(CANVAS_HEIGHT / 4);
-    let lower_border = terminal_height / 2 + CANVAS_HEIGHT / 4;
+fn draw_borders(renderer: &mut impl Renderer, ctx: &FrameContext) -> crossterm::Result<()> {
+    let FrameContext {
+        left_border,
+        right_border,
+        upper_border,
+        lower_border,
+        ..
+    } = *ctx;
+    let border = ctx.charset.border;
+    let vertical = border[0].to_string();
 
     // Vertical lines
     for i in upper_border..=lower_border {
-        writer
-            .queue(cursor::MoveTo(left_border, i))?
-            .queue(style::Print(BORDER_STYLE[0]))?
-            .queue(cursor::MoveTo(right_border, i))?
-            .queue(style::Print(BORDER_STYLE[0]))?;
+        renderer.draw_text(left_border, i, &vertical, ctx.colored(ctx.theme.border))?;
+        renderer.draw_text(right_border, i, &vertical, ctx.colored(ctx.theme.border))?;
     }
 
     // Horizontal lines and corners
-    writer
-        .queue(cursor::MoveTo(left_border, upper_border))?
-        .queue(style::Print(BORDER_STYLE[2]))?
-        .queue(style::Print(
-            BORDER_STYLE[1]
-                .to_string()
-                .repeat(CANVAS_WIDTH as usize - 1),
-        ))?
-        .queue(style::Print(BORDER_STYLE[3]))?;
-
-    writer
-        .queue(cursor::MoveTo(left_border, lower_border))?
-        .queue(style::Print(BORDER_STYLE[4]))?
-        .queue(style::Print(
-            BORDER_STYLE[1]
-                .to_string()
-                .repeat(CANVAS_WIDTH as usize - 1),
-        ))?
-        .queue(style::Print(BORDER_STYLE[5]))?;
+    let horizontal = border[1]
+        .to_string()
+        .repeat((right_border - left_border).saturating_sub(1) as usize);
+
+    renderer.draw_text(
+        left_border,
+        upper_border,
+        &format!("{}{}{}", border[2], horizontal, border[3]),
+        ctx.colored(ctx.theme.border),
+    )?;
+    renderer.draw_text(
+        left_border,
+        lower_border,
+        &format!("{}{}{}", border[4], horizontal, border[5]),
+        ctx.colored(ctx.theme.border),
+    )?;
 
     Ok(())
 }
 
-fn handle_events(controller: &mut Controller) {
-    if let Ok(ref mut queue) = controller.event_queue.as_ref().lock() {
-        while let Some(e) = queue.pop() {
-            match e {
-                event::Event::Key(event) => {
-                    if event.code == event::KeyCode::Char('q') {
-                        controller.should_close = true;
-                    }
-                    controller.last_event = Some(event::Event::Key(event));
-                }
-                event::Event::Mouse(event) => {
-                    controller.last_event = Some(event::Event::Mouse(event))
-                }
-                _ => ()
-            }
-        }
-    }
+fn show_logo(renderer: &mut impl Renderer, ctx: &FrameContext) -> crossterm::Result<()> {
+    draw_logo(renderer, ctx, logo_top(ctx))
+}
+
+// Where the logo starts when it is centered.
+fn logo_top(ctx: &FrameContext) -> u16 {
+    let line_count = ctx.charset.logo.lines().count() as u16;
+    (ctx.terminal_height / 2).saturating_sub(line_count / 2)
 }
 
-fn continue_game_logic(controller: &mut Controller) {
-    let snake = &mut controller.snake;
+fn draw_logo(renderer: &mut impl Renderer, ctx: &FrameContext, top: u16) -> crossterm::Result<()> {
+    // `lines` also strips the `\r` of CRLF line endings.
+    let lines: Vec<&str> = ctx.charset.logo.lines().collect();
 
-    match controller.last_event {
-        Some(event::Event::Key(keyevent)) => match keyevent.code {
-            event::KeyCode::Up if snake.current_direction != Direction::Down => snake.current_direction = Direction::Up,
-            event::KeyCode::Down if snake.current_direction != Direction::Up => snake.current_direction = Direction::Down,
-            event::KeyCode::Left if snake.current_direction != Direction::Right => snake.current_direction = Direction::Left,
-            event::KeyCode::Right if snake.current_direction != Direction::Left => snake.current_direction = Direction::Right,
-            _ => (),
-        },
-        _ => (),
+    let max_line_len = lines
+        .iter()
+        .map(|line| glyphs::display_width(line))
+        .max()
+        .unwrap_or(0) as u16;
+
+    let left = (ctx.terminal_width / 2).saturating_sub(max_line_len / 2);
+
+    for (index, line) in lines.iter().enumerate() {
+        renderer.draw_text(left, top + index as u16, line, ctx.colored(ctx.theme.logo))?;
     }
+    Ok(())
+}
 
-    if snake.current_direction != Direction::Stop {
-        let first_element = snake
-            .elements
-            .get(0)
-            .expect("First element should exist.")
-            .clone();
+fn clear_canvas(renderer: &mut impl Renderer, ctx: &FrameContext) -> crossterm::Result<()> {
+    let blank = " ".repeat((ctx.right_border - ctx.left_border).saturating_sub(1) as usize);
 
-        snake.elements.rotate_right(1);
+    for row in ctx.upper_border + 1..ctx.lower_border {
+        renderer.draw_text(ctx.left_border + 1, row, &blank, ctx.plain())?;
+    }
 
-        let new_first_element = snake
-            .elements
-            .get_mut(0)
-            .expect("First element should exist.");
+    Ok(())
+}
 
-        *new_first_element = first_element;
+// "GAME OVER" fading in from dark red while its letters spread apart, one step per tick.
+fn show_death_animation(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    tick: u32,
+) -> crossterm::Result<()> {
+    let progress = f64::from(tick + 1) / f64::from(DEATH_ANIMATION_TICKS);
+    let spacing = " ".repeat((progress * 2.0) as usize);
+    let message = "GAME OVER"
+        .chars()
+        .map(String::from)
+        .collect::<Vec<_>>()
+        .join(&spacing);
+
+    let brightness = (60.0 + progress * 195.0) as u8;
+
+    clear_canvas(renderer, ctx)?;
+
+    renderer.draw_text(
+        ctx.centered(&message),
+        ctx.terminal_height / 2,
+        &message,
+        ctx.colored(Color::Rgb {
+            r: brightness,
+            g: brightness / 5,
+            b: brightness / 5,
+        }),
+    )
+}
 
-        let (ref mut x, ref mut y) = new_first_element.0;
+fn show_endscreen(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    controller: &Controller,
+    unlocked: &[&Achievement],
+    leaderboard: Option<&Leaderboard>,
+) -> crossterm::Result<()> {
+    show_logo(renderer, ctx)?;
+
+    let middle = ctx.terminal_height / 2;
+
+    if let Some(death) = controller.stats.death {
+        let cause = death.description();
+
+        renderer.draw_text(
+            ctx.centered(cause),
+            middle.saturating_add(4),
+            cause,
+            ctx.colored(ctx.theme.alert),
+        )?;
+    }
 
-        match snake.current_direction {
-            Direction::Left if *x > 0 => *x -= 1,
-            Direction::Right if *x < (CANVAS_WIDTH / 2 - 2) as u32 => *x += 1,
-            Direction::Up if *y > 0 => *y -= 1,
-            Direction::Down if *y < (CANVAS_HEIGHT / 2 - 3) as u32 => *y += 1,
-            _ => controller.losed = true,
-        }
+    if controller.second.is_some() {
+        let banner = match winner(controller) {
+            Some(player) => match player_name(controller, player) {
+                "You" => String::from("YOU WIN"),
+                name => format!("{} WINS", name),
+            },
+            None => String::from("DRAW"),
+        };
+
+        renderer.draw_text(
+            ctx.centered(&banner),
+            middle.saturating_add(3),
+            &banner,
+            ctx.colored(ctx.theme.highlight),
+        )?;
     }
 
-    // Check if snake collides with apple
-    if let Some((ref mut apple_pos, _)) = controller.apple {
-        if apple_pos == snake.elements.get(0).expect("First element should exist.") {
-            controller.apple = None;
-            controller.score += 1;
-            snake.elements.push(snake.elements.last().expect("Snake always has at least one element.").clone());
-        }
+    if controller.won {
+        let banner = format!("YOU WIN - length {}", controller.snake.elements.len());
+
+        renderer.draw_text(
+            ctx.centered(&banner),
+            middle.saturating_add(4),
+            &banner,
+            ctx.colored(ctx.theme.highlight),
+        )?;
     }
 
-    // Place new apple
-    if let None = controller.apple {
-        let rand_pos = (
-            (rand::random::<u16>() % (CANVAS_WIDTH / 2 - 1)) as u32,
-            (rand::random::<u16>() % (CANVAS_HEIGHT / 2 - 2)) as u32,
-        );
-        let rand_pos = CanvasSpace(rand_pos);
+    // Padded to the widest possible score so the message doesn't shift as the score grows.
+    let score_message = match &controller.second {
+        Some(second) => format!(
+            "Scores: {} : {}",
+            score_label(controller.score),
+            score_label(second.score)
+        ),
+        None => format!("Your Score: {:<10}", score_label(controller.score)),
+    };
 
-        let apple_type_num = rand::random::<usize>() % APPLE.len();
+    renderer.draw_text(
+        ctx.centered(&score_message),
+        middle.saturating_add(5),
+        &score_message,
+        ctx.plain(),
+    )?;
+
+    let time_message = format!(
+        "Time: {}  Best combo: x{}  Seed: {}",
+        time_label(controller.stats.elapsed),
+        controller.stats.best_combo.max(1),
+        controller.rng.seed()
+    );
+
+    renderer.draw_text(
+        ctx.centered(&time_message),
+        middle.saturating_add(6),
+        &time_message,
+        ctx.plain(),
+    )?;
+
+    let hint = if controller.snapshot_requested {
+        "Snapshot will be saved - press r to play again"
+    } else {
+        "Press s to save a snapshot or r to play again"
+    };
+
+    renderer.draw_text(
+        ctx.centered(hint),
+        middle.saturating_add(7),
+        hint,
+        ctx.colored(ctx.theme.muted),
+    )?;
+
+    for (index, achievement) in unlocked.iter().enumerate() {
+        let line = format!(
+            "Unlocked: {} - {}",
+            achievement.name, achievement.description
+        );
 
-        controller.apple = Some((rand_pos, AppleType(APPLE[apple_type_num])));
+        renderer.draw_text(
+            ctx.centered(&line),
+            middle.saturating_add(9 + index as u16),
+            &line,
+            ctx.colored(ctx.theme.highlight),
+        )?;
     }
 
-    // Check if first element collides with an other element
-    for (index, current) in snake.elements.iter().enumerate() {
-        if index == 0 || index == 1 {
-            continue;
-        }
+    if let Some(leaderboard) = leaderboard {
+        let top = middle.saturating_add(10 + unlocked.len() as u16);
+        draw_high_scores(renderer, ctx, leaderboard, top)?;
+    }
 
-        if snake.elements.get(0).expect("Snake has at least one element.") == current {
-            controller.losed = true;
-        }
+    if let Some(prompt) = &controller.name_prompt {
+        draw_name_prompt(renderer, ctx, prompt)?;
     }
+
+    Ok(())
+}
+
+// "3", "2", "1" and then "Go!" as the snake sets off.
+fn draw_countdown(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    label: &str,
+) -> crossterm::Result<()> {
+    let text = format!(" {} ", label);
+    renderer.set_layer(Layer::Hud);
+
+    renderer.draw_text(
+        ctx.centered(&text),
+        ctx.terminal_height / 2,
+        &text,
+        ctx.colored(ctx.theme.highlight)
+            .attribute(style::Attribute::Bold)
+            .attribute(style::Attribute::Reverse),
+    )
 }
 
-fn show_logo(writer: &mut impl Write) -> crossterm::Result<()> {
-    let logo = include_str!("logo.txt");
-    let line_len = logo.find('\n').expect("Logo has \\n");
-    let (terminal_width, terminal_height) = terminal::size()?;
+fn draw_paused(renderer: &mut impl Renderer, ctx: &FrameContext) -> crossterm::Result<()> {
+    let text = " Paused ";
+    renderer.set_layer(Layer::Hud);
 
-    for (index, line) in logo.split("\n").enumerate() {
-        writer
-            .queue(cursor::MoveTo(
-                (terminal_width / 2).saturating_sub((line_len / 6) as u16),
-                index as u16 + (terminal_height / 2).saturating_sub(2),
-            ))?
-            .queue(style::PrintStyledContent(line.dark_red()))?;
+    renderer.draw_text(
+        ctx.centered(text),
+        ctx.terminal_height / 2,
+        text,
+        ContentStyle::new().attribute(style::Attribute::Reverse),
+    )
+}
+
+// A bordered box in the middle of the terminal, sized to its longest line. The inside is filled
+// with spaces so that nothing underneath shows through.
+fn draw_help(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    lines: &[String],
+) -> crossterm::Result<()> {
+    let title = "Controls - press any key to close";
+    renderer.set_layer(Layer::Hud);
+    let inner_width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .chain(std::iter::once(title.len()))
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    let width = inner_width as u16 + 2;
+    let height = lines.len() as u16 + 4;
+    let left = (ctx.terminal_width / 2).saturating_sub(width / 2);
+    let top = (ctx.terminal_height / 2).saturating_sub(height / 2);
+
+    let border = ctx.charset.border;
+    let horizontal = border[1].to_string().repeat(inner_width);
+    let rows = std::iter::once(title.to_string())
+        .chain(std::iter::once(String::new()))
+        .chain(lines.iter().cloned());
+
+    renderer.draw_text(
+        left,
+        top,
+        &format!("{}{}{}", border[2], horizontal, border[3]),
+        ctx.plain(),
+    )?;
+
+    for (index, row) in rows.enumerate() {
+        renderer.draw_text(
+            left,
+            top + 1 + index as u16,
+            &format!(
+                "{} {:<width$} {}",
+                border[0],
+                row,
+                border[0],
+                width = inner_width - 2
+            ),
+            ctx.plain(),
+        )?;
+    }
+
+    renderer.draw_text(
+        left,
+        top + height - 1,
+        &format!("{}{}{}", border[4], horizontal, border[5]),
+        ctx.plain(),
+    )
+}
+
+// The top ten as a block starting at row `top`. Rows that would run into the name prompt are
+// left out on short terminals.
+fn draw_high_scores(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    leaderboard: &Leaderboard,
+    top: u16,
+) -> crossterm::Result<()> {
+    let table = leaderboard::leaderboard_table(leaderboard);
+    let width = table
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let left = (ctx.terminal_width / 2).saturating_sub(width / 2);
+
+    for (row, line) in (top..ctx.terminal_height.saturating_sub(2)).zip(table.lines()) {
+        renderer.draw_text(left, row, line, ctx.colored(ctx.theme.muted))?;
     }
+
     Ok(())
 }
 
-fn show_endscreen(writer: &mut impl Write, controller: &Controller) -> crossterm::Result<()> {
-    show_logo(writer)?;
+// On the bottom line, below the name prompt.
+fn draw_online_status(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    text: &str,
+) -> crossterm::Result<()> {
+    renderer.draw_text(
+        ctx.centered(text),
+        ctx.terminal_height.saturating_sub(1),
+        text,
+        ctx.colored(ctx.theme.muted),
+    )
+}
 
-    let score_message = format!("Your Score: {}", controller.score);
+fn draw_name_prompt(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    prompt: &NamePrompt,
+) -> crossterm::Result<()> {
+    let line = match prompt.state {
+        PromptState::Editing => format!("New high score! Your name: {}_", prompt.text),
+        PromptState::Submitted => format!("Saved as {}", prompt.name()),
+        PromptState::Cancelled => String::from("Not saved"),
+    };
+    let row = ctx.terminal_height.saturating_sub(2);
+
+    // Blanked first, so that nothing of a longer line before is left over.
+    renderer.draw_text(
+        0,
+        row,
+        &" ".repeat(ctx.terminal_width as usize),
+        ctx.plain(),
+    )?;
+    renderer.draw_text(
+        ctx.centered(&line),
+        row,
+        &line,
+        ctx.colored(ctx.theme.highlight),
+    )
+}
 
-    let (terminal_width, terminal_height) = terminal::size()?;
+fn draw_spectator(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    controller: &Controller,
+    status: &str,
+) -> crossterm::Result<()> {
+    renderer.clear()?;
+    renderer.set_layer(Layer::Board);
+
+    draw_borders(renderer, ctx)?;
+    draw_obstacles(renderer, ctx, &controller.obstacles)?;
+
+    renderer.set_layer(Layer::Entities);
+    draw_snake(renderer, ctx, render, &controller.snake, 0)?;
+    if let Some(second) = &controller.second {
+        draw_snake(renderer, ctx, render, &second.snake, 1)?;
+    }
+    draw_apples(renderer, ctx, render, controller)?;
+
+    renderer.set_layer(Layer::Hud);
+    renderer.draw_text(
+        ctx.centered(status),
+        ctx.upper_border.saturating_sub(1),
+        status,
+        ctx.colored(ctx.theme.muted),
+    )
+}
 
-    writer
-        .queue(cursor::MoveTo((terminal_width / 2).saturating_sub(score_message.len() as u16/2), (terminal_height / 2).saturating_add(5),))?
-        .queue(style::Print(score_message))?;
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Play,
+    Broadcast(String),
+    Spectate(String),
+    Host(String),
+    Join(String),
+    Watch(String),
+    Server(String),
+    Telnet(String),
+    Replay(PathBuf),
+    Edit(PathBuf),
+}
 
-    writer.flush()?;
-    Ok(())
+#[derive(Debug, Clone)]
+struct Options {
+    mode: Mode,
+    show_timing: bool,
+    fps_counter: bool,
+    sound: bool,
+    snapshot: Option<PathBuf>,
+    force: bool,
+    cast: Option<PathBuf>,
+    record: Option<PathBuf>,
+    ghost: Option<PathBuf>,
+    no_ghost: bool,
+    obstacles_every: Option<u32>,
+    ramp: Option<Ramp>,
+    smooth: bool,
+    mouse: bool,
+    // Like --mouse, and the snake also heads for the cursor without clicking.
+    mouse_follow: bool,
+    // The board is as large as the terminal allows, instead of --width by --height.
+    fit: bool,
+    // The name of `render.theme` among all `themes`, for the options screen.
+    theme: String,
+    themes: Vec<(String, Theme)>,
+    start: StartPosition,
+    config: GameConfig,
+    game_mode: GameMode,
+    portals: bool,
+    wrap: bool,
+    two_players: bool,
+    // Who steers the second snake, if not a second player on the same keyboard.
+    ai: Option<Skill>,
+    difficulty: Difficulty,
+    max_length: Option<usize>,
+    apple_count: usize,
+    save: Option<PathBuf>,
+    load: Option<PathBuf>,
+    bench: bool,
+    seed: Option<u64>,
+    keys: KeyBindings,
+    render: RenderConfig,
+    no_menu: bool,
+    demo: bool,
+    // What steers the player's snake, with --bot or --demo.
+    bot: Option<Bot>,
+    // The room to --join on a server.
+    room: String,
+    online_leaderboard: Option<Endpoint>,
 }
 
-fn main() -> crossterm::Result<()> {
-    let mut stdout = stdout();
+// Everything kept between runs. A file that can't be read only disables its own part.
+#[derive(Default)]
+struct Profile {
+    leaderboard: Option<(PathBuf, Leaderboard)>,
+    // Reported once the terminal is back to normal.
+    leaderboard_error: Option<String>,
+    achievements: Option<(PathBuf, Progress)>,
+    // The best run so far, if there is one, and where a better one is saved.
+    ghost: Option<(PathBuf, Option<Ghost>)>,
+    recording: Ghost,
+    // This run's input, written out with --record.
+    replay: Replay,
+}
 
-    terminal::enable_raw_mode()?;
-    stdout
-        .execute(terminal::EnterAlternateScreen)?
-        .execute(cursor::Hide)?;
+impl Profile {
+    fn load(options: &Options) -> Self {
+        let ghost_path = if options.no_ghost {
+            None
+        } else {
+            options.ghost.clone().or_else(ghost::default_path)
+        };
+
+        Profile {
+            leaderboard: load_saved(
+                "leaderboard",
+                leaderboard::default_path(),
+                Leaderboard::load,
+            ),
+            leaderboard_error: None,
+            achievements: load_saved("achievements", achievements::default_path(), Progress::load),
+            ghost: load_saved("ghost", ghost_path, Ghost::load),
+            recording: Ghost::default(),
+            replay: Replay::default(),
+        }
+    }
+}
 
-    let mut game_controller = Controller {
-        should_close: false,
-        event_queue: Arc::new(Mutex::new(Vec::new())),
-        last_event: None,
-        snake: Snake {
-            elements: vec![CanvasSpace((
-                (CANVAS_WIDTH / 4) as u32,
-                (CANVAS_HEIGHT / 4 - 1) as u32,
-            ))],
-            current_direction: Direction::Stop,
-        },
-        apple: None,
-        score: 0,
-        losed: false,
+fn load_saved<T>(
+    what: &str,
+    path: Option<PathBuf>,
+    load: impl Fn(&Path) -> std::io::Result<T>,
+) -> Option<(PathBuf, T)> {
+    let path = path?;
+
+    match load(&path) {
+        Ok(saved) => Some((path, saved)),
+        Err(e) => {
+            eprintln!("Ignoring the {} at {}: {}", what, path.display(), e);
+            None
+        }
+    }
+}
+
+// `file` supplies the defaults, which the flags in `args` override.
+fn parse_args(
+    args: impl Iterator<Item = String>,
+    file: &Config,
+    user_themes: &[(String, Theme)],
+) -> Result<Options, String> {
+    let mut mode = Mode::Play;
+    let mut show_timing = false;
+    let mut fps_counter = false;
+    let mut sound = file.sound.unwrap_or(false);
+    let mut snapshot = None;
+    let mut force = false;
+    let mut cast = None;
+    let mut record = None;
+    let mut ghost = None;
+    let mut no_ghost = false;
+    let mut no_menu = false;
+    let mut demo = false;
+    let mut bot = None;
+    let mut room = None;
+    let mut obstacles_every = None;
+    let mut ramp = None;
+    let mut max_speed = None;
+    let mut grid = false;
+    let mut colorblind = file.colorblind.unwrap_or(false);
+    let mut smooth = false;
+    let mut mouse = false;
+    let mut mouse_follow = false;
+    let mut fit = false;
+    let mut sized = false;
+    let mut start = None;
+    let mut level = None;
+    let mut config = GameConfig::default();
+    let mut game_mode = GameMode::Classic;
+    let mut portals = false;
+    let mut wrap = false;
+    let mut two_players = false;
+    let mut ai = None;
+    let mut max_length = None;
+    let mut apple_count = 1;
+    let mut save = None;
+    let mut load = None;
+    let mut bench = false;
+    let mut seed = None;
+    let mut canvas_size = (
+        file.width.unwrap_or(CANVAS_WIDTH),
+        file.height.unwrap_or(CANVAS_HEIGHT),
+    );
+    // NO_COLOR only counts when it is set to something, and piped output never gets colors.
+    let mut no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+        || !std::io::stdout().is_terminal()
+        || file.color == Some(false);
+    let locale = |name| std::env::var(name).ok();
+    let mut ascii = !glyphs::unicode_locale(
+        locale("LC_ALL").as_deref(),
+        locale("LC_CTYPE").as_deref(),
+        locale("LANG").as_deref(),
+    );
+    let mut snake_char = file.snake_char.clone();
+    let mut apple_char = file.apple_char.clone();
+    let themes = theme::all(user_themes);
+    let mut theme_name = file
+        .theme
+        .clone()
+        .unwrap_or_else(|| String::from("classic"));
+
+    if let Some(speed) = file.speed {
+        if speed > MAX_SPEED {
+            return Err(format!(
+                "The configured speed must be from 1 to {}.",
+                MAX_SPEED
+            ));
+        }
+        config.ticks_per_sec = speed;
+    }
+
+    // A preset only changes the defaults, so the flags override it wherever they come.
+    let args: Vec<String> = args.collect();
+    let difficulty = match args.iter().position(|arg| arg == "--difficulty") {
+        Some(index) => args
+            .get(index + 1)
+            .ok_or_else(|| String::from("--difficulty expects a value."))?
+            .parse::<Difficulty>()
+            .map(Some)?,
+        None => None,
     };
+    if let Some(preset) = difficulty.map(Difficulty::preset) {
+        config.ticks_per_sec = preset.ticks_per_sec;
+        canvas_size = preset.canvas_size;
+        wrap = preset.wrap;
+        apple_count = preset.apple_count;
+        ramp = preset.ramp;
+    }
+    let mut args = args.into_iter();
 
-    let event_queue = Arc::clone(&game_controller.event_queue);
-    let _ = thread::spawn(move || -> crossterm::Result<()> {
-        loop {
-            if event::poll(time::Duration::from_millis(100))? {
-                let event = event::read()?;
-                while let Ok(ref mut queue) = event_queue.as_ref().lock() {
-                    queue.push(event);
-                    break;
+    let mut keys = KeyBindings::default();
+    for (code, action) in &file.keys {
+        keys.bind(*code, *action);
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} expects a value.", arg));
+
+        match arg.as_str() {
+            "--broadcast" => mode = Mode::Broadcast(value()?),
+            "--spectate" => mode = Mode::Spectate(value()?),
+            "--host" => {
+                mode = Mode::Host(value()?);
+                two_players = true;
+            }
+            "--join" => mode = Mode::Join(value()?),
+            "--watch" => mode = Mode::Watch(value()?),
+            "--room" => room = Some(value()?),
+            "--server" => {
+                mode = Mode::Server(value()?);
+                two_players = true;
+            }
+            "--telnet" => mode = Mode::Telnet(value()?),
+            "--replay" => mode = Mode::Replay(PathBuf::from(value()?)),
+            "--edit" => mode = Mode::Edit(PathBuf::from(value()?)),
+            "--show-timing" => show_timing = true,
+            "--fps-counter" => fps_counter = true,
+            "--sound" => sound = true,
+            "--no-sound" => sound = false,
+            "--snapshot" => snapshot = Some(PathBuf::from(value()?)),
+            "--force" => force = true,
+            "--cast" => cast = Some(PathBuf::from(value()?)),
+            "--record" => record = Some(PathBuf::from(value()?)),
+            "--ghost" => ghost = Some(PathBuf::from(value()?)),
+            "--no-ghost" => no_ghost = true,
+            "--no-menu" => no_menu = true,
+            "--demo" => demo = true,
+            "--bot" => bot = Some(Registry::default().create(&value()?)?),
+            "--obstacles-every" => {
+                let points = value()?;
+                match points.parse::<u32>() {
+                    Ok(points) if points > 0 => obstacles_every = Some(points),
+                    _ => return Err(format!("{} expects a positive number.", arg)),
+                }
+            }
+            "--snake-char" => snake_char = Some(value()?),
+            "--apple-char" => apple_char = Some(value()?),
+            "--grid" => grid = true,
+            "--colorblind" => colorblind = true,
+            "--theme" => theme_name = value()?,
+            "--no-color" => no_color = true,
+            "--ascii" => ascii = true,
+            "--smooth" => smooth = true,
+            "--mouse" => mouse = true,
+            "--mouse-follow" => {
+                mouse = true;
+                mouse_follow = true;
+            }
+            "--fit" => fit = true,
+            "--start" => start = Some(value()?.parse()?),
+            "--level" => level = Some(PathBuf::from(value()?)),
+            "--mode" => game_mode = value()?.parse()?,
+            "--portals" => portals = true,
+            "--two-players" => two_players = true,
+            "--ai" => {
+                ai = Some(value()?.parse()?);
+                two_players = true;
+            }
+            "--wrap" => wrap = true,
+            "--no-wrap" => wrap = false,
+            // Already applied before the other flags.
+            "--difficulty" => {
+                value()?;
+            }
+            "--layout" => config.layout = Some(value()?.parse()?),
+            "--max-length" => {
+                let length = value()?;
+                match length.parse::<usize>() {
+                    Ok(length) if length > 0 => max_length = Some(length),
+                    _ => return Err(format!("{} expects a positive number.", arg)),
+                }
+            }
+            "--apples" => {
+                let count = value()?;
+                match count.parse::<usize>() {
+                    Ok(count) if (1..=MAX_APPLES).contains(&count) => apple_count = count,
+                    _ => {
+                        return Err(format!(
+                            "{} expects a number from 1 to {}.",
+                            arg, MAX_APPLES
+                        ))
+                    }
+                }
+            }
+            "--save" => save = Some(PathBuf::from(value()?)),
+            "--load" => load = Some(PathBuf::from(value()?)),
+            "--bench" => bench = true,
+            "--width" | "--height" => {
+                let size = value()?;
+                let size = match size.parse::<u16>() {
+                    Ok(size) if size > 0 => size,
+                    _ => return Err(format!("{} expects a positive number.", arg)),
+                };
+
+                if arg == "--width" {
+                    canvas_size.0 = size;
+                } else {
+                    canvas_size.1 = size;
+                }
+                sized = true;
+            }
+            "--speed" => {
+                let speed = value()?;
+                match speed.parse::<u16>() {
+                    Ok(speed) if (1..=MAX_SPEED).contains(&speed) => config.ticks_per_sec = speed,
+                    _ => return Err(format!("{} expects a number from 1 to {}.", arg, MAX_SPEED)),
+                }
+            }
+            "--ramp" => ramp = Some(Ramp::new(value()?.parse()?)),
+            "--max-speed" => {
+                let speed = value()?;
+                match speed.parse::<u16>() {
+                    Ok(speed) if (1..=MAX_SPEED).contains(&speed) => max_speed = Some(speed),
+                    _ => return Err(format!("{} expects a number from 1 to {}.", arg, MAX_SPEED)),
+                }
+            }
+            "--seed" => {
+                let value = value()?;
+                match value.parse::<u64>() {
+                    Ok(value) => seed = Some(value),
+                    _ => return Err(format!("{} expects a whole number.", arg)),
+                }
+            }
+            "--start-length" => {
+                let length = value()?;
+                match length.parse::<usize>() {
+                    Ok(length) if (1..=MAX_START_LENGTH).contains(&length) => {
+                        config.start_length = length
+                    }
+                    _ => {
+                        return Err(format!(
+                            "{} expects a number from 1 to {}.",
+                            arg, MAX_START_LENGTH
+                        ))
+                    }
                 }
             }
+            _ => return Err(format!("Unknown argument `{}`.", arg)),
         }
-    });
+    }
 
-    // Create a sync channel with bound 0 so that it is absolutely synchronous.
-    let (tick_tx, tick_rx) = mpsc::sync_channel(0);
+    config.bounds = CanvasBounds::from_canvas_size(canvas_size.0, canvas_size.1);
 
-    let _ = thread::spawn(move || loop {
-        thread::sleep(time::Duration::from_millis(1000 / TICKS_PER_SEC as u64));
-        tick_tx.try_send(()).ok();
-    });
+    // A level brings its own board size, and its start unless --start overrides it.
+    let level_given = level.is_some();
+    if let Some(path) = level {
+        let level = Level::load(&path)
+            .map_err(|e| format!("Can't load the level {}: {}", path.display(), e))?;
 
-    for _ in tick_rx {
-        handle_events(&mut game_controller);
+        config.bounds = level.bounds;
+        config.obstacles = level.walls;
+        config.no_apples = level.no_apples;
+        start = start.or(level.spawn.map(StartPosition::At));
+    }
+    let start = start.unwrap_or(StartPosition::Center);
+
+    // The board of a fitted game is only known once there is a terminal to fit, see
+    // `fit_board`.
+    if fit && (sized || level_given) {
+        return Err(String::from(
+            "--fit can't be combined with --width, --height or --level.",
+        ));
+    }
+    if fit && matches!(mode, Mode::Server(_)) {
+        return Err(String::from(
+            "--fit needs a terminal, which --server has none of.",
+        ));
+    }
 
-        if !game_controller.losed {
-            continue_game_logic(&mut game_controller);
-            draw(&mut stdout, &game_controller)?;
-        } else {
-            show_endscreen(&mut stdout, &game_controller)?;
+    if max_length.is_some_and(|max_length| config.start_length > max_length) {
+        return Err(String::from("--start-length can't exceed --max-length."));
+    }
+
+    // Saves only know about one snake.
+    if two_players && (save.is_some() || load.is_some()) {
+        return Err(String::from(
+            "--two-players, --ai and --host can't be combined with --save or --load.",
+        ));
+    }
+
+    if ai.is_some() && matches!(mode, Mode::Host(_) | Mode::Server(_)) {
+        return Err(String::from(
+            "--ai can't be combined with --host or --server.",
+        ));
+    }
+
+    // Every connection plays a game of its own, and keeps nothing of it.
+    if matches!(mode, Mode::Telnet(_))
+        && (save.is_some() || load.is_some() || cast.is_some() || record.is_some())
+    {
+        return Err(String::from(
+            "--telnet can't be combined with --save, --load, --cast or --record.",
+        ));
+    }
+
+    if room.is_some() && !matches!(mode, Mode::Join(_) | Mode::Watch(_)) {
+        return Err(String::from("--room only works with --join or --watch."));
+    }
+
+    // A demo plays itself, greedily unless --bot picks another way.
+    if demo && bot.is_none() {
+        bot = Some(Registry::default().create("greedy")?);
+    }
+
+    if let Some(cap) = max_speed {
+        match &mut ramp {
+            Some(ramp) => ramp.cap = cap,
+            None => return Err(String::from("--max-speed only works with --ramp.")),
         }
+    }
 
-        if game_controller.should_close {
-            break;
+    let colors = theme::find(&themes, &theme_name)?;
+    let charset = if ascii {
+        glyphs::ASCII
+    } else {
+        glyphs::UNICODE
+    };
+
+    Ok(Options {
+        mode,
+        show_timing,
+        fps_counter,
+        sound,
+        snapshot,
+        force,
+        cast,
+        record,
+        ghost,
+        no_ghost,
+        obstacles_every,
+        ramp,
+        smooth,
+        mouse,
+        mouse_follow,
+        fit,
+        theme: theme_name,
+        themes,
+        start,
+        config,
+        game_mode,
+        portals,
+        wrap,
+        two_players,
+        ai,
+        difficulty: difficulty.unwrap_or(Difficulty::Normal),
+        max_length,
+        apple_count,
+        save,
+        load,
+        bench,
+        seed,
+        keys,
+        render: RenderConfig {
+            grid,
+            colorblind,
+            no_color,
+            theme: colors,
+            truecolor: theme::truecolor(std::env::var("COLORTERM").ok().as_deref()),
+            charset,
+            ..RenderConfig::new(
+                snake_char.or_else(|| Some(String::from(charset.snake))),
+                apple_char,
+            )?
+        },
+        no_menu,
+        demo,
+        bot,
+        room: room.unwrap_or_else(|| String::from(server::DEFAULT_ROOM)),
+        online_leaderboard: file.online_leaderboard.clone(),
+    })
+}
+
+fn draw_timing(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    stats: &TimingStats,
+) -> crossterm::Result<()> {
+    let timing = format!(
+        "{:5.1} TPS  {:5.2} ms/frame",
+        stats.ticks_per_sec,
+        stats.frame_time.as_secs_f64() * 1000.0
+    );
+
+    renderer.set_layer(Layer::Hud);
+    renderer.draw_text(
+        0,
+        ctx.terminal_height.saturating_sub(1),
+        &timing,
+        ctx.colored(ctx.theme.muted),
+    )
+}
+
+fn draw_fps_counter(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    actual_tps: f64,
+) -> crossterm::Result<()> {
+    let counter = format!("{:5.1} TPS", actual_tps);
+
+    renderer.set_layer(Layer::Hud);
+    renderer.draw_text(
+        ctx.terminal_width.saturating_sub(counter.len() as u16),
+        0,
+        &counter,
+        ctx.colored(ctx.theme.muted),
+    )
+}
+
+fn run_game(
+    writer: &mut impl Terminal,
+    controller: &mut Controller,
+    events: &mpsc::Receiver<event::Event>,
+    broadcaster: Option<Broadcaster>,
+    mut host: Option<Host>,
+    profile: &mut Profile,
+    options: &mut Options,
+) -> crossterm::Result<bool> {
+    // The menu runs its own loop, and the game is set up with whatever was chosen there.
+    if controller.screen == GameScreen::Menu {
+        if !run_menu(writer, events, options, profile)? {
+            controller.screen = GameScreen::Closed;
+            return Ok(false);
         }
+
+        *controller = renew_controller(options)?;
     }
 
-    stdout
-        .execute(terminal::LeaveAlternateScreen)?
-        .execute(cursor::Show)?;
-    terminal::disable_raw_mode()
+    // Networked rounds start right away, so that the other side isn't kept waiting unawares.
+    let counts_down = !options.demo && host.is_none();
+    if counts_down && controller.screen == GameScreen::Playing {
+        start_countdown(controller);
+    }
+
+    let start = Instant::now();
+    let mut timestep =
+        FixedTimestep::new(start + effects::tick_interval(controller.ticks_per_sec, &[]));
+    let mut stats = TimingStats::new(start);
+    let mut tick: u64 = 0;
+
+    // Only measured when it is shown.
+    let mut tick_rate = if options.fps_counter {
+        Some(TickRateMeter::new())
+    } else {
+        None
+    };
+
+    let mut feedback: Box<dyn Feedback> = if options.sound {
+        Box::new(TerminalBell::with_tick_rate(controller.ticks_per_sec))
+    } else {
+        Box::new(Silent)
+    };
+
+    let today = snapshot::today();
+    let mut unlocked_this_run: Vec<&Achievement> = Vec::new();
+    let mut toast: Option<(String, u32)> = None;
+    // Ticks left to show "Go!" for once the countdown is over.
+    let mut go_ticks = 0;
+
+    // Turned off for good once the terminal can't keep up with the frame rate.
+    let mut smooth = options.smooth;
+    let mut motion = None;
+
+    let mut help_was_open = false;
+    let mut death_ticks = 0;
+    let mut animation_was_shown = false;
+    let mut recorded = false;
+    let mut online: Option<online::Status> = None;
+    let mut global_was_shown = false;
+    let mut demo_frames = 0;
+
+    let mut screen = Screen::new();
+
+    loop {
+        // Logic runs as often as the wall clock demands, but the board is drawn only once. In
+        // smooth mode frames are also drawn in between ticks.
+        let interval =
+            effects::tick_interval(speed::ticks_per_sec(controller), &controller.active_effects);
+        let due_ticks = if smooth {
+            timestep.wait_at_most(interval, SMOOTH_FRAME_BUDGET)
+        } else {
+            timestep.wait(interval)
+        };
+
+        for _ in 0..due_ticks {
+            handle_events(controller, events);
+            if let Some(host) = &host {
+                host.receive(controller);
+            }
+
+            // Counted from the tick after the one the snake died on.
+            if controller.screen == GameScreen::GameOver && !controller.show_help {
+                death_ticks = (death_ticks + 1).min(DEATH_ANIMATION_TICKS);
+            }
+
+            if controller.screen == GameScreen::Countdown && !controller.show_help {
+                count_down(controller);
+                if controller.screen == GameScreen::Playing {
+                    go_ticks = u32::from(controller.ticks_per_sec);
+                }
+            } else {
+                go_ticks = go_ticks.saturating_sub(1);
+            }
+
+            // The help overlay pauses the game too.
+            if controller.screen == GameScreen::Playing && !controller.show_help {
+                take_buffered_turn(controller);
+                motion = Motion::capture(controller);
+                profile.replay.record(controller);
+                continue_game_logic(controller);
+                profile.recording.record(controller);
+
+                if let Some((path, progress)) = &mut profile.achievements {
+                    let unlocked = progress.unlock_new(controller, &today);
+
+                    // Saved right away, so that nothing is lost if the game gets killed.
+                    if !unlocked.is_empty() {
+                        let text = match progress.save(path) {
+                            Ok(()) => format!("Achievement unlocked: {}", unlocked[0].name),
+                            Err(e) => format!("Could not save achievements: {}", e),
+                        };
+
+                        toast = Some((text, TOAST_SECS * u32::from(controller.ticks_per_sec)));
+                        unlocked_this_run.extend(unlocked);
+                    }
+                }
+            }
+
+            if let Some((_, remaining_ticks)) = &mut toast {
+                *remaining_ticks -= 1;
+
+                if *remaining_ticks == 0 {
+                    toast = None;
+                }
+            }
+
+            for cue in controller.cues.drain(..) {
+                feedback.cue(cue);
+            }
+            feedback.tick(writer)?;
+
+            if let Some(tick_rate) = &mut tick_rate {
+                tick_rate.record_tick(Instant::now());
+            }
+
+            tick += 1;
+        }
+
+        if controller.restart_requested {
+            toast = finish_run(profile, options, controller)
+                .map(|text| (text, TOAST_SECS * u32::from(controller.ticks_per_sec)));
+
+            *controller = renew_controller(options)?;
+            if counts_down {
+                start_countdown(controller);
+            }
+            profile.recording = Ghost::default();
+            profile.replay = Replay::default();
+
+            unlocked_this_run.clear();
+            motion = None;
+            death_ticks = 0;
+            animation_was_shown = false;
+            recorded = false;
+            online = None;
+
+            // The end screen is drawn over the last frame, so it has to be wiped.
+            screen.next_mut().clear();
+            continue;
+        }
+
+        // Only losing gets the animation, a won run goes straight to the end screen.
+        let animating = controller.screen == GameScreen::GameOver
+            && !controller.won
+            && death_ticks < DEATH_ANIMATION_TICKS;
+
+        // A demo starts over once the end screen has been up for a moment.
+        if controller.demo
+            && controller.screen == GameScreen::GameOver
+            && death_ticks == DEATH_ANIMATION_TICKS
+        {
+            demo_frames += 1;
+            if demo_frames > DEMO_END_SCREEN_FRAMES {
+                demo_frames = 0;
+                controller.restart_requested = true;
+            }
+        }
+
+        // A run that made it onto the leaderboard asks for a name once it's over. Two player
+        // games don't count.
+        let ranks = controller.second.is_none()
+            && profile
+                .leaderboard
+                .as_ref()
+                .is_some_and(|(_, leaderboard)| leaderboard.qualifies(controller.score));
+
+        if controller.screen == GameScreen::GameOver
+            && !animating
+            && controller.name_prompt.is_none()
+            && ranks
+        {
+            controller.name_prompt = Some(NamePrompt::new());
+        }
+
+        // Recorded as soon as the name is in, so the end screen can show where the run placed.
+        if let (Some((path, leaderboard)), Some(prompt)) =
+            (&mut profile.leaderboard, &controller.name_prompt)
+        {
+            if prompt.state == PromptState::Submitted && !recorded {
+                let entry = Entry {
+                    name: prompt.name(),
+                    score: controller.score,
+                    date: snapshot::timestamp(),
+                    board: Some((controller.bounds.width(), controller.bounds.height())),
+                    completed: controller.won,
+                };
+
+                match Leaderboard::record(path, entry) {
+                    Ok(updated) => *leaderboard = updated,
+                    Err(e) => {
+                        profile.leaderboard_error = Some(format!(
+                            "Could not save the leaderboard to {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    }
+                }
+                recorded = true;
+            }
+        }
+
+        // Sent once the run is over and, if it made the leaderboard, has a name. Like the local
+        // leaderboard, only solo runs played by hand count.
+        let named = controller
+            .name_prompt
+            .as_ref()
+            .is_none_or(|prompt| prompt.state != PromptState::Editing);
+
+        if let Some(endpoint) = &options.online_leaderboard {
+            if controller.screen == GameScreen::GameOver
+                && !animating
+                && named
+                && online.is_none()
+                && controller.second.is_none()
+                && options.bot.is_none()
+            {
+                online = Some(online::submit(endpoint, &submission(profile, controller)));
+            }
+        }
+
+        let (global, online_line) = match online.as_mut().map(online::Status::poll) {
+            None => (None, None),
+            Some(None) => (
+                None,
+                Some(String::from("Sending to the online leaderboard...")),
+            ),
+            Some(Some(Ok(top))) => (Some(top.clone()), Some(String::from("Global top 10"))),
+            Some(Some(Err(e))) => (None, Some(format!("Online leaderboard: {}", e))),
+        };
+
+        if std::mem::take(&mut controller.redraw_requested) {
+            screen.invalidate();
+        }
+
+        let frame_start = Instant::now();
+        // A paused board is drawn dimmed and without colors.
+        let paused = controller.screen == GameScreen::Paused;
+        let mut ctx = FrameContext::new(writer, &controller.bounds, &options.render)?;
+        ctx.no_color |= paused;
+        ctx.dimmed = paused;
+        let mut renderer = TerminalRenderer::new(writer, &mut screen, &ctx);
+
+        // Nobody can play a board they can't see all of.
+        if ctx.too_small() && controller.screen == GameScreen::Playing {
+            controller.screen = GameScreen::Paused;
+        }
+
+        if options.mouse {
+            controller.mouse_origin = Some(ctx.origin.clone());
+        }
+
+        match controller.screen {
+            GameScreen::Countdown | GameScreen::Playing | GameScreen::Paused => {
+                let ghost = match &profile.ghost {
+                    Some((_, Some(ghost))) if controller.show_ghost => ghost.snake_at(controller),
+                    _ => None,
+                };
+
+                let partial_cells = match &motion {
+                    Some(motion)
+                        if smooth
+                            && options.render.charset.unicode
+                            && !controller.show_help
+                            && !paused =>
+                    {
+                        let progress = timestep.progress(Instant::now(), interval);
+                        smooth::partial_cells(motion, controller, smooth::phase(progress))
+                    }
+                    _ => Vec::new(),
+                };
+
+                draw(
+                    &mut renderer,
+                    &ctx,
+                    &options.render,
+                    controller,
+                    ghost,
+                    &partial_cells,
+                    toast.as_ref().map(|(text, _)| text.as_str()),
+                )?;
+
+                if controller.screen == GameScreen::Countdown {
+                    draw_countdown(&mut renderer, &ctx, &countdown_secs(controller).to_string())?;
+                } else if go_ticks > 0 && !paused {
+                    draw_countdown(&mut renderer, &ctx, "Go!")?;
+                }
+
+                if paused {
+                    draw_paused(&mut renderer, &ctx)?;
+                }
+            }
+            GameScreen::GameOver if animating => {
+                show_death_animation(&mut renderer, &ctx, death_ticks)?;
+                animation_was_shown = true;
+            }
+            GameScreen::GameOver => {
+                // The end screen is drawn over what is already there, so a closed overlay has to be
+                // wiped explicitly, and so does the last frame of the animation.
+                if help_was_open && !controller.show_help {
+                    renderer.clear()?;
+                }
+
+                if animation_was_shown {
+                    clear_canvas(&mut renderer, &ctx)?;
+                    animation_was_shown = false;
+                }
+
+                // The global table takes the place of the local one once it's in.
+                if global.is_some() != global_was_shown {
+                    renderer.clear()?;
+                    global_was_shown = global.is_some();
+                }
+
+                let leaderboard = global.as_ref().or(profile
+                    .leaderboard
+                    .as_ref()
+                    .map(|(_, leaderboard)| leaderboard));
+                show_endscreen(
+                    &mut renderer,
+                    &ctx,
+                    controller,
+                    &unlocked_this_run,
+                    leaderboard,
+                )?;
+
+                if let Some(line) = &online_line {
+                    draw_online_status(&mut renderer, &ctx, line)?;
+                }
+            }
+            // Nothing is left to draw on the way out.
+            GameScreen::Menu | GameScreen::Closed => (),
+        }
+
+        if controller.show_help {
+            draw_help(&mut renderer, &ctx, &controller.keys.help_lines())?;
+        }
+        help_was_open = controller.show_help;
+
+        stats.frame_time = frame_start.elapsed();
+        stats.record_ticks(Instant::now(), due_ticks);
+
+        if stats.frame_time > SMOOTH_FRAME_BUDGET {
+            smooth = false;
+        }
+
+        if options.show_timing {
+            draw_timing(&mut renderer, &ctx, &stats)?;
+        }
+
+        if let Some(tick_rate) = &tick_rate {
+            draw_fps_counter(&mut renderer, &ctx, tick_rate.ticks_per_sec())?;
+        }
+
+        renderer.present()?;
+
+        // Frames drawn in between ticks have nothing new to send.
+        if let Some(broadcaster) = broadcaster.as_ref().filter(|_| due_ticks > 0) {
+            broadcaster.send(&FrameSnapshot::capture(tick, controller));
+        }
+        if let Some(host) = host.as_mut().filter(|_| due_ticks > 0) {
+            host.send(tick, controller);
+        }
+
+        if controller.screen == GameScreen::Closed {
+            break;
+        }
+    }
+
+    Ok(true)
+}
+
+fn run_spectator(
+    writer: &mut impl Terminal,
+    controller: &mut Controller,
+    events: &mpsc::Receiver<event::Event>,
+    frames: mpsc::Receiver<FrameSnapshot>,
+    render: &RenderConfig,
+) -> crossterm::Result<()> {
+    let mut timestep = FixedTimestep::new(Instant::now());
+    let mut status = String::from("Waiting for the first frame...");
+
+    let mut screen = Screen::new();
+
+    loop {
+        timestep.wait(effects::tick_interval(controller.ticks_per_sec, &[]));
+
+        handle_spectator_events(controller, events);
+
+        loop {
+            match frames.try_recv() {
+                Ok(frame) => {
+                    frame.apply(controller);
+                    status = format!("Spectating - tick {}", frame.tick);
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    status = String::from("Broadcast ended - press q to quit");
+                    break;
+                }
+            }
+        }
+
+        let ctx = FrameContext::new(writer, &controller.bounds, render)?;
+        let mut renderer = TerminalRenderer::new(writer, &mut screen, &ctx);
+
+        if controller.screen == GameScreen::GameOver {
+            show_endscreen(&mut renderer, &ctx, controller, &[], None)?;
+        } else {
+            draw_spectator(&mut renderer, &ctx, render, controller, &status)?;
+        }
+        renderer.present()?;
+
+        if controller.screen == GameScreen::Closed {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Spectators don't play, pause or open the help, they can only leave with the quit key or
+// ctrl+c.
+fn handle_spectator_events(controller: &mut Controller, events: &mpsc::Receiver<event::Event>) {
+    for e in events.try_iter() {
+        if let event::Event::Key(event) = e {
+            let ctrl_c = event.code == event::KeyCode::Char('c')
+                && event.modifiers.contains(event::KeyModifiers::CONTROL);
+
+            if ctrl_c || controller.keys.action(event.code) == Some(Action::Quit) {
+                controller.screen = GameScreen::Closed;
+            }
+        }
+    }
+}
+
+// Plays on the board of a --host game or a --server room: key presses go to whoever runs the
+// game, and the board comes back from there. With --watch the board only comes back.
+fn run_client(
+    writer: &mut impl Terminal,
+    controller: &mut Controller,
+    events: &mpsc::Receiver<event::Event>,
+    mut client: Client,
+    render: &RenderConfig,
+) -> crossterm::Result<()> {
+    let mut timestep = FixedTimestep::new(Instant::now());
+    let mut status = String::from("Waiting for the game to start...");
+
+    let mut screen = Screen::new();
+
+    loop {
+        // The host sets the pace, so this only has to keep up with it.
+        timestep.wait(SMOOTH_FRAME_BUDGET);
+
+        handle_events(controller, events);
+
+        // What a turn does is up to the host, so it is only passed on, the latest one each frame.
+        controller.turns.clear();
+        if let Some(event::Event::Key(key)) = controller.last_event.take() {
+            let turn = controller
+                .keys
+                .playing_action(key.code)
+                .and_then(action_direction);
+
+            if let Some(Err(e)) = turn.map(|turn| client.turn(turn)) {
+                status = format!("Lost the host: {} - press q to quit", e);
+            }
+        }
+
+        match client.receive(controller) {
+            Ok(Some(_)) => {
+                let second = controller.second.as_ref().map_or(0, |second| second.score);
+                status = if client.is_watching() {
+                    format!("Watching - P1 {} - P2 {}", controller.score, second)
+                } else {
+                    format!("Opponent {} - You {}", controller.score, second)
+                };
+            }
+            Ok(None) => (),
+            Err(e) => status = format!("{} - press q to quit", e),
+        }
+
+        let ctx = FrameContext::new(writer, &controller.bounds, render)?;
+        let mut renderer = TerminalRenderer::new(writer, &mut screen, &ctx);
+
+        if controller.screen == GameScreen::GameOver {
+            show_endscreen(&mut renderer, &ctx, controller, &[], None)?;
+        } else {
+            draw_spectator(&mut renderer, &ctx, render, controller, &status)?;
+        }
+        renderer.present()?;
+
+        if controller.screen == GameScreen::Closed {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// What the options screen can change before a game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Setting {
+    Difficulty,
+    Speed,
+    GameMode,
+    Sound,
+    Grid,
+    Theme,
+    Smooth,
+    Colorblind,
+    Controls,
+    Back,
+}
+
+const SETTINGS: [Setting; 10] = [
+    Setting::Difficulty,
+    Setting::Speed,
+    Setting::GameMode,
+    Setting::Sound,
+    Setting::Grid,
+    Setting::Theme,
+    Setting::Smooth,
+    Setting::Colorblind,
+    Setting::Controls,
+    Setting::Back,
+];
+
+fn setting_line(options: &Options, setting: Setting) -> String {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+
+    match setting {
+        Setting::Difficulty => format!("Difficulty: < {:?} >", options.difficulty),
+        Setting::Speed => format!("Speed: < {} >", options.config.ticks_per_sec),
+        Setting::GameMode => format!("Mode: < {:?} >", options.game_mode),
+        Setting::Sound => format!("Sound: < {} >", on_off(options.sound)),
+        Setting::Grid => format!("Grid: < {} >", on_off(options.render.grid)),
+        Setting::Theme => format!("Theme: < {} >", options.theme),
+        Setting::Smooth => format!("Smooth: < {} >", on_off(options.smooth)),
+        Setting::Colorblind => format!("Colorblind: < {} >", on_off(options.render.colorblind)),
+        Setting::Controls => String::from("Controls"),
+        Setting::Back => String::from("Back"),
+    }
+}
+
+// Left and right step the difficulty and the speed, everything else only has two values to
+// toggle between.
+fn adjust_setting(options: &mut Options, setting: Setting, step: i8) {
+    match setting {
+        Setting::Difficulty => {
+            options.difficulty = options.difficulty.step(step);
+
+            let preset = options.difficulty.preset();
+            options.config.ticks_per_sec = preset.ticks_per_sec;
+            // A fitted board keeps its size.
+            if !options.fit {
+                options.config.bounds =
+                    CanvasBounds::from_canvas_size(preset.canvas_size.0, preset.canvas_size.1);
+            }
+            options.wrap = preset.wrap;
+            options.apple_count = preset.apple_count;
+            options.ramp = preset.ramp;
+        }
+        Setting::Speed => {
+            let speed = i32::from(options.config.ticks_per_sec) + i32::from(step);
+            options.config.ticks_per_sec = speed.clamp(1, i32::from(MAX_SPEED)) as u16;
+        }
+        Setting::GameMode => {
+            options.game_mode = match options.game_mode {
+                GameMode::Classic => GameMode::Hunger,
+                GameMode::Hunger => GameMode::Shrink,
+                GameMode::Shrink => GameMode::Classic,
+            }
+        }
+        Setting::Sound => options.sound = !options.sound,
+        Setting::Grid => options.render.grid = !options.render.grid,
+        // Themes go round in a circle either way.
+        Setting::Theme => {
+            let count = options.themes.len() as i32;
+            let current = options
+                .themes
+                .iter()
+                .position(|(name, _)| *name == options.theme)
+                .unwrap_or(0) as i32;
+            let (name, theme) =
+                &options.themes[(current + i32::from(step)).rem_euclid(count) as usize];
+
+            options.theme = name.clone();
+            options.render.theme = *theme;
+        }
+        Setting::Smooth => options.smooth = !options.smooth,
+        Setting::Colorblind => options.render.colorblind = !options.render.colorblind,
+        Setting::Controls | Setting::Back => (),
+    }
+}
+
+// The actions the controls screen can give another key. Esc goes back, there is no room for a
+// Back entry on a 24 row terminal.
+const CONTROLS: [Action; 8] = [
+    Action::Up,
+    Action::Down,
+    Action::Left,
+    Action::Right,
+    Action::Sprint,
+    Action::Pause,
+    Action::Restart,
+    Action::Quit,
+];
+
+fn control_line(options: &Options, action: Action) -> String {
+    let keys: Vec<String> = options
+        .keys
+        .keys(action)
+        .into_iter()
+        .map(key_label)
+        .collect();
+    let keys = if keys.is_empty() {
+        String::from("none")
+    } else {
+        keys.join("/")
+    };
+    format!("{}: {}", action.description(), keys)
+}
+
+// The screens reachable from the main menu.
+enum MenuScreen {
+    Main(Menu<MainMenuItem>),
+    Options(Menu<Setting>),
+    Controls(Menu<Action>),
+    // Waiting for the key to bind, with the controls screen to go back to.
+    Rebinding(Menu<Action>, Action),
+    HighScores,
+}
+
+// Shows the main menu until a game is started, which returns true, or the player quits.
+fn run_menu(
+    writer: &mut impl Terminal,
+    events: &mpsc::Receiver<event::Event>,
+    options: &mut Options,
+    profile: &Profile,
+) -> crossterm::Result<bool> {
+    let mut screen = MenuScreen::Main(Menu::new(&MAIN_MENU));
+
+    let mut shown = Screen::new();
+
+    loop {
+        let ctx = FrameContext::new(writer, &options.config.bounds, &options.render)?;
+        let mut renderer = TerminalRenderer::new(writer, &mut shown, &ctx);
+
+        match &screen {
+            MenuScreen::Main(menu) => {
+                let lines: Vec<String> = menu
+                    .items
+                    .iter()
+                    .map(|item| String::from(item.label()))
+                    .collect();
+                draw_menu(&mut renderer, &ctx, &lines, Some(menu.selected))?;
+            }
+            MenuScreen::Options(menu) => {
+                let lines: Vec<String> = menu
+                    .items
+                    .iter()
+                    .map(|setting| setting_line(options, *setting))
+                    .collect();
+                draw_menu(&mut renderer, &ctx, &lines, Some(menu.selected))?;
+            }
+            MenuScreen::Controls(menu) => {
+                let lines: Vec<String> = menu
+                    .items
+                    .iter()
+                    .map(|action| control_line(options, *action))
+                    .collect();
+                draw_menu(&mut renderer, &ctx, &lines, Some(menu.selected))?;
+            }
+            MenuScreen::Rebinding(_, action) => {
+                let lines = [
+                    format!("Press a key for: {}", action.description()),
+                    String::from("Esc to cancel"),
+                    String::from("It lasts until you quit, keep it in [keys] in config.toml"),
+                ];
+                draw_menu(&mut renderer, &ctx, &lines, None)?;
+            }
+            MenuScreen::HighScores => {
+                draw_menu(
+                    &mut renderer,
+                    &ctx,
+                    &[String::from("Press any key to go back")],
+                    None,
+                )?;
+
+                let top = (ctx.terminal_height / 2).saturating_add(6);
+                match &profile.leaderboard {
+                    Some((_, leaderboard)) => {
+                        draw_high_scores(&mut renderer, &ctx, leaderboard, top)?
+                    }
+                    None => draw_menu_line(
+                        &mut renderer,
+                        &ctx,
+                        top,
+                        "The leaderboard isn't available.",
+                    )?,
+                }
+            }
+        }
+        renderer.present()?;
+
+        // Redrawn every now and then even without a key, so that resizing the terminal works.
+        let key = match events.recv_timeout(INPUT_POLL_INTERVAL) {
+            Ok(event::Event::Key(key)) => key,
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(false),
+        };
+
+        if key.code == event::KeyCode::Char('c')
+            && key.modifiers.contains(event::KeyModifiers::CONTROL)
+        {
+            return Ok(false);
+        }
+
+        screen = match screen {
+            MenuScreen::Main(mut menu) => match menu.handle_key(key.code) {
+                Some(MenuInput::Choose(MainMenuItem::Play)) => return Ok(true),
+                Some(MenuInput::Choose(MainMenuItem::Options)) => {
+                    MenuScreen::Options(Menu::new(&SETTINGS))
+                }
+                Some(MenuInput::Choose(MainMenuItem::HighScores)) => MenuScreen::HighScores,
+                Some(MenuInput::Choose(MainMenuItem::Quit)) | Some(MenuInput::Back) => {
+                    return Ok(false)
+                }
+                _ => MenuScreen::Main(menu),
+            },
+            MenuScreen::Options(mut menu) => match menu.handle_key(key.code) {
+                Some(MenuInput::Choose(Setting::Back)) | Some(MenuInput::Back) => {
+                    MenuScreen::Main(Menu::new(&MAIN_MENU))
+                }
+                Some(MenuInput::Choose(Setting::Controls)) => {
+                    MenuScreen::Controls(Menu::new(&CONTROLS))
+                }
+                Some(MenuInput::Choose(setting)) => {
+                    adjust_setting(options, setting, 1);
+                    MenuScreen::Options(menu)
+                }
+                Some(MenuInput::Adjust(setting, step)) => {
+                    adjust_setting(options, setting, step);
+                    MenuScreen::Options(menu)
+                }
+                None => MenuScreen::Options(menu),
+            },
+            MenuScreen::Controls(mut menu) => match menu.handle_key(key.code) {
+                Some(MenuInput::Back) => {
+                    let mut settings = Menu::new(&SETTINGS);
+                    settings.selected = SETTINGS
+                        .iter()
+                        .position(|setting| *setting == Setting::Controls)
+                        .unwrap_or(0);
+                    MenuScreen::Options(settings)
+                }
+                Some(MenuInput::Choose(action)) => MenuScreen::Rebinding(menu, action),
+                _ => MenuScreen::Controls(menu),
+            },
+            // Any key but Esc is taken, arrows and Enter included.
+            MenuScreen::Rebinding(menu, action) => {
+                if key.code != event::KeyCode::Esc {
+                    options.keys.bind(key.code, action);
+                }
+                MenuScreen::Controls(menu)
+            }
+            MenuScreen::HighScores => MenuScreen::Main(Menu::new(&MAIN_MENU)),
+        };
+    }
+}
+
+// The logo with a list of lines under it, `selected` one highlighted.
+fn draw_menu(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    lines: &[String],
+    selected: Option<usize>,
+) -> crossterm::Result<()> {
+    renderer.clear()?;
+
+    // A list that would run off the bottom moves up into the gap under the logo, and then
+    // pushes the logo up as well.
+    let top = (ctx.terminal_height / 2)
+        .saturating_add(4)
+        .min(ctx.terminal_height.saturating_sub(lines.len() as u16));
+    let logo_height = ctx.charset.logo.lines().count() as u16;
+    draw_logo(
+        renderer,
+        ctx,
+        logo_top(ctx).min(top.saturating_sub(logo_height)),
+    )?;
+
+    for (index, line) in lines.iter().enumerate() {
+        let row = top.saturating_add(index as u16);
+
+        if selected == Some(index) {
+            let line = format!("> {} <", line);
+            renderer.draw_text(
+                ctx.centered(&line),
+                row,
+                &line,
+                ctx.colored(ctx.theme.highlight),
+            )?;
+        } else {
+            draw_menu_line(renderer, ctx, row, line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_menu_line(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    row: u16,
+    line: &str,
+) -> crossterm::Result<()> {
+    renderer.draw_text(ctx.centered(line), row, line, ctx.plain())
+}
+
+// Plays a recorded run back in place of the player. The only keys are quitting and `+` and `-`,
+// which double and halve the speed.
+fn run_replay(
+    writer: &mut impl Terminal,
+    controller: &mut Controller,
+    events: &mpsc::Receiver<event::Event>,
+    replay: &Replay,
+    render: &RenderConfig,
+) -> crossterm::Result<()> {
+    let mut playback = Playback::new(replay);
+    let mut speed = 1.0;
+
+    // Nothing moves before the first key, so that wait is skipped.
+    while controller.snake.current_direction == Direction::Stop && playback.step(controller) {}
+    controller.cues.clear();
+
+    let mut timestep = FixedTimestep::new(Instant::now());
+
+    let mut screen = Screen::new();
+
+    loop {
+        let interval =
+            effects::tick_interval(speed::ticks_per_sec(controller), &controller.active_effects);
+
+        for _ in 0..timestep.wait(interval.div_f64(speed)) {
+            playback.step(controller);
+
+            // Replays are watched in silence.
+            controller.cues.clear();
+        }
+
+        for e in events.try_iter() {
+            if let event::Event::Key(key) = e {
+                let ctrl_c = key.code == event::KeyCode::Char('c')
+                    && key.modifiers.contains(event::KeyModifiers::CONTROL);
+
+                match key.code {
+                    event::KeyCode::Char('+') => speed = (speed * 2.0).min(MAX_REPLAY_SPEED),
+                    event::KeyCode::Char('-') => speed = (speed / 2.0).max(1.0 / MAX_REPLAY_SPEED),
+                    code if ctrl_c || controller.keys.action(code) == Some(Action::Quit) => {
+                        controller.screen = GameScreen::Closed
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let ctx = FrameContext::new(writer, &controller.bounds, render)?;
+        let mut renderer = TerminalRenderer::new(writer, &mut screen, &ctx);
+
+        if controller.screen == GameScreen::GameOver {
+            show_endscreen(&mut renderer, &ctx, controller, &[], None)?;
+        } else {
+            let status = match playback.position() {
+                _ if playback.is_over() => String::from("Replay over - press q to quit"),
+                (tick, ticks) => format!("Replay - tick {}/{} at {}x", tick, ticks, speed),
+            };
+
+            draw_spectator(&mut renderer, &ctx, render, controller, &status)?;
+        }
+        renderer.present()?;
+
+        if controller.screen == GameScreen::Closed {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_editor(
+    renderer: &mut impl Renderer,
+    ctx: &FrameContext,
+    render: &RenderConfig,
+    editor: &Editor,
+    status: &str,
+) -> crossterm::Result<()> {
+    renderer.clear()?;
+
+    draw_borders(renderer, ctx)?;
+    draw_obstacles(renderer, ctx, &editor.level.walls)?;
+
+    for cell in &editor.level.no_apples {
+        renderer.draw_cell(cell, ctx.charset.no_apples, ctx.colored(ctx.theme.muted))?;
+    }
+
+    if let Some(spawn) = &editor.level.spawn {
+        renderer.draw_cell(spawn, &render.snake, ctx.colored(ctx.theme.snake))?;
+    }
+
+    // The cursor shows what is under it, reversed so that it stands out without colors too.
+    let under_cursor = match editor.level.tile(&editor.cursor) {
+        Tile::Empty => "  ",
+        Tile::Wall => ctx.charset.obstacle,
+        Tile::NoApples => ctx.charset.no_apples,
+        Tile::Spawn => &render.snake,
+    };
+    renderer.draw_cell(
+        &editor.cursor,
+        under_cursor,
+        ctx.plain().attribute(style::Attribute::Reverse),
+    )?;
+
+    let help = "Arrows move, space wall, x no apples, s start, w save, q quit";
+    for (text, row) in &[
+        (status, ctx.upper_border.saturating_sub(1)),
+        (help, ctx.lower_border.saturating_add(1)),
+    ] {
+        renderer.draw_text(ctx.centered(text), *row, text, ctx.colored(ctx.theme.muted))?;
+    }
+
+    renderer.present()
+}
+
+fn run_editor(
+    writer: &mut impl Terminal,
+    events: &mpsc::Receiver<event::Event>,
+    editor: &mut Editor,
+    path: &Path,
+    render: &RenderConfig,
+) -> crossterm::Result<()> {
+    // Reports the last save until the next key.
+    let mut saved: Option<String> = None;
+
+    let mut screen = Screen::new();
+
+    loop {
+        let status = match &saved {
+            Some(message) => message.clone(),
+            None if editor.confirm_quit => {
+                String::from("Unsaved changes - press q again to quit without saving")
+            }
+            None if editor.modified => format!("Editing {} (modified)", path.display()),
+            None => format!("Editing {}", path.display()),
+        };
+
+        let ctx = FrameContext::new(writer, &editor.level.bounds, render)?;
+        draw_editor(
+            &mut TerminalRenderer::new(writer, &mut screen, &ctx),
+            &ctx,
+            render,
+            editor,
+            &status,
+        )?;
+
+        // Nothing moves on its own, so the editor only redraws after input.
+        let key = match events.recv() {
+            Ok(event::Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(_) => return Ok(()),
+        };
+        saved = None;
+
+        if key.code == event::KeyCode::Char('c')
+            && key.modifiers.contains(event::KeyModifiers::CONTROL)
+        {
+            return Ok(());
+        }
+
+        match editor.handle_key(key.code) {
+            Some(EditorInput::Save) => {
+                saved = Some(match editor.level.save(path) {
+                    Ok(()) => {
+                        editor.modified = false;
+                        format!("Saved to {}", path.display())
+                    }
+                    Err(e) => format!("Could not save to {}: {}", path.display(), e),
+                })
+            }
+            Some(EditorInput::Quit) => return Ok(()),
+            None => (),
+        }
+    }
+}
+
+// One game for a telnet client, from the menu to the end screen. The client's terminal is in
+// character mode already, so there is no raw mode to enter.
+fn play_over_telnet(
+    mut connection: telnet::Connection,
+    events: &mpsc::Receiver<event::Event>,
+    mut options: Options,
+) -> crossterm::Result<()> {
+    // Runs played by strangers don't go on this machine's global leaderboard, and telnet
+    // input is decoded into key presses only.
+    options.online_leaderboard = None;
+    options.mouse = false;
+    options.mouse_follow = false;
+    // Nothing says what the client's terminal can show.
+    options.render.truecolor = false;
+
+    if options.fit {
+        if let Err(message) = fit_board(&mut options, connection.size()) {
+            writeln!(connection, "{}\r", message)?;
+            return Ok(());
+        }
+    }
+
+    let mut controller = match new_controller(&options) {
+        Ok(controller) => controller,
+        Err(message) => {
+            writeln!(connection, "{}\r", message)?;
+            return Ok(());
+        }
+    };
+
+    connection
+        .execute(terminal::EnterAlternateScreen)?
+        .execute(cursor::Hide)?;
+
+    if !options.no_menu && !options.demo {
+        controller.screen = GameScreen::Menu;
+    }
+    let result = run_game(
+        &mut connection,
+        &mut controller,
+        events,
+        None,
+        None,
+        &mut Profile::default(),
+        &mut options,
+    );
+
+    connection
+        .execute(terminal::LeaveAlternateScreen)?
+        .execute(cursor::Show)?;
+
+    result.map(|_| ())
+}
+
+// Forwards terminal events to the game loop until `stop` is dropped. Polling with a timeout
+// instead of blocking in `event::read` is what lets the thread notice the shutdown.
+fn spawn_input_thread(
+    events: mpsc::Sender<event::Event>,
+    stop: mpsc::Receiver<()>,
+) -> thread::JoinHandle<crossterm::Result<()>> {
+    thread::spawn(move || {
+        while let Err(mpsc::TryRecvError::Empty) = stop.try_recv() {
+            if event::poll(INPUT_POLL_INTERVAL)? && events.send(event::read()?).is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+fn main() -> crossterm::Result<()> {
+    let file_config = match config::default_path().map(|path| (Config::load(&path), path)) {
+        Some((Ok(config), _)) => config,
+        Some((Err(e), path)) => {
+            eprintln!("Could not read {}: {}", path.display(), e);
+            std::process::exit(2);
+        }
+        None => Config::default(),
+    };
+
+    let user_themes = match config::themes_dir().map(|dir| (theme::load_dir(&dir), dir)) {
+        Some((Ok(themes), _)) => themes,
+        Some((Err(e), dir)) => {
+            eprintln!("Could not read {}: {}", dir.display(), e);
+            std::process::exit(2);
+        }
+        None => Vec::new(),
+    };
+
+    let mut options = match parse_args(std::env::args().skip(1), &file_config, &user_themes) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Telnet games are fitted to every client's window instead.
+    if options.fit && !matches!(options.mode, Mode::Telnet(_)) {
+        let size = terminal::size()
+            .map_err(|e| format!("--fit can't tell the size of the terminal: {}", e));
+        if let Err(message) = size.and_then(|size| fit_board(&mut options, size)) {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    }
+
+    if let Err(message) = validate_game_config(&options.config) {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    }
+
+    // A server has no terminal to draw to, so it runs until it is killed.
+    if let Mode::Server(addr) = &options.mode {
+        println!("Serving rooms on {}...", addr);
+        let addr = addr.clone();
+        server::run(
+            addr,
+            move || new_controller_or_exit(&options),
+            |room, event| println!("{}", event.describe(room)),
+        )?;
+        return Ok(());
+    }
+
+    if let Mode::Telnet(addr) = &options.mode {
+        println!("Serving games over telnet on {}...", addr);
+        let listener = TcpListener::bind(addr)?;
+        telnet::serve(listener, move |connection, events| {
+            if let Err(e) = play_over_telnet(connection, &events, options.clone()) {
+                eprintln!("A telnet game ended with an error: {}", e);
+            }
+        });
+        return Ok(());
+    }
+
+    if options.bench {
+        println!(
+            "Running the game logic for {}s with a snake of {}...",
+            bench::BENCH_DURATION.as_secs(),
+            bench::BENCH_SNAKE_LENGTH
+        );
+
+        let report = bench::run(bench::BENCH_DURATION, bench::BENCH_SNAKE_LENGTH, || {
+            new_controller_or_exit(&options)
+        });
+        println!("{}", bench::report_line(&report));
+        return Ok(());
+    }
+
+    // Connect before entering raw mode so that network errors stay readable.
+    let mut broadcaster = None;
+    let mut frames = None;
+    let mut host = None;
+    let mut client = None;
+    let mut replay = None;
+    let mut editor = None;
+
+    match &options.mode {
+        Mode::Play => (),
+        Mode::Broadcast(addr) => broadcaster = Some(Broadcaster::bind(addr)?),
+        Mode::Spectate(addr) => frames = Some(broadcast::spectate(addr)?),
+        Mode::Host(addr) => {
+            println!("Waiting for a player to join on {}...", addr);
+            host = Some(Host::accept(addr)?);
+        }
+        Mode::Join(addr) => client = Some(Client::join(addr, &options.room)?),
+        Mode::Watch(addr) => client = Some(Client::watch(addr, &options.room)?),
+        Mode::Server(_) | Mode::Telnet(_) => unreachable!("servers don't get this far"),
+        Mode::Replay(path) => match Replay::load(path) {
+            Ok(loaded) => replay = Some(loaded),
+            Err(e) => {
+                eprintln!("Could not load the replay {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        },
+        // A level that doesn't exist yet starts out empty, at the size given on the command
+        // line.
+        Mode::Edit(path) => match Level::load(path) {
+            Ok(level) => editor = Some((Editor::new(level), path.clone())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                editor = Some((
+                    Editor::new(Level::empty(options.config.bounds)),
+                    path.clone(),
+                ))
+            }
+            Err(e) => {
+                eprintln!("Could not load the level {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        },
+    }
+
+    // Spectators, replays, demos and bots don't play, and whoever joins a game plays on the
+    // host's board, so they have nothing to put on the leaderboard.
+    let mut profile = match (&frames, &replay, &editor) {
+        (None, None, None) if options.bot.is_none() && client.is_none() => Profile::load(&options),
+        _ => Profile::default(),
+    };
+
+    let resumed = match (&replay, &options.load) {
+        (Some(replay), _) => match replay.controller() {
+            Ok(start) => Some(start),
+            Err(e) => {
+                eprintln!("Could not start the replay: {}", e);
+                std::process::exit(2);
+            }
+        },
+        (None, Some(path)) => match save::load(path) {
+            Ok(mut loaded) => {
+                // A resumed run waits for the first key, like a new one.
+                loaded.snake.current_direction = Direction::Stop;
+                loaded.keys = options.keys.clone();
+                Some(loaded)
+            }
+            Err(e) => {
+                eprintln!("Could not load {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        },
+        (None, None) => None,
+    };
+
+    // Everything the game writes to the terminal goes through here, recorded or not.
+    let mut stdout = LocalTerminal(match &options.cast {
+        Some(path) => Box::new(CastWriter::new(
+            stdout(),
+            BufWriter::new(File::create(path)?),
+            terminal::size()?,
+            "rusty-snake",
+        )?),
+        None => Box::new(stdout()),
+    });
+
+    terminal::enable_raw_mode()?;
+    stdout
+        .execute(terminal::EnterAlternateScreen)?
+        .execute(cursor::Hide)?;
+
+    if options.mouse {
+        stdout.execute(event::EnableMouseCapture)?;
+    }
+
+    let (event_tx, event_rx) = mpsc::channel();
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let input_thread = spawn_input_thread(event_tx, stop_rx);
+
+    let mut game_controller = match resumed {
+        Some(resumed) => resumed,
+        None => {
+            let mut controller = new_controller_or_exit(&options);
+
+            // New games start from the menu, where their options can still be changed.
+            if frames.is_none() && client.is_none() && !options.no_menu && !options.demo {
+                controller.screen = GameScreen::Menu;
+            }
+            controller
+        }
+    };
+
+    // Whatever happens in the loop, the terminal has to be restored afterwards.
+    let result = match (frames, &replay, &mut editor) {
+        // Editing plays nothing, so there is nothing to report afterwards either.
+        (_, _, Some((editor, path))) => {
+            run_editor(&mut stdout, &event_rx, editor, path, &options.render).map(|()| false)
+        }
+        (Some(frames), _, None) => run_spectator(
+            &mut stdout,
+            &mut game_controller,
+            &event_rx,
+            frames,
+            &options.render,
+        )
+        .map(|()| true),
+        (None, Some(replay), None) => run_replay(
+            &mut stdout,
+            &mut game_controller,
+            &event_rx,
+            replay,
+            &options.render,
+        )
+        .map(|()| true),
+        (None, None, None) => match client {
+            Some(client) => run_client(
+                &mut stdout,
+                &mut game_controller,
+                &event_rx,
+                client,
+                &options.render,
+            )
+            .map(|()| true),
+            None => run_game(
+                &mut stdout,
+                &mut game_controller,
+                &event_rx,
+                broadcaster,
+                host,
+                &mut profile,
+                &mut options,
+            ),
+        },
+    };
+    let played = result.as_ref().is_ok_and(|played| *played);
+    let result = result.map(|_| ());
+
+    // The input thread has to be gone before leaving raw mode, otherwise it would swallow the
+    // first key typed into the shell.
+    drop(stop_tx);
+    let input_result = input_thread.join().unwrap_or(Ok(()));
+
+    if options.mouse {
+        stdout.execute(event::DisableMouseCapture)?;
+    }
+
+    stdout
+        .execute(terminal::LeaveAlternateScreen)?
+        .execute(cursor::Show)?;
+    terminal::disable_raw_mode()?;
+
+    // Quitting from the menu leaves nothing to report.
+    if !played {
+        return result.and(input_result);
+    }
+
+    // Completes the recording before anything is printed below the game.
+    drop(stdout);
+
+    // Written only now that the alternate screen is gone, so that errors end up readable.
+    if let Some(path) = &options.save {
+        if game_controller.stats.death.is_some() || game_controller.won {
+            println!("The run is over, so it wasn't saved to {}.", path.display());
+        } else {
+            match save::save(&game_controller, path) {
+                Ok(()) => println!("Run saved to {}", path.display()),
+                Err(e) => eprintln!("Could not save the run to {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    if let Some(path) = &options.record {
+        match profile.replay.save(path) {
+            Ok(()) => println!("Replay saved to {}", path.display()),
+            Err(e) => eprintln!("Could not save the replay to {}: {}", path.display(), e),
+        }
+    }
+
+    if let Some(path) = snapshot::snapshot_path(options.snapshot.as_deref(), &game_controller) {
+        match save_snapshot(&path, &game_controller, options.force) {
+            Ok(message) => println!("{}", message),
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+
+    if let Some(e) = &profile.leaderboard_error {
+        eprintln!("{}", e);
+    }
+
+    if let Some((_, leaderboard)) = &profile.leaderboard {
+        print!("{}", leaderboard::leaderboard_table(leaderboard));
+    }
+
+    if let Err(message) = keep_best_ghost(&mut profile, &options) {
+        eprintln!("{}", message);
+    }
+
+    result.and(input_result)
+}
+
+// Sizes the board for --fit to a terminal of `size`, making sure the game still fits on it.
+fn fit_board(options: &mut Options, size: (u16, u16)) -> Result<(), String> {
+    options.config.bounds = CanvasBounds::fit_terminal(size);
+
+    if let StartPosition::At(head) = &options.start {
+        if !options.config.bounds.contains(head) {
+            return Err(format!(
+                "Start {},{} is outside the {}x{} board that fits the terminal.",
+                head.0 .0,
+                head.0 .1,
+                options.config.bounds.width(),
+                options.config.bounds.height()
+            ));
+        }
+    }
+
+    validate_game_config(&options.config)
+}
+
+fn new_controller(options: &Options) -> Result<Controller, String> {
+    let mut controller = Controller::new();
+    // Seeded first, so that a random start is reproducible too.
+    if let Some(seed) = options.seed {
+        controller.rng = GameRng::seed_from_u64(seed);
+    }
+    controller.bounds = options.config.bounds;
+    controller.ticks_per_sec = options.config.ticks_per_sec;
+    controller.ramp = options.ramp;
+    controller.keys = options.keys.clone();
+    controller.follow_cursor = options.mouse_follow;
+    controller.obstacles = options.config.obstacles.clone();
+    controller.no_apples = options.config.no_apples.clone();
+    controller.obstacles_every = options.obstacles_every;
+    controller.game_mode = options.game_mode;
+    controller.max_length = options.max_length;
+    controller.wrap = options.wrap;
+    controller.demo = options.demo;
+    controller.pilot = options.bot.clone();
+    controller.apple_count = options.apple_count;
+
+    place_snake(&mut controller, &options.start, options.config.start_length)?;
+
+    // Placed after the snake so that they never cover it.
+    if let Some(layout) = options.config.layout {
+        board::place_layout(&mut controller, layout);
+    }
+    if options.two_players {
+        place_second_player(&mut controller, options.config.start_length)?;
+        if let Some(second) = &mut controller.second {
+            second.ai = options.ai;
+            second.remote = matches!(options.mode, Mode::Host(_) | Mode::Server(_));
+        }
+    }
+    if options.portals {
+        place_portals(&mut controller);
+    }
+
+    Ok(controller)
+}
+
+// The same as `new_controller`, for the command line, where a game that can't be set up ends the
+// program.
+fn new_controller_or_exit(options: &Options) -> Controller {
+    new_controller(options).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    })
+}
+
+// The same as `new_controller`, for a game that is already running in the terminal, which has to
+// be restored before the message can be read.
+fn renew_controller(options: &Options) -> io::Result<Controller> {
+    new_controller(options).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+// The finished run as the online leaderboard gets it. Runs that didn't make the local
+// leaderboard have no name yet, so they go by the login name.
+fn submission(profile: &Profile, controller: &Controller) -> Submission {
+    let name = match &controller.name_prompt {
+        Some(prompt) if prompt.state == PromptState::Submitted => prompt.name(),
+        _ => std::env::var("USER").unwrap_or_else(|_| String::from("anonymous")),
+    };
+
+    Submission {
+        entry: Entry {
+            name,
+            score: controller.score,
+            date: snapshot::timestamp(),
+            board: Some((controller.bounds.width(), controller.bounds.height())),
+            completed: controller.won,
+        },
+        seed: controller.rng.seed(),
+        replay: profile.replay.digest(),
+    }
+}
+
+// --snapshot saves the final board of every run, `s` on the end screen only the current one.
+// Wraps up a round before the next one starts: its ghost and snapshot are saved right away
+// instead of on exit. Returns how that went, for the next round to show.
+fn finish_run(profile: &mut Profile, options: &Options, controller: &Controller) -> Option<String> {
+    let snapshot = snapshot::snapshot_path(options.snapshot.as_deref(), controller)
+        .map(|path| save_snapshot(&path, controller, options.force).unwrap_or_else(|e| e));
+
+    keep_best_ghost(profile, options).err().or(snapshot)
+}
+
+// Makes this run's recording the ghost if it beat the old one, both on disk and for the next
+// round. The recording of a resumed run lacks its beginning, so it can't become the ghost.
+fn keep_best_ghost(profile: &mut Profile, options: &Options) -> Result<(), String> {
+    if let (Some((path, best)), None) = (&mut profile.ghost, &options.load) {
+        let best_score = best.as_ref().map_or(0, Ghost::score);
+
+        if profile.recording.score() > best_score {
+            profile
+                .recording
+                .save(path)
+                .map_err(|e| format!("Could not save the ghost to {}: {}", path.display(), e))?;
+            *best = Some(profile.recording.clone());
+        }
+    }
+
+    Ok(())
+}
+
+fn save_snapshot(path: &Path, controller: &Controller, force: bool) -> Result<String, String> {
+    let text = snapshot::snapshot_text(controller, Some(controller.rng.seed()), &snapshot::today());
+
+    match snapshot::write_snapshot(path, &text, force) {
+        Ok(()) => Ok(format!("Snapshot saved to {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Err(format!(
+            "{} already exists, pass --force to overwrite it.",
+            path.display()
+        )),
+        Err(e) => Err(format!(
+            "Could not save snapshot to {}: {}",
+            path.display(),
+            e
+        )),
+    }
 }
@@ -0,0 +1,81 @@
+// The menus shown before a game: a vertical list of entries, moved through with the arrow keys.
+// What the entries do is up to the frontend.
+
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MainMenuItem {
+    Play,
+    Options,
+    HighScores,
+    Quit,
+}
+
+pub const MAIN_MENU: [MainMenuItem; 4] = [
+    MainMenuItem::Play,
+    MainMenuItem::Options,
+    MainMenuItem::HighScores,
+    MainMenuItem::Quit,
+];
+
+impl MainMenuItem {
+    pub fn label(self) -> &'static str {
+        match self {
+            MainMenuItem::Play => "Play",
+            MainMenuItem::Options => "Options",
+            MainMenuItem::HighScores => "High Scores",
+            MainMenuItem::Quit => "Quit",
+        }
+    }
+}
+
+/// What a key press on a menu asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuInput<T> {
+    // Enter on an entry.
+    Choose(T),
+    // Left or right on an entry, as -1 or 1, for changing the value an entry shows.
+    Adjust(T, i8),
+    // Esc, leaving the menu.
+    Back,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu<T> {
+    pub items: Vec<T>,
+    pub selected: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    // A menu needs at least one entry.
+    pub fn new(items: &[T]) -> Self {
+        Menu {
+            items: items.to_vec(),
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> T {
+        self.items[self.selected]
+    }
+
+    /// Up and down move the selection, wrapping around at either end. Every other key that means
+    /// something is handed back to the caller.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<MenuInput<T>> {
+        match code {
+            KeyCode::Up => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(self.items.len() - 1);
+                None
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % self.items.len();
+                None
+            }
+            KeyCode::Left => Some(MenuInput::Adjust(self.selected(), -1)),
+            KeyCode::Right => Some(MenuInput::Adjust(self.selected(), 1)),
+            KeyCode::Enter => Some(MenuInput::Choose(self.selected())),
+            KeyCode::Esc => Some(MenuInput::Back),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,706 @@
+// Two players on one board over TCP, for --host and --join. The host runs the game and is the
+// only one that does: the joined player sends the turns they ask for and gets the board back
+// after every tick, mostly as the difference to the one before.
+//
+// Nothing is predicted on the joining side, so a turn shows up there one round trip after the
+// key press. The host applies the latest turn that arrived on its next tick, the same way it
+// applies the keys of a local second player.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::achievements::Death;
+use crate::broadcast::{parse_bounds, parse_cell, parse_cells};
+use crate::{
+    Apple, AppleType, CanvasBounds, CanvasSpace, Controller, Direction, GameScreen, Player,
+};
+
+// In the order they are numbered in on the wire.
+const DEATHS: [Death; 7] = [
+    Death::Wall,
+    Death::SelfCollision,
+    Death::Obstacle,
+    Death::Starved,
+    Death::Poisoned,
+    Death::Crushed,
+    Death::Rival,
+];
+
+/// Everything the joined player needs to draw the board. Index 0 is the host's snake.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    pub tick: u64,
+    pub bounds: CanvasBounds,
+    pub game_over: bool,
+    pub scores: [u32; 2],
+    pub deaths: [Option<Death>; 2],
+    pub snakes: [Vec<CanvasSpace>; 2],
+    pub apples: Vec<(CanvasSpace, char)>,
+    pub obstacles: Vec<CanvasSpace>,
+}
+
+/// How one snake changed in a tick: the cell it moved its head onto, if it moved, and its length
+/// afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub head: Option<CanvasSpace>,
+    pub length: usize,
+}
+
+/// What changed since the last board was sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub tick: u64,
+    pub game_over: bool,
+    pub scores: [u32; 2],
+    pub deaths: [Option<Death>; 2],
+    pub moves: [Move; 2],
+    // Left out if they are the same as before.
+    pub apples: Option<Vec<(CanvasSpace, char)>>,
+}
+
+/// One line from the host.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Update {
+    Full(Board),
+    Step(Step),
+}
+
+impl Board {
+    pub fn capture(tick: u64, controller: &Controller) -> Self {
+        let (second_score, second_death, second_snake) = match &controller.second {
+            Some(second) => (
+                second.score,
+                second.death,
+                second.snake.elements.iter().cloned().collect(),
+            ),
+            None => (0, None, Vec::new()),
+        };
+
+        Board {
+            tick,
+            bounds: controller.bounds,
+            game_over: controller.screen == GameScreen::GameOver,
+            scores: [controller.score, second_score],
+            deaths: [controller.stats.death, second_death],
+            snakes: [
+                controller.snake.elements.iter().cloned().collect(),
+                second_snake,
+            ],
+            apples: controller
+                .apples
+                .iter()
+                .map(|apple| (apple.position.clone(), apple.apple_type.glyph()))
+                .collect(),
+            obstacles: controller.obstacles.clone(),
+        }
+    }
+
+    /// What to send to get from `self` to `next`. Snakes that did more than move on by a cell,
+    /// like after a restart, and a changed board or walls make it the whole board.
+    pub fn diff(&self, next: &Board) -> Update {
+        let moves = [
+            snake_move(&self.snakes[0], &next.snakes[0]),
+            snake_move(&self.snakes[1], &next.snakes[1]),
+        ];
+
+        match moves {
+            [Some(first), Some(second)]
+                if self.bounds == next.bounds && self.obstacles == next.obstacles =>
+            {
+                Update::Step(Step {
+                    tick: next.tick,
+                    game_over: next.game_over,
+                    scores: next.scores,
+                    deaths: next.deaths,
+                    moves: [first, second],
+                    apples: Some(next.apples.clone()).filter(|apples| *apples != self.apples),
+                })
+            }
+            _ => Update::Full(next.clone()),
+        }
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        self.tick = step.tick;
+        self.game_over = step.game_over;
+        self.scores = step.scores;
+        self.deaths = step.deaths;
+
+        for (snake, step) in self.snakes.iter_mut().zip(&step.moves) {
+            if let Some(head) = &step.head {
+                snake.insert(0, head.clone());
+            }
+            snake.truncate(step.length);
+        }
+
+        if let Some(apples) = &step.apples {
+            self.apples = apples.clone();
+        }
+    }
+
+    /// The board as the player of the first snake sees it, as the second snake. Players who
+    /// joined always play the second one.
+    pub fn swapped(mut self) -> Self {
+        self.scores.swap(0, 1);
+        self.deaths.swap(0, 1);
+        self.snakes.swap(0, 1);
+        self
+    }
+
+    /// Shows the board on `controller`, with the joined player's snake as the second one.
+    pub fn show(&self, controller: &mut Controller) {
+        // Quitting is up to the joined player, like it is for spectators.
+        if controller.screen != GameScreen::Closed {
+            controller.screen = if self.game_over {
+                GameScreen::GameOver
+            } else {
+                GameScreen::Playing
+            };
+        }
+
+        controller.bounds = self.bounds;
+        controller.obstacles = self.obstacles.clone();
+        controller.score = self.scores[0];
+        controller.stats.death = self.deaths[0];
+        controller.snake.elements = self.snakes[0].iter().cloned().collect();
+
+        let second = controller
+            .second
+            .get_or_insert_with(|| Player::new(Default::default()));
+        second.score = self.scores[1];
+        second.death = self.deaths[1];
+        second.snake.elements = self.snakes[1].iter().cloned().collect();
+
+        controller.apples = self
+            .apples
+            .iter()
+            .map(|(position, glyph)| Apple::new(position.clone(), AppleType::from_glyph(*glyph)))
+            .collect();
+    }
+}
+
+// `next` as a move from `previous`, if it is one.
+fn snake_move(previous: &[CanvasSpace], next: &[CanvasSpace]) -> Option<Move> {
+    if previous.starts_with(next) {
+        return Some(Move {
+            head: None,
+            length: next.len(),
+        });
+    }
+
+    match next.split_first() {
+        Some((head, body)) if previous.starts_with(body) => Some(Move {
+            head: Some(head.clone()),
+            length: next.len(),
+        }),
+        _ => None,
+    }
+}
+
+// Updates travel as one line each, made of space-separated fields. A whole board is
+// `full <tick> <width>,<height>,<inset> <game over> <scores> <deaths> <apples> <walls> <snake>
+// <snake>` and a step is `step <tick> <game over> <scores> <deaths> <move> <move> <apples>`.
+// Lists of cells are `;`-separated `x,y` pairs and apples carry their glyph as a third part,
+// with `-` for an empty list and `=` for apples that didn't change. Deaths are numbered after
+// `DEATHS`, a move is `<head>:<length>` with `-` for a head that stayed put.
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Update::Full(board) => write!(
+                f,
+                "full {} {},{},{} {} {} {} {} {} {} {}",
+                board.tick,
+                board.bounds.width(),
+                board.bounds.height(),
+                board.bounds.inset(),
+                board.game_over as u8,
+                scores(&board.scores),
+                deaths(&board.deaths),
+                apples(&board.apples),
+                cells(&board.obstacles),
+                cells(&board.snakes[0]),
+                cells(&board.snakes[1]),
+            ),
+            Update::Step(step) => write!(
+                f,
+                "step {} {} {} {} {} {} {}",
+                step.tick,
+                step.game_over as u8,
+                scores(&step.scores),
+                deaths(&step.deaths),
+                snake_step(&step.moves[0]),
+                snake_step(&step.moves[1]),
+                step.apples
+                    .as_ref()
+                    .map_or(String::from("="), |a| apples(a)),
+            ),
+        }
+    }
+}
+
+fn scores(scores: &[u32; 2]) -> String {
+    format!("{},{}", scores[0], scores[1])
+}
+
+fn deaths(deaths: &[Option<Death>; 2]) -> String {
+    let death = |death: Option<Death>| match death.and_then(|d| DEATHS.iter().position(|&x| x == d))
+    {
+        Some(index) => index.to_string(),
+        None => String::from("-"),
+    };
+
+    format!("{},{}", death(deaths[0]), death(deaths[1]))
+}
+
+fn cells(cells: &[CanvasSpace]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| format!("{},{}", cell.0 .0, cell.0 .1))
+        .collect();
+
+    list(cells)
+}
+
+fn apples(apples: &[(CanvasSpace, char)]) -> String {
+    let apples: Vec<String> = apples
+        .iter()
+        .map(|(cell, glyph)| format!("{},{},{}", cell.0 .0, cell.0 .1, glyph))
+        .collect();
+
+    list(apples)
+}
+
+fn snake_step(step: &Move) -> String {
+    match &step.head {
+        Some(head) => format!("{},{}:{}", head.0 .0, head.0 .1, step.length),
+        None => format!("-:{}", step.length),
+    }
+}
+
+fn list(items: Vec<String>) -> String {
+    if items.is_empty() {
+        String::from("-")
+    } else {
+        items.join(";")
+    }
+}
+
+impl FromStr for Update {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.trim_end().split(' ').collect();
+
+        match fields.as_slice() {
+            ["full", tick, bounds, game_over, scores, deaths, apples, walls, first, second] => {
+                Ok(Update::Full(Board {
+                    tick: parse_tick(tick)?,
+                    bounds: parse_bounds(bounds)?,
+                    game_over: *game_over == "1",
+                    scores: parse_scores(scores)?,
+                    deaths: parse_deaths(deaths)?,
+                    apples: parse_apples(apples)?,
+                    obstacles: parse_cells(walls)?,
+                    snakes: [parse_cells(first)?, parse_cells(second)?],
+                }))
+            }
+            ["step", tick, game_over, scores, deaths, first, second, apples] => {
+                Ok(Update::Step(Step {
+                    tick: parse_tick(tick)?,
+                    game_over: *game_over == "1",
+                    scores: parse_scores(scores)?,
+                    deaths: parse_deaths(deaths)?,
+                    moves: [parse_move(first)?, parse_move(second)?],
+                    apples: match *apples {
+                        "=" => None,
+                        apples => Some(parse_apples(apples)?),
+                    },
+                }))
+            }
+            _ => Err(format!("Unknown update `{}`.", line.trim_end())),
+        }
+    }
+}
+
+fn parse_tick(tick: &str) -> Result<u64, String> {
+    tick.parse().map_err(|e| format!("Invalid tick: {}", e))
+}
+
+fn parse_scores(scores: &str) -> Result<[u32; 2], String> {
+    match scores.split_once(',') {
+        Some((first, second)) => Ok([
+            first.parse().map_err(|e| format!("Invalid score: {}", e))?,
+            second
+                .parse()
+                .map_err(|e| format!("Invalid score: {}", e))?,
+        ]),
+        None => Err(format!("Invalid scores `{}`.", scores)),
+    }
+}
+
+fn parse_deaths(deaths: &str) -> Result<[Option<Death>; 2], String> {
+    let death = |death: &str| match death {
+        "-" => Ok(None),
+        index => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| DEATHS.get(index).copied())
+            .map(Some)
+            .ok_or(format!("Invalid death `{}`.", index)),
+    };
+
+    match deaths.split_once(',') {
+        Some((first, second)) => Ok([death(first)?, death(second)?]),
+        None => Err(format!("Invalid deaths `{}`.", deaths)),
+    }
+}
+
+fn parse_apples(apples: &str) -> Result<Vec<(CanvasSpace, char)>, String> {
+    if apples == "-" {
+        return Ok(Vec::new());
+    }
+
+    apples
+        .split(';')
+        .map(|apple| {
+            let parts: Vec<&str> = apple.splitn(3, ',').collect();
+            let glyph = parts
+                .get(2)
+                .and_then(|part| part.chars().next())
+                .ok_or("Apple is missing its glyph.")?;
+
+            Ok((parse_cell(&parts[..2].join(","))?, glyph))
+        })
+        .collect()
+}
+
+fn parse_move(step: &str) -> Result<Move, String> {
+    let (head, length) = step
+        .split_once(':')
+        .ok_or(format!("Invalid move `{}`.", step))?;
+
+    Ok(Move {
+        head: match head {
+            "-" => None,
+            head => Some(parse_cell(head)?),
+        },
+        length: length
+            .parse()
+            .map_err(|e| format!("Invalid length: {}", e))?,
+    })
+}
+
+/// How long a new connection has to say whether it plays or watches.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many updates may queue up for a single connection. A host drops what doesn't fit and
+/// sends the whole board next, a client stops reading until it caught up.
+pub const UPDATE_BACKLOG: usize = 4;
+
+/// What a player or spectator sends, one per line. Connections start by picking a room, which a
+/// host doesn't have, so it ignores the name. Players then ask for turns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    Join(String),
+    Watch(String),
+    Turn(Direction),
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Request::Join(room) => write!(f, "join {}", room),
+            Request::Watch(room) => write!(f, "watch {}", room),
+            Request::Turn(direction) => match direction {
+                Direction::Up => write!(f, "turn up"),
+                Direction::Down => write!(f, "turn down"),
+                Direction::Left => write!(f, "turn left"),
+                Direction::Right => write!(f, "turn right"),
+                Direction::Stop => write!(f, "turn stop"),
+            },
+        }
+    }
+}
+
+impl FromStr for Request {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        match line.trim_end().split_once(' ') {
+            Some(("join", room)) if !room.is_empty() => Ok(Request::Join(room.to_string())),
+            Some(("watch", room)) if !room.is_empty() => Ok(Request::Watch(room.to_string())),
+            Some(("turn", "up")) => Ok(Request::Turn(Direction::Up)),
+            Some(("turn", "down")) => Ok(Request::Turn(Direction::Down)),
+            Some(("turn", "left")) => Ok(Request::Turn(Direction::Left)),
+            Some(("turn", "right")) => Ok(Request::Turn(Direction::Right)),
+            _ => Err(format!("Unknown request `{}`.", line.trim_end())),
+        }
+    }
+}
+
+/// A connected player or spectator, as seen from the end that runs the game.
+pub struct Peer {
+    updates: SyncSender<String>,
+    requests: Receiver<Request>,
+    // Cleared once the connection stops delivering requests.
+    connected: Arc<AtomicBool>,
+    sent: Option<Board>,
+}
+
+impl Peer {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        let reader = stream.try_clone()?;
+        let (request_tx, request_rx) = mpsc::channel();
+        let connected = Arc::new(AtomicBool::new(true));
+        let reading = Arc::clone(&connected);
+        let _ = thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let request = match line {
+                    Ok(line) => line.parse::<Request>(),
+                    Err(_) => break,
+                };
+
+                if let Ok(request) = request {
+                    if request_tx.send(request).is_err() {
+                        break;
+                    }
+                }
+            }
+
+            reading.store(false, Ordering::Relaxed);
+        });
+
+        // Writing happens on its own thread so that a slow connection never holds up a tick.
+        // Every update builds on the one before, so once one is dropped, see `send`, the next
+        // one is the whole board again.
+        let (update_tx, update_rx) = mpsc::sync_channel::<String>(UPDATE_BACKLOG);
+        let _ = thread::spawn(move || {
+            let mut stream = stream;
+            for line in update_rx {
+                if stream.write_all(line.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Peer {
+            updates: update_tx,
+            requests: request_rx,
+            connected,
+            sent: None,
+        })
+    }
+
+    /// Waits up to `timeout` for the peer to say something.
+    pub fn request(&self, timeout: Duration) -> Option<Request> {
+        self.requests.recv_timeout(timeout).ok()
+    }
+
+    /// Whether the peer is still connected. Anything it asked for in the meantime is kept.
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// The last turn asked for since the last call, if any.
+    pub fn turn(&self) -> Option<Direction> {
+        self.requests
+            .try_iter()
+            .filter_map(|request| match request {
+                Request::Turn(turn) => Some(turn),
+                Request::Join(_) | Request::Watch(_) => None,
+            })
+            .last()
+    }
+
+    /// Sends `board`, as far as it differs from the last one sent. A peer that is lagging
+    /// behind skips it and gets the whole board with the next one. Returns whether the peer is
+    /// still there.
+    pub fn send(&mut self, board: Board) -> bool {
+        let update = match &self.sent {
+            Some(sent) => sent.diff(&board),
+            None => Update::Full(board.clone()),
+        };
+
+        match self.updates.try_send(format!("{}\n", update)) {
+            Ok(()) => {
+                self.sent = Some(board);
+                true
+            }
+            Err(TrySendError::Full(_)) => {
+                self.sent = None;
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// The hosting end of a game. If the other player leaves, their snake carries on straight.
+/// Spectators can come and go throughout.
+pub struct Host {
+    peer: Peer,
+    spectators: Vec<Peer>,
+    arrivals: Receiver<Peer>,
+}
+
+impl Host {
+    /// Waits for a player to join on `addr`. Anyone who connects to watch is let in as well.
+    pub fn accept(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let mut spectators = Vec::new();
+
+        let peer = loop {
+            let peer = Peer::new(listener.accept()?.0)?;
+
+            match peer.request(REQUEST_TIMEOUT) {
+                Some(Request::Watch(_)) => spectators.push(peer),
+                Some(Request::Join(_)) => break peer,
+                _ => (),
+            }
+        };
+
+        // The game is full from here on, so only spectators get in.
+        let (arrival_tx, arrival_rx) = mpsc::channel();
+        let _ = thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let peer = match Peer::new(stream) {
+                    Ok(peer) => peer,
+                    Err(_) => continue,
+                };
+
+                if let Some(Request::Watch(_)) = peer.request(REQUEST_TIMEOUT) {
+                    if arrival_tx.send(peer).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Host {
+            peer,
+            spectators,
+            arrivals: arrival_rx,
+        })
+    }
+
+    /// Hands the latest turn the other player asked for to the second snake.
+    pub fn receive(&self, controller: &mut Controller) {
+        if let (Some(turn), Some(second)) = (self.peer.turn(), &mut controller.second) {
+            second.turn = Some(turn);
+        }
+    }
+
+    /// Sends the board as it is after `tick` to the other player and everyone watching.
+    pub fn send(&mut self, tick: u64, controller: &Controller) {
+        let board = Board::capture(tick, controller);
+
+        self.spectators.extend(self.arrivals.try_iter());
+        self.spectators
+            .retain_mut(|spectator| spectator.send(board.clone()));
+        self.peer.send(board);
+    }
+}
+
+/// The joining end of a game, or a spectator of it.
+pub struct Client {
+    stream: TcpStream,
+    updates: Receiver<Update>,
+    board: Option<Board>,
+    watching: bool,
+}
+
+impl Client {
+    /// Connects to a host, or to a server and its room called `room`.
+    pub fn join(addr: impl ToSocketAddrs, room: &str) -> io::Result<Self> {
+        Client::connect(addr, Request::Join(room.to_string()))
+    }
+
+    /// Connects like `join`, but only to look on.
+    pub fn watch(addr: impl ToSocketAddrs, room: &str) -> io::Result<Self> {
+        Client::connect(addr, Request::Watch(room.to_string()))
+    }
+
+    fn connect(addr: impl ToSocketAddrs, request: Request) -> io::Result<Self> {
+        let watching = matches!(request, Request::Watch(_));
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        stream.write_all(format!("{}\n", request).as_bytes())?;
+
+        let reader = stream.try_clone()?;
+        let (update_tx, update_rx) = mpsc::sync_channel(UPDATE_BACKLOG);
+        let _ = thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let update = match line {
+                    Ok(line) => line.parse::<Update>(),
+                    Err(_) => break,
+                };
+
+                // A bad line would leave every step after it applied to the wrong board.
+                match update {
+                    Ok(update) => {
+                        if update_tx.send(update).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(Client {
+            stream,
+            updates: update_rx,
+            board: None,
+            watching,
+        })
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    /// Asks the host to turn this player's snake. Spectators have none, so they send nothing.
+    pub fn turn(&mut self, direction: Direction) -> io::Result<()> {
+        match direction {
+            _ if self.watching => Ok(()),
+            Direction::Stop => Ok(()),
+            direction => self
+                .stream
+                .write_all(format!("{}\n", Request::Turn(direction)).as_bytes()),
+        }
+    }
+
+    /// Applies every update that arrived and shows the result on `controller`. Returns the tick
+    /// the board is at, or an error once the host is gone.
+    pub fn receive(&mut self, controller: &mut Controller) -> Result<Option<u64>, String> {
+        loop {
+            match self.updates.try_recv() {
+                Ok(Update::Full(board)) => self.board = Some(board),
+                Ok(Update::Step(step)) => match &mut self.board {
+                    Some(board) => board.apply(&step),
+                    None => return Err(String::from("The host sent a step before the board.")),
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(String::from("The host left.")),
+            }
+        }
+
+        match &self.board {
+            Some(board) => {
+                board.show(controller);
+                Ok(Some(board.tick))
+            }
+            None => Ok(None),
+        }
+    }
+}
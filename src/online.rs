@@ -0,0 +1,203 @@
+// Submits finished runs to the online leaderboard set with `online-leaderboard` in the config
+// and fetches its top ten for the end screen. Scores go to `POST <url>/scores` as one JSON
+// record, like the ones in the local leaderboard plus the seed and a hash of the replay, and
+// `GET <url>/scores` answers with a leaderboard file.
+//
+// Talking to the server needs the `online` feature, which brings a small blocking HTTP/1.0
+// client. Without it, every submission ends in an error saying so, and so does anything going
+// wrong on the way: the game never waits for the network.
+
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::leaderboard::{Entry, Leaderboard};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub host: String,
+    pub port: u16,
+    // Without the trailing slash, empty for the root.
+    pub path: String,
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    /// Parses an `http://host[:port][/path]` URL. There is no TLS, so `https` is turned down.
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let rest = match url.split_once("://") {
+            Some(("http", rest)) => rest,
+            Some((scheme, _)) => {
+                return Err(format!(
+                    "Unsupported scheme `{}` in `{}`, only http:// leaderboards work.",
+                    scheme, url
+                ))
+            }
+            None => return Err(format!("`{}` is not an http:// URL.", url)),
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("Invalid port `{}` in `{}`.", port, url))?,
+            ),
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(format!("`{}` has no host.", url));
+        }
+
+        Ok(Endpoint {
+            host: host.to_string(),
+            port,
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// A finished run, as sent to the online leaderboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submission {
+    pub entry: Entry,
+    pub seed: u64,
+    // See `Replay::digest`.
+    pub replay: u64,
+}
+
+impl Submission {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{{}, \"seed\": {}, \"replay\": \"{:016x}\"}}",
+            self.entry.json_fields(),
+            self.seed,
+            self.replay
+        )
+    }
+}
+
+/// How a submission is going.
+#[derive(Debug)]
+pub enum Status {
+    Pending(Receiver<Result<Leaderboard, String>>),
+    // The global top ten, or why there is none.
+    Done(Result<Leaderboard, String>),
+}
+
+impl Status {
+    /// Looks for the answer without waiting for it.
+    pub fn poll(&mut self) -> Option<&Result<Leaderboard, String>> {
+        if let Status::Pending(answer) = self {
+            match answer.try_recv() {
+                Ok(result) => *self = Status::Done(result),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    *self = Status::Done(Err(String::from("The submission got lost.")))
+                }
+            }
+        }
+
+        match self {
+            Status::Done(result) => Some(result),
+            Status::Pending(_) => None,
+        }
+    }
+}
+
+/// The body of an HTTP `response`, if its status is a success.
+pub fn response_body(response: &str) -> Result<&str, String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("The leaderboard sent an incomplete response.")?;
+    let status = head.lines().next().unwrap_or_default();
+
+    match status.split(' ').nth(1) {
+        Some(code) if code.starts_with('2') => Ok(body),
+        _ => Err(format!("The leaderboard answered `{}`.", status)),
+    }
+}
+
+/// Sends `submission` and fetches the top ten afterwards, on a thread of its own.
+#[cfg(feature = "online")]
+pub fn submit(endpoint: &Endpoint, submission: &Submission) -> Status {
+    let (answer_tx, answer_rx) = std::sync::mpsc::channel();
+    let endpoint = endpoint.clone();
+    let body = submission.to_json();
+
+    let _ = std::thread::spawn(move || {
+        let path = format!("{}/scores", endpoint.path);
+        let result = http::request(&endpoint, "POST", &path, Some(&body))
+            .and_then(|_| http::request(&endpoint, "GET", &path, None))
+            .and_then(|top| Leaderboard::from_json(&top));
+
+        let _ = answer_tx.send(result);
+    });
+
+    Status::Pending(answer_rx)
+}
+
+#[cfg(not(feature = "online"))]
+pub fn submit(_endpoint: &Endpoint, _submission: &Submission) -> Status {
+    Status::Done(Err(String::from(
+        "This build has no online support, see the `online` feature.",
+    )))
+}
+
+#[cfg(feature = "online")]
+mod http {
+    use std::io::{Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::time::Duration;
+
+    use super::{response_body, Endpoint};
+
+    // Long enough for a slow server, short enough that an unreachable one is soon given up on.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // HTTP/1.0 keeps responses unchunked and closes the connection after them, so the body is
+    // simply everything up to the end.
+    pub(super) fn request(
+        endpoint: &Endpoint,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<String, String> {
+        let fail = |e: std::io::Error| format!("Could not reach {}: {}", endpoint.host, e);
+
+        let addr = (endpoint.host.as_str(), endpoint.port)
+            .to_socket_addrs()
+            .map_err(fail)?
+            .next()
+            .ok_or(format!("Could not resolve {}.", endpoint.host))?;
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT).map_err(fail)?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(fail)?;
+        stream.set_write_timeout(Some(TIMEOUT)).map_err(fail)?;
+
+        let mut request = format!(
+            "{} {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: rusty-snake\r\n",
+            method,
+            if path.is_empty() { "/" } else { path },
+            endpoint.host
+        );
+        if let Some(body) = body {
+            request.push_str(&format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                body.len()
+            ));
+        }
+        request.push_str("\r\n");
+        request.push_str(body.unwrap_or_default());
+
+        stream.write_all(request.as_bytes()).map_err(fail)?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).map_err(fail)?;
+
+        response_body(&response).map(String::from)
+    }
+}
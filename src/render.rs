@@ -0,0 +1,441 @@
+// What the game draws with, apart from where it ends up. Everything on screen is either a cell
+// of the board or a line of text at a screen position, and a `Renderer` puts those somewhere:
+// the terminal through crossterm, or any other frontend that implements it.
+//
+// Drawing goes into a `Frame` first, on one of a few layers, so that the HUD ends up over the
+// board whatever order things are drawn in. Presenting a frame puts its layers onto a grid,
+// bottom first. The terminal renderer keeps a `Screen` of what it last printed and only prints
+// what changed, which keeps slow terminals and remote sessions from flickering.
+
+use crossterm::style::ContentStyle;
+
+use crate::glyphs::{self, char_width, display_width};
+use crate::CanvasSpace;
+
+pub trait Renderer {
+    /// Blanks the whole screen.
+    fn clear(&mut self) -> crossterm::Result<()>;
+
+    /// Makes everything drawn from now on go into `layer`. Frames start on `Layer::Board`.
+    fn set_layer(&mut self, layer: Layer);
+
+    /// Draws `glyph` into a cell of the board, which is two columns wide.
+    fn draw_cell(
+        &mut self,
+        cell: &CanvasSpace,
+        glyph: &str,
+        style: ContentStyle,
+    ) -> crossterm::Result<()>;
+
+    /// Draws `text` starting at column `x` of row `y`.
+    fn draw_text(
+        &mut self,
+        x: u16,
+        y: u16,
+        text: &str,
+        style: ContentStyle,
+    ) -> crossterm::Result<()>;
+
+    /// Shows everything drawn since the last call.
+    fn present(&mut self) -> crossterm::Result<()>;
+}
+
+/// What a frame is made of, from the bottom up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    // Borders, the background grid and obstacles.
+    #[default]
+    Board,
+    // Snakes, apples and everything else that moves.
+    Entities,
+    // Scores, messages and overlays.
+    Hud,
+}
+
+/// One column of the screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    // A character and whatever zero-width ones follow it. Empty for the right half of a wide
+    // character, which the cell before it holds.
+    pub symbol: String,
+    pub style: ContentStyle,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Cell {
+            symbol: String::from(" "),
+            style: ContentStyle::new(),
+        }
+    }
+
+    fn is_continuation(&self) -> bool {
+        self.symbol.is_empty()
+    }
+}
+
+/// The styled characters of a whole screen, the way a terminal keeps them: text stays until
+/// something is drawn over it or the grid is cleared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: u16, height: u16) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![Cell::blank(); usize::from(width) * usize::from(height)],
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.symbol.clear();
+            cell.symbol.push(' ');
+            cell.style = ContentStyle::new();
+        }
+    }
+
+    pub fn row(&self, y: u16) -> &[Cell] {
+        let start = usize::from(y) * usize::from(self.width);
+        &self.cells[start..start + usize::from(self.width)]
+    }
+
+    /// Writes `text` from column `x` of row `y` on, cutting it off at the right edge.
+    pub fn put_text(&mut self, x: u16, y: u16, text: &str, style: ContentStyle) {
+        if y >= self.height {
+            return;
+        }
+
+        let mut x = usize::from(x);
+        let width = usize::from(self.width);
+        let row = usize::from(y) * width;
+        // Where the last character went, for zero-width ones to join it.
+        let mut last = None;
+
+        for c in text.chars() {
+            let columns = glyphs::char_width(c);
+
+            if columns == 0 {
+                if let Some(index) = last {
+                    let cell: &mut Cell = &mut self.cells[index];
+                    cell.symbol.push(c);
+                }
+                continue;
+            }
+            if x + columns > width {
+                break;
+            }
+
+            // A wide character that is partly drawn over loses its other half.
+            if self.cells[row + x].is_continuation() && x > 0 {
+                self.set(row + x - 1, " ", style);
+            }
+            let after = x + columns;
+            if after < width && self.cells[row + after].is_continuation() {
+                self.set(row + after, " ", style);
+            }
+
+            let mut symbol = [0; 4];
+            self.set(row + x, c.encode_utf8(&mut symbol), style);
+            if columns == 2 {
+                self.set(row + x + 1, "", style);
+            }
+
+            last = Some(row + x);
+            x = after;
+        }
+    }
+
+    fn set(&mut self, index: usize, symbol: &str, style: ContentStyle) {
+        let cell = &mut self.cells[index];
+        cell.symbol.clear();
+        cell.symbol.push_str(symbol);
+        cell.style = style;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Stroke {
+    layer: Layer,
+    x: u16,
+    y: u16,
+    text: String,
+    style: ContentStyle,
+}
+
+/// What is drawn during one frame, kept by layer until it is put onto a grid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frame {
+    // Whether the grid is blanked before anything goes onto it.
+    cleared: bool,
+    layer: Layer,
+    strokes: Vec<Stroke>,
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Throws away what was drawn so far, and blanks the grid the frame goes onto.
+    pub fn clear(&mut self) {
+        self.cleared = true;
+        self.strokes.clear();
+    }
+
+    pub fn set_layer(&mut self, layer: Layer) {
+        self.layer = layer;
+    }
+
+    pub fn draw_text(&mut self, x: u16, y: u16, text: &str, style: ContentStyle) {
+        self.strokes.push(Stroke {
+            layer: self.layer,
+            x,
+            y,
+            text: text.to_string(),
+            style,
+        });
+    }
+
+    /// Draws the frame onto `grid`, one layer after the other and in drawing order within a
+    /// layer. Unless the frame was cleared, what the grid already holds stays where nothing is
+    /// drawn over it.
+    pub fn compose(&self, grid: &mut Grid) {
+        if self.cleared {
+            grid.clear();
+        }
+
+        let mut strokes: Vec<&Stroke> = self.strokes.iter().collect();
+        strokes.sort_by_key(|stroke| stroke.layer);
+        for stroke in strokes {
+            grid.put_text(stroke.x, stroke.y, &stroke.text, stroke.style);
+        }
+    }
+}
+
+/// Columns `start..end` of row `y`, which have to be printed again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub y: u16,
+    pub start: u16,
+    pub end: u16,
+}
+
+/// Where `next` differs from `shown`, one span per row from the first change to the last. Spans
+/// never start or end in the middle of a wide character. Both grids have to be the same size.
+pub fn changed_spans(shown: &Grid, next: &Grid) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    for y in 0..next.height {
+        let (before, after) = (shown.row(y), next.row(y));
+        let differs = |x: &usize| before[*x] != after[*x];
+
+        let (mut start, mut end) = match (
+            (0..after.len()).find(differs),
+            (0..after.len()).rev().find(differs),
+        ) {
+            (Some(first), Some(last)) => (first, last + 1),
+            _ => continue,
+        };
+
+        while start > 0 && after[start].is_continuation() {
+            start -= 1;
+        }
+        while end < after.len() && after[end].is_continuation() {
+            end += 1;
+        }
+
+        spans.push(Span {
+            y,
+            start: start as u16,
+            end: end as u16,
+        });
+    }
+
+    spans
+}
+
+/// What the screen shows and what the next frame will show, so that presenting a frame only
+/// has to print what changed. A backend draws into `next`, prints `changes` and then calls
+/// `presented`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    // `None` when the screen has to be painted from scratch.
+    shown: Option<Grid>,
+    next: Grid,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Screen {
+            shown: None,
+            next: Grid::new(0, 0),
+        }
+    }
+
+    /// Starts over at a new size, if the screen isn't that size already.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        if self.next.size() != (width, height) {
+            self.next = Grid::new(width, height);
+            self.shown = None;
+        }
+    }
+
+    pub fn next(&self) -> &Grid {
+        &self.next
+    }
+
+    pub fn next_mut(&mut self) -> &mut Grid {
+        &mut self.next
+    }
+
+    /// Makes the next frame paint everything, for when the screen may have been messed up
+    /// behind the game's back.
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    /// The spans to print for the next frame, and whether the screen has to be cleared first.
+    pub fn changes(&self) -> (bool, Vec<Span>) {
+        match &self.shown {
+            Some(shown) => (false, changed_spans(shown, &self.next)),
+            None => {
+                let (width, height) = self.next.size();
+                (true, changed_spans(&Grid::new(width, height), &self.next))
+            }
+        }
+    }
+
+    pub fn presented(&mut self) {
+        match &mut self.shown {
+            Some(shown) => shown.clone_from(&self.next),
+            None => self.shown = Some(self.next.clone()),
+        }
+    }
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which part of a status row a field goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A piece of text for a status row, see `layout_status_row`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusField {
+    pub text: String,
+    pub style: ContentStyle,
+    pub align: Align,
+    // Lower is more important.
+    pub priority: u8,
+    // Whether a field that doesn't fit whole is cut off instead of left out.
+    pub cut_off: bool,
+}
+
+/// Columns kept free between two fields of a status row.
+pub const STATUS_GAP: usize = 2;
+
+/// Places `fields` on a row that is `width` columns wide, without any of them overlapping. Left
+/// fields are packed from the left edge and right ones from the right edge, both in the order
+/// given, and centered ones sit in the middle of what is left. If they don't all fit, the most
+/// important fields go first and those that don't fit whole are left out, or cut off to the
+/// space that is left if they allow it. Returns the fields that made it with their column, from
+/// left to right.
+pub fn layout_status_row(fields: Vec<StatusField>, width: u16) -> Vec<(u16, StatusField)> {
+    let width = usize::from(width);
+
+    let mut by_priority: Vec<usize> = (0..fields.len()).collect();
+    by_priority.sort_by_key(|&index| fields[index].priority);
+
+    let mut kept = vec![false; fields.len()];
+    let mut fields = fields;
+    let mut used = 0;
+
+    for index in by_priority {
+        let gap = if used > 0 { STATUS_GAP } else { 0 };
+        let field = &mut fields[index];
+        let text = cut_off(&field.text, width.saturating_sub(used + gap));
+
+        if text.is_empty() || (text != field.text && !field.cut_off) {
+            continue;
+        }
+
+        used += gap + display_width(&text);
+        field.text = text;
+        kept[index] = true;
+    }
+
+    let fields: Vec<StatusField> = fields
+        .into_iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(field, _)| field)
+        .collect();
+    let aligned = |align: Align| fields.iter().filter(move |field| field.align == align);
+    let group_width = |align: Align| -> usize {
+        aligned(align)
+            .map(|field| display_width(&field.text) + STATUS_GAP)
+            .sum()
+    };
+
+    let mut placed = Vec::new();
+
+    let mut x = 0;
+    for field in aligned(Align::Left) {
+        placed.push((x, field.clone()));
+        x += display_width(&field.text) + STATUS_GAP;
+    }
+    let left_end = x;
+
+    // What is left between the left and right fields, with a gap on either side.
+    let right_start = width.saturating_sub(group_width(Align::Right));
+    let center_width = group_width(Align::Center).saturating_sub(STATUS_GAP);
+    let mut x = (width.saturating_sub(center_width) / 2)
+        .min(right_start.saturating_sub(center_width))
+        .max(left_end);
+    for field in aligned(Align::Center) {
+        placed.push((x, field.clone()));
+        x += display_width(&field.text) + STATUS_GAP;
+    }
+
+    let mut x = right_start + STATUS_GAP;
+    for field in aligned(Align::Right) {
+        placed.push((x, field.clone()));
+        x += display_width(&field.text) + STATUS_GAP;
+    }
+
+    placed.sort_by_key(|(x, _)| *x);
+    placed
+        .into_iter()
+        .map(|(x, field)| (x as u16, field))
+        .collect()
+}
+
+// The longest start of `text` that fits into `columns`.
+fn cut_off(text: &str, columns: usize) -> String {
+    let mut width = 0;
+
+    text.chars()
+        .take_while(|&c| {
+            width += char_width(c);
+            width <= columns
+        })
+        .collect()
+}
@@ -0,0 +1,240 @@
+// Replays for --record and --replay. A run is fully determined by where it started, including
+// the seed of its random numbers, and by the input of every tick, so that is all a replay
+// stores: the starting state as a save state, followed by the ticks' input.
+//
+// The input is run-length encoded as one letter per tick: `U`, `D`, `L` and `R` for a turn, `.`
+// for none, in lower case (or `*` instead of `.`) when the player sprinted on that tick.
+// `12R` stands for twelve ticks in a row asking to go right.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crossterm::event;
+
+use crate::keys::Action;
+use crate::{continue_game_logic, direction_key, save, Controller, Direction, SPRINT_TICKS};
+
+const HEADER: &str = "rusty-snake replay 1";
+
+/// What the player did on one tick of the game logic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Input {
+    pub turn: Option<Direction>,
+    pub sprint: bool,
+}
+
+impl Input {
+    // Read off the controller right before its tick runs, when it holds the key the tick acts on.
+    fn capture(controller: &Controller) -> Self {
+        let action = match controller.last_event {
+            Some(event::Event::Key(key)) => controller.keys.playing_action(key.code),
+            _ => None,
+        };
+
+        Input {
+            turn: match action {
+                Some(Action::Up) => Some(Direction::Up),
+                Some(Action::Down) => Some(Direction::Down),
+                Some(Action::Left) => Some(Direction::Left),
+                Some(Action::Right) => Some(Direction::Right),
+                _ => None,
+            },
+            // The sprint counter is only full on the tick the key was pressed.
+            sprint: controller.sprint_ticks == SPRINT_TICKS,
+        }
+    }
+
+    fn letter(self) -> char {
+        let letter = match self.turn {
+            Some(Direction::Up) => 'U',
+            Some(Direction::Down) => 'D',
+            Some(Direction::Left) => 'L',
+            Some(Direction::Right) => 'R',
+            Some(Direction::Stop) | None => '.',
+        };
+
+        match (letter, self.sprint) {
+            ('.', true) => '*',
+            (letter, true) => letter.to_ascii_lowercase(),
+            (letter, false) => letter,
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        let turn = match letter.to_ascii_uppercase() {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            '.' | '*' => None,
+            _ => return None,
+        };
+
+        Some(Input {
+            turn,
+            sprint: letter == '*' || letter.is_ascii_lowercase(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    // The save state the run started from, empty until the first tick is recorded.
+    pub start: String,
+    pub inputs: Vec<Input>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        text.parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_string())
+    }
+
+    /// Records the input of the tick that `controller` is about to run. Has to be called right
+    /// before `continue_game_logic`, and the first call also records the starting state.
+    pub fn record(&mut self, controller: &Controller) {
+        if self.inputs.is_empty() {
+            self.start = save::to_json(controller);
+        }
+
+        self.inputs.push(Input::capture(controller));
+    }
+
+    /// A 64-bit FNV-1a hash of the replay file, which identifies a run without sending all of it.
+    pub fn digest(&self) -> u64 {
+        self.to_string()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// A fresh controller in the replay's starting state.
+    pub fn controller(&self) -> Result<Controller, String> {
+        save::from_json(&self.start)
+    }
+}
+
+impl std::fmt::Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut inputs = String::new();
+        let mut runs = self.inputs.iter().map(|input| input.letter()).peekable();
+
+        while let Some(letter) = runs.next() {
+            let mut count = 1;
+            while runs.peek() == Some(&letter) {
+                runs.next();
+                count += 1;
+            }
+
+            if count > 1 {
+                inputs.push_str(&count.to_string());
+            }
+            inputs.push(letter);
+        }
+
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "{}", inputs)?;
+        write!(f, "{}", self.start)
+    }
+}
+
+impl std::str::FromStr for Replay {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.splitn(3, '\n');
+
+        if parts.next().map(str::trim_end) != Some(HEADER) {
+            return Err(String::from("Not a rusty-snake replay."));
+        }
+
+        let encoded = parts.next().unwrap_or_default().trim_end();
+        let start = String::from(parts.next().unwrap_or_default());
+
+        let mut inputs = Vec::new();
+        let mut count = String::new();
+
+        for c in encoded.chars() {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+
+            let input =
+                Input::from_letter(c).ok_or(format!("Unknown input `{}` in the replay.", c))?;
+            let times = match count.as_str() {
+                "" => 1,
+                count => count
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid count `{}` in the replay.", count))?,
+            };
+
+            inputs.extend(std::iter::repeat_n(input, times));
+            count.clear();
+        }
+
+        if !count.is_empty() {
+            return Err(String::from("The replay's input ends in a count."));
+        }
+
+        Ok(Replay { start, inputs })
+    }
+}
+
+/// Feeds a replay's input back into the game logic in place of the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    inputs: Vec<Input>,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: &Replay) -> Self {
+        Playback {
+            inputs: replay.inputs.clone(),
+            next: 0,
+        }
+    }
+
+    /// Runs the next recorded tick. Returns false, leaving `controller` untouched, once the
+    /// recording is over.
+    pub fn step(&mut self, controller: &mut Controller) -> bool {
+        let input = match self.inputs.get(self.next) {
+            Some(input) => *input,
+            None => return false,
+        };
+        self.next += 1;
+
+        controller.last_event = input
+            .turn
+            .and_then(direction_key)
+            .map(|code| event::Event::Key(code.into()));
+
+        if input.sprint {
+            controller.sprint_ticks = SPRINT_TICKS;
+        }
+
+        continue_game_logic(controller);
+        true
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.next >= self.inputs.len()
+    }
+
+    /// How many ticks have been played, out of how many there are.
+    pub fn position(&self) -> (usize, usize) {
+        (self.next, self.inputs.len())
+    }
+}
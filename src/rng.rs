@@ -0,0 +1,65 @@
+// The game's own random numbers. Everything random in a run is drawn from here, so two runs with
+// the same seed and the same input play out identically.
+//
+// The generator is SplitMix64 rather than one of rand's: its raw sequence is fixed by this file
+// instead of depending on rand's choice of `StdRng`, and its whole state is a single number that
+// save states can store.
+
+use rand::{Error, RngCore};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn seed_from_u64(seed: u64) -> Self {
+        GameRng { seed, state: seed }
+    }
+
+    /// A generator with a fresh seed, for runs that weren't asked to be reproducible.
+    pub fn from_entropy() -> Self {
+        GameRng::seed_from_u64(rand::random())
+    }
+
+    /// Carries on from a state saved with `state`, still reporting the seed it started from.
+    pub fn resume(seed: u64, state: u64) -> Self {
+        GameRng { seed, state }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+// Save states for --save and --load. Only what the game logic needs to carry on is stored;
+// input, feedback and the end screen always start out fresh.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::effects::{ActiveEffect, Effect};
+use crate::json::{self, Value};
+use crate::rng::GameRng;
+use crate::speed::{Curve, Ramp};
+use crate::{Apple, AppleType, CanvasBounds, CanvasSpace, Controller, Direction, GameMode};
+
+const VERSION: u32 = 1;
+
+pub fn save(controller: &Controller, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, to_json(controller))
+}
+
+pub fn load(path: &Path) -> io::Result<Controller> {
+    let json = fs::read_to_string(path)?;
+    from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn to_json(controller: &Controller) -> String {
+    let stats = &controller.stats;
+
+    let fields = vec![
+        ("version", VERSION.to_string()),
+        (
+            "bounds",
+            format!(
+                "[{}, {}, {}]",
+                controller.bounds.width(),
+                controller.bounds.height(),
+                controller.bounds.inset()
+            ),
+        ),
+        (
+            "snake",
+            cells(controller.snake.elements.iter().collect::<Vec<_>>()),
+        ),
+        (
+            "direction",
+            json::quote(direction_name(controller.snake.current_direction)),
+        ),
+        (
+            "apples",
+            format!(
+                "[{}]",
+                controller
+                    .apples
+                    .iter()
+                    .map(|apple| format!(
+                        "[{}, {}, {}, {}, {}]",
+                        apple.position.0 .0,
+                        apple.position.0 .1,
+                        json::quote(&apple.apple_type.glyph().to_string()),
+                        apple.age,
+                        apple.flash_ticks
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        ("apple_count", controller.apple_count.to_string()),
+        (
+            "pickup",
+            match &controller.pickup {
+                Some((cell, effect)) => format!(
+                    "[{}, {}, {}]",
+                    cell.0 .0,
+                    cell.0 .1,
+                    json::quote(effect_name(*effect))
+                ),
+                None => String::from("null"),
+            },
+        ),
+        (
+            "effects",
+            format!(
+                "[{}]",
+                controller
+                    .active_effects
+                    .iter()
+                    .map(|active| format!(
+                        "[{}, {}]",
+                        json::quote(effect_name(active.effect)),
+                        active.remaining_ticks
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        ("score", controller.score.to_string()),
+        ("golden_due", controller.golden_due.to_string()),
+        (
+            "spawn_apple_next_tick",
+            controller.spawn_apple_next_tick.to_string(),
+        ),
+        ("combo", controller.combo.to_string()),
+        ("mode", json::quote(mode_name(controller.game_mode))),
+        ("obstacles_every", optional(controller.obstacles_every)),
+        (
+            "obstacles",
+            cells(controller.obstacles.iter().collect::<Vec<_>>()),
+        ),
+        (
+            "no_apples",
+            cells(controller.no_apples.iter().collect::<Vec<_>>()),
+        ),
+        (
+            "portals",
+            match &controller.portals {
+                Some((a, b)) => cells(vec![a, b]),
+                None => String::from("null"),
+            },
+        ),
+        ("max_length", optional(controller.max_length)),
+        ("show_ghost", controller.show_ghost.to_string()),
+        ("wrap", controller.wrap.to_string()),
+        ("ticks_per_sec", controller.ticks_per_sec.to_string()),
+        (
+            "ramp",
+            match &controller.ramp {
+                Some(ramp) => format!("[{}, {}]", json::quote(curve_name(ramp.curve)), ramp.cap),
+                None => String::from("null"),
+            },
+        ),
+        // Strings, since JSON numbers can't hold every u64.
+        (
+            "rng",
+            format!(
+                "[{}, {}]",
+                json::quote(&controller.rng.seed().to_string()),
+                json::quote(&controller.rng.state().to_string())
+            ),
+        ),
+        (
+            "stats",
+            format!(
+                "{{\"ticks\": {}, \"elapsed_ms\": {}, \"apples_eaten\": {}, \
+                 \"ticks_since_apple\": {}, \"time_since_apple_ms\": {}, \"best_combo\": {}}}",
+                stats.ticks,
+                stats.elapsed.as_millis(),
+                stats.apples_eaten,
+                stats.ticks_since_apple,
+                stats.time_since_apple.as_millis(),
+                stats.best_combo
+            ),
+        ),
+    ];
+
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|(key, value)| format!("  {}: {}", json::quote(key), value))
+        .collect();
+
+    format!("{{\n{}\n}}\n", fields.join(",\n"))
+}
+
+pub fn from_json(json: &str) -> Result<Controller, String> {
+    let save = json::parse(json)?;
+
+    if field(&save, "version")?.as_u32() != Some(VERSION) {
+        return Err(format!(
+            "Only version {} save states are supported.",
+            VERSION
+        ));
+    }
+
+    let mut controller = Controller::new();
+
+    let bounds = array(field(&save, "bounds")?)?;
+    controller.bounds = match bounds {
+        [width, height] => CanvasBounds::new(number(width)?, number(height)?),
+        // The border of a shrinking board has closed in by `inset` cells.
+        [width, height, inset] => {
+            CanvasBounds::new(number(width)?, number(height)?).with_inset(number(inset)?)
+        }
+        _ => return Err(String::from("Bounds must be a width and a height.")),
+    };
+
+    controller.snake.elements = array(field(&save, "snake")?)?
+        .iter()
+        .map(cell)
+        .collect::<Result<_, _>>()?;
+    if controller.snake.elements.is_empty() {
+        return Err(String::from("The snake needs at least a head."));
+    }
+
+    controller.snake.current_direction = match text(field(&save, "direction")?)? {
+        "up" => Direction::Up,
+        "down" => Direction::Down,
+        "left" => Direction::Left,
+        "right" => Direction::Right,
+        "stop" => Direction::Stop,
+        other => return Err(format!("Unknown direction `{}`.", other)),
+    };
+
+    // Save states from before --apples hold a single apple, which flashed with the board.
+    controller.apples = match (save.get("apples"), field(&save, "apple")) {
+        (Some(apples), _) => array(apples)?
+            .iter()
+            .map(|apple| match array(apple)? {
+                [x, y, glyph, age, flash_ticks] => Ok(Apple {
+                    age: number(age)?,
+                    flash_ticks: number(flash_ticks)?.min(u32::from(u8::MAX)) as u8,
+                    ..parse_apple(x, y, glyph)?
+                }),
+                _ => Err(String::from(
+                    "An apple is a cell, a glyph, its age and its flash.",
+                )),
+            })
+            .collect::<Result<_, String>>()?,
+        (None, Ok(Value::Null)) => Vec::new(),
+        (None, Ok(apple)) => match array(apple)? {
+            [x, y, glyph] => vec![Apple {
+                flash_ticks: number(field(&save, "flash_ticks")?)?.min(u32::from(u8::MAX)) as u8,
+                ..parse_apple(x, y, glyph)?
+            }],
+            _ => return Err(String::from("An apple is a cell and a glyph.")),
+        },
+        (None, Err(e)) => return Err(e),
+    };
+    if let Some(apple_count) = save.get("apple_count") {
+        controller.apple_count = number(apple_count)?.max(1) as usize;
+    }
+
+    controller.pickup = match field(&save, "pickup")? {
+        Value::Null => None,
+        pickup => match array(pickup)? {
+            [x, y, effect] => Some((
+                CanvasSpace((coordinate(x)?, coordinate(y)?)),
+                parse_effect(effect)?,
+            )),
+            _ => return Err(String::from("A pickup is a cell and an effect.")),
+        },
+    };
+
+    controller.active_effects = array(field(&save, "effects")?)?
+        .iter()
+        .map(|active| match array(active)? {
+            [effect, remaining_ticks] => Ok(ActiveEffect {
+                effect: parse_effect(effect)?,
+                remaining_ticks: number(remaining_ticks)?,
+            }),
+            _ => Err(String::from(
+                "An effect is a name and the ticks it has left.",
+            )),
+        })
+        .collect::<Result<_, String>>()?;
+
+    controller.score = number(field(&save, "score")?)?;
+    if let Some(golden_due) = save.get("golden_due") {
+        controller.golden_due = flag(golden_due)?;
+    }
+    controller.spawn_apple_next_tick = flag(field(&save, "spawn_apple_next_tick")?)?;
+    controller.combo = number(field(&save, "combo")?)?;
+
+    controller.game_mode = match text(field(&save, "mode")?)? {
+        "classic" => GameMode::Classic,
+        "hunger" => GameMode::Hunger,
+        "shrink" => GameMode::Shrink,
+        other => return Err(format!("Unknown mode `{}`.", other)),
+    };
+
+    controller.obstacles_every = match field(&save, "obstacles_every")? {
+        Value::Null => None,
+        every => Some(number(every)?),
+    };
+    controller.obstacles = array(field(&save, "obstacles")?)?
+        .iter()
+        .map(cell)
+        .collect::<Result<_, _>>()?;
+    // Save states from before --level have no cells without apples.
+    if let Some(no_apples) = save.get("no_apples") {
+        controller.no_apples = array(no_apples)?
+            .iter()
+            .map(cell)
+            .collect::<Result<_, _>>()?;
+    }
+
+    controller.portals = match field(&save, "portals")? {
+        Value::Null => None,
+        portals => match array(portals)? {
+            [a, b] => Some((cell(a)?, cell(b)?)),
+            _ => return Err(String::from("Portals come in pairs.")),
+        },
+    };
+
+    controller.max_length = match field(&save, "max_length")? {
+        Value::Null => None,
+        max_length => Some(number(max_length)? as usize),
+    };
+    controller.show_ghost = flag(field(&save, "show_ghost")?)?;
+    // Save states from before --wrap stop at the edge.
+    if let Some(wrap) = save.get("wrap") {
+        controller.wrap = flag(wrap)?;
+    }
+
+    // Save states from before --speed existed ran at the default rate.
+    if let Some(ticks_per_sec) = save.get("ticks_per_sec") {
+        controller.ticks_per_sec = number(ticks_per_sec)?.clamp(1, u32::from(u16::MAX)) as u16;
+    }
+    // Neither did --ramp.
+    controller.ramp = match save.get("ramp") {
+        None | Some(Value::Null) => None,
+        Some(ramp) => match array(ramp)? {
+            [curve, cap] => Some(Ramp {
+                curve: text(curve)?.parse()?,
+                cap: number(cap)?.clamp(1, u32::from(u16::MAX)) as u16,
+            }),
+            _ => return Err(String::from("A ramp is a curve and a cap.")),
+        },
+    };
+
+    // Save states from before seeded runs carry on with a fresh seed.
+    if let Some(rng) = save.get("rng") {
+        controller.rng = match array(rng)? {
+            [seed, state] => GameRng::resume(big_number(seed)?, big_number(state)?),
+            _ => return Err(String::from("The generator is a seed and a state.")),
+        };
+    }
+
+    let stats = field(&save, "stats")?;
+    controller.stats.ticks = long(field(stats, "ticks")?)?;
+    controller.stats.elapsed = Duration::from_millis(long(field(stats, "elapsed_ms")?)?);
+    controller.stats.apples_eaten = number(field(stats, "apples_eaten")?)?;
+    controller.stats.ticks_since_apple = long(field(stats, "ticks_since_apple")?)?;
+    controller.stats.time_since_apple =
+        Duration::from_millis(long(field(stats, "time_since_apple_ms")?)?);
+    controller.stats.best_combo = number(field(stats, "best_combo")?)?;
+
+    Ok(controller)
+}
+
+fn cells(cells: Vec<&CanvasSpace>) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| format!("[{}, {}]", cell.0 .0, cell.0 .1))
+        .collect();
+
+    format!("[{}]", cells.join(", "))
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::from("null"), |value| value.to_string())
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+        Direction::Stop => "stop",
+    }
+}
+
+fn effect_name(effect: Effect) -> &'static str {
+    match effect {
+        Effect::SpeedUp => "speed-up",
+        Effect::SlowDown => "slow-down",
+        Effect::Invincible => "invincible",
+    }
+}
+
+fn curve_name(curve: Curve) -> &'static str {
+    match curve {
+        Curve::Linear => "linear",
+        Curve::Exponential => "exponential",
+    }
+}
+
+fn mode_name(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Classic => "classic",
+        GameMode::Hunger => "hunger",
+        GameMode::Shrink => "shrink",
+    }
+}
+
+fn parse_effect(value: &Value) -> Result<Effect, String> {
+    match text(value)? {
+        "speed-up" => Ok(Effect::SpeedUp),
+        "slow-down" => Ok(Effect::SlowDown),
+        "invincible" => Ok(Effect::Invincible),
+        other => Err(format!("Unknown effect `{}`.", other)),
+    }
+}
+
+fn parse_apple(x: &Value, y: &Value, glyph: &Value) -> Result<Apple, String> {
+    let glyph = text(glyph)?
+        .chars()
+        .next()
+        .ok_or("The apple is missing its glyph.")?;
+
+    Ok(Apple::new(
+        CanvasSpace((coordinate(x)?, coordinate(y)?)),
+        AppleType::from_glyph(glyph),
+    ))
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    value
+        .get(key)
+        .ok_or(format!("The save state is missing `{}`.", key))
+}
+
+fn array(value: &Value) -> Result<&[Value], String> {
+    value
+        .as_array()
+        .ok_or(format!("Expected an array, got {:?}.", value))
+}
+
+fn text(value: &Value) -> Result<&str, String> {
+    value
+        .as_str()
+        .ok_or(format!("Expected a string, got {:?}.", value))
+}
+
+fn flag(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(flag) => Ok(*flag),
+        _ => Err(format!("Expected true or false, got {:?}.", value)),
+    }
+}
+
+fn number(value: &Value) -> Result<u32, String> {
+    value
+        .as_u32()
+        .ok_or(format!("Expected a whole number, got {:?}.", value))
+}
+
+// Counters that can outgrow a `u32` on very long runs.
+fn long(value: &Value) -> Result<u64, String> {
+    value
+        .as_f64()
+        .filter(|number| number.fract() == 0.0 && *number >= 0.0)
+        .map(|number| number as u64)
+        .ok_or(format!("Expected a whole number, got {:?}.", value))
+}
+
+fn coordinate(value: &Value) -> Result<i32, String> {
+    value
+        .as_f64()
+        .filter(|number| number.fract() == 0.0 && number.abs() <= f64::from(i32::MAX))
+        .map(|number| number as i32)
+        .ok_or(format!("Expected a coordinate, got {:?}.", value))
+}
+
+fn big_number(value: &Value) -> Result<u64, String> {
+    text(value)?
+        .parse()
+        .map_err(|_| format!("Expected a number in a string, got {:?}.", value))
+}
+
+fn cell(value: &Value) -> Result<CanvasSpace, String> {
+    match array(value)? {
+        [x, y] => Ok(CanvasSpace((coordinate(x)?, coordinate(y)?))),
+        _ => Err(format!("Expected a cell, got {:?}.", value)),
+    }
+}
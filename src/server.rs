@@ -0,0 +1,292 @@
+// Rooms of networked games for --server, played by clients that --join with a --room and looked
+// on by those that --watch. Each room seats two players and runs its game on its own thread
+// through `headless::GameState`, without a terminal. A round starts once both seats are taken
+// and a new one follows a few seconds after it ends. Rooms go away when everyone has left.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::effects;
+use crate::headless::GameState;
+use crate::net::{Board, Peer, Request, REQUEST_TIMEOUT};
+use crate::speed;
+use crate::timing::FixedTimestep;
+use crate::{Controller, GameScreen};
+
+/// The room players end up in if they don't ask for one.
+pub const DEFAULT_ROOM: &str = "default";
+
+// How long the end screen of a round stays up before the next one starts.
+const RESTART_SECS: u64 = 3;
+
+// How often a room checks for players while no round is on.
+const IDLE_INTERVAL: Duration = Duration::from_millis(100);
+
+type Rooms = Arc<Mutex<HashMap<String, Sender<Arrival>>>>;
+
+// Where rooms report what happens in them, see `RoomEvent`.
+type Log = Arc<dyn Fn(&str, RoomEvent) + Send + Sync>;
+
+/// Something that happened in a room. The server doesn't print anything itself, it hands these
+/// to whoever runs it along with the room's name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoomEvent {
+    Opened,
+    Closed,
+    RoundStarted,
+    PlayerJoined,
+    // A player was turned away because both seats are taken.
+    Full,
+    SpectatorJoined,
+    PlayerLeft,
+}
+
+impl RoomEvent {
+    /// A line for the server's output about the room called `room`.
+    pub fn describe(&self, room: &str) -> String {
+        match self {
+            RoomEvent::Opened => format!("Opened room `{}`.", room),
+            RoomEvent::Closed => format!("Closed room `{}`.", room),
+            RoomEvent::RoundStarted => format!("Room `{}` starts a round.", room),
+            RoomEvent::PlayerJoined => format!("A player joined room `{}`.", room),
+            RoomEvent::Full => format!("Room `{}` is full.", room),
+            RoomEvent::SpectatorJoined => format!("Someone is watching room `{}`.", room),
+            RoomEvent::PlayerLeft => format!("A player left room `{}`.", room),
+        }
+    }
+}
+
+// Someone who connected, on their way to a room.
+enum Arrival {
+    Player(Peer),
+    Spectator(Peer),
+}
+
+/// Accepts players on `addr` and seats them in their rooms, forever. `new_game` sets up the
+/// board of every round; it needs to have a second player. Everything that happens in a room
+/// goes to `log`.
+pub fn run(
+    addr: impl ToSocketAddrs,
+    new_game: impl Fn() -> Controller + Send + Sync + 'static,
+    log: impl Fn(&str, RoomEvent) + Send + Sync + 'static,
+) -> io::Result<()> {
+    serve(TcpListener::bind(addr)?, new_game, log);
+    Ok(())
+}
+
+/// Like `run`, on a listener that is already bound.
+pub fn serve(
+    listener: TcpListener,
+    new_game: impl Fn() -> Controller + Send + Sync + 'static,
+    log: impl Fn(&str, RoomEvent) + Send + Sync + 'static,
+) {
+    let rooms: Rooms = Arc::new(Mutex::new(HashMap::new()));
+    let new_game = Arc::new(new_game);
+    let log: Log = Arc::new(log);
+
+    for stream in listener.incoming().flatten() {
+        let rooms = Arc::clone(&rooms);
+        let new_game = Arc::clone(&new_game);
+        let log = Arc::clone(&log);
+
+        let _ = thread::spawn(move || {
+            let peer = match Peer::new(stream) {
+                Ok(peer) => peer,
+                Err(_) => return,
+            };
+            let (name, arrival) = match peer.request(REQUEST_TIMEOUT) {
+                Some(Request::Join(name)) => (name, Arrival::Player(peer)),
+                Some(Request::Watch(name)) => (name, Arrival::Spectator(peer)),
+                _ => (String::from(DEFAULT_ROOM), Arrival::Player(peer)),
+            };
+
+            seat(&rooms, &log, name, arrival, move || new_game());
+        });
+    }
+}
+
+// Hands `arrival` to the room called `name`, opening it if it isn't there.
+fn seat(
+    rooms: &Rooms,
+    log: &Log,
+    name: String,
+    arrival: Arrival,
+    new_game: impl Fn() -> Controller + Send + 'static,
+) {
+    let mut rooms_guard = match rooms.lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+
+    // A room that is just closing has dropped its end, so the arrival gets a new one.
+    let arrival = match rooms_guard.get(&name) {
+        Some(arrivals) => match arrivals.send(arrival) {
+            Ok(()) => return,
+            Err(mpsc::SendError(arrival)) => arrival,
+        },
+        None => arrival,
+    };
+
+    let (arrival_tx, arrival_rx) = mpsc::channel();
+    let _ = arrival_tx.send(arrival);
+    rooms_guard.insert(name.clone(), arrival_tx);
+
+    let rooms = Arc::clone(rooms);
+    let log = Arc::clone(log);
+    let _ = thread::spawn(move || {
+        Room::new(name.clone(), arrival_rx, log).run(&rooms, new_game);
+    });
+}
+
+struct Room {
+    name: String,
+    arrivals: Receiver<Arrival>,
+    // The first seat plays the first snake.
+    seats: [Option<Peer>; 2],
+    spectators: Vec<Peer>,
+    log: Log,
+}
+
+impl Room {
+    fn new(name: String, arrivals: Receiver<Arrival>, log: Log) -> Self {
+        Room {
+            name,
+            arrivals,
+            seats: [None, None],
+            spectators: Vec::new(),
+            log,
+        }
+    }
+
+    fn report(&self, event: RoomEvent) {
+        (self.log)(&self.name, event);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.seats.iter().all(Option::is_none) && self.spectators.is_empty()
+    }
+
+    fn run(mut self, rooms: &Rooms, new_game: impl Fn() -> Controller) {
+        self.report(RoomEvent::Opened);
+
+        loop {
+            self.take_arrivals();
+
+            if self.is_empty() {
+                // Closed while holding the lock, so that nobody is sent here in the meantime.
+                if let Ok(mut rooms) = rooms.lock() {
+                    self.take_arrivals();
+
+                    if self.is_empty() {
+                        rooms.remove(&self.name);
+                        self.report(RoomEvent::Closed);
+                        return;
+                    }
+                }
+            }
+
+            if self.seats.iter().all(Option::is_some) {
+                self.report(RoomEvent::RoundStarted);
+                self.play(GameState::new(new_game()));
+            } else {
+                thread::sleep(IDLE_INTERVAL);
+                self.drop_departed();
+            }
+        }
+    }
+
+    // Seats whoever arrived. A full room turns players away, but there is always room to watch.
+    fn take_arrivals(&mut self) {
+        while let Ok(arrival) = self.arrivals.try_recv() {
+            match arrival {
+                Arrival::Player(peer) => match self.seats.iter_mut().find(|seat| seat.is_none()) {
+                    Some(seat) => {
+                        *seat = Some(peer);
+                        self.report(RoomEvent::PlayerJoined);
+                    }
+                    None => self.report(RoomEvent::Full),
+                },
+                Arrival::Spectator(peer) => {
+                    self.spectators.push(peer);
+                    self.report(RoomEvent::SpectatorJoined);
+                }
+            }
+        }
+    }
+
+    // Lets go of everyone who left while waiting for a round.
+    fn drop_departed(&mut self) {
+        for seat in &mut self.seats {
+            if seat.as_ref().is_some_and(|peer| !peer.is_connected()) {
+                *seat = None;
+                (self.log)(&self.name, RoomEvent::PlayerLeft);
+            }
+        }
+        self.spectators.retain(Peer::is_connected);
+    }
+
+    // Plays one round, and shows how it ended for a moment. Players who leave in between have
+    // their snake carry on straight, and whoever joins takes over their seat.
+    fn play(&mut self, mut state: GameState) {
+        let mut timestep = FixedTimestep::new(Instant::now());
+        let mut tick = 0;
+        let mut over_since = None;
+
+        loop {
+            let controller = &state.controller;
+            let interval = effects::tick_interval(
+                speed::ticks_per_sec(controller),
+                &controller.active_effects,
+            );
+
+            for _ in 0..timestep.wait(interval) {
+                self.take_arrivals();
+
+                let first = self.seats[0].as_ref().and_then(Peer::turn);
+                let second = self.seats[1].as_ref().and_then(Peer::turn);
+                if let (Some(turn), Some(player)) = (second, &mut state.controller.second) {
+                    player.turn = Some(turn);
+                }
+                state.step(first);
+                tick += 1;
+            }
+
+            self.send(tick, &state.controller);
+
+            if self.seats.iter().all(Option::is_none) {
+                return;
+            }
+
+            if state.controller.screen == GameScreen::GameOver {
+                let since = *over_since.get_or_insert_with(Instant::now);
+                if since.elapsed() >= Duration::from_secs(RESTART_SECS) {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, tick: u64, controller: &Controller) {
+        let board = Board::capture(tick, controller);
+
+        for (index, seat) in self.seats.iter_mut().enumerate() {
+            let board = match index {
+                0 => board.clone().swapped(),
+                _ => board.clone(),
+            };
+
+            if seat.as_mut().is_some_and(|peer| !peer.send(board)) {
+                *seat = None;
+                (self.log)(&self.name, RoomEvent::PlayerLeft);
+            }
+        }
+
+        self.spectators
+            .retain_mut(|spectator| spectator.send(board.clone()));
+    }
+}
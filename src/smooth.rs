@@ -0,0 +1,105 @@
+// Presentation for `--smooth`: between two ticks the head slides into its new cell and the tail
+// out of its old one, drawn with half-block characters. The game state itself only ever moves
+// in whole cells.
+
+use crate::{CanvasSpace, Controller};
+
+// Steps a cell is split into while the snake slides through it.
+pub const PHASES: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Where the head and tail were before the last tick, so the renderer knows what to slide from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Motion {
+    pub head_from: CanvasSpace,
+    pub tail_from: CanvasSpace,
+}
+
+impl Motion {
+    pub fn capture(controller: &Controller) -> Option<Self> {
+        let elements = &controller.snake.elements;
+
+        Some(Motion {
+            head_from: elements.front()?.clone(),
+            tail_from: elements.back()?.clone(),
+        })
+    }
+}
+
+/// The side of `from` that `to` borders on, if they are neighbours.
+pub fn side_between(from: &CanvasSpace, to: &CanvasSpace) -> Option<Side> {
+    let ((from_x, from_y), (to_x, to_y)) = (from.0, to.0);
+
+    if from_y == to_y && to_x == from_x + 1 {
+        Some(Side::Right)
+    } else if from_y == to_y && from_x == to_x + 1 {
+        Some(Side::Left)
+    } else if from_x == to_x && to_y == from_y + 1 {
+        Some(Side::Down)
+    } else if from_x == to_x && from_y == to_y + 1 {
+        Some(Side::Up)
+    } else {
+        None
+    }
+}
+
+/// How far into the current tick the frame is, as a phase from 0 to `PHASES - 1`.
+pub fn phase(tick_progress: f64) -> u8 {
+    ((tick_progress.clamp(0.0, 1.0) * PHASES as f64) as u8).min(PHASES - 1)
+}
+
+/// The part of the new head cell that is already covered, for a head entering from `side`.
+pub fn entering_glyph(side: Side, phase: u8) -> &'static str {
+    let glyphs = match side {
+        Side::Left => ["  ", "▌ ", "█ ", "█▌"],
+        Side::Right => ["  ", " ▐", " █", "▐█"],
+        Side::Up => ["  ", "  ", "▀▀", "▀▀"],
+        Side::Down => ["  ", "  ", "▄▄", "▄▄"],
+    };
+
+    glyphs[phase.min(PHASES - 1) as usize]
+}
+
+/// The part of the old tail cell that is still covered, for a tail leaving towards `side`.
+pub fn leaving_glyph(side: Side, phase: u8) -> &'static str {
+    let glyphs = match side {
+        Side::Right => ["██", "▐█", " █", " ▐"],
+        Side::Left => ["██", "█▌", "█ ", "▌ "],
+        Side::Down => ["██", "██", "▄▄", "▄▄"],
+        Side::Up => ["██", "██", "▀▀", "▀▀"],
+    };
+
+    glyphs[phase.min(PHASES - 1) as usize]
+}
+
+/// Partial cells to draw over the board for one frame: the new head and, unless the snake grew,
+/// the cell the tail just left. Moves that skipped a cell, like sprinting, aren't interpolated.
+pub fn partial_cells(
+    motion: &Motion,
+    controller: &Controller,
+    phase: u8,
+) -> Vec<(CanvasSpace, &'static str)> {
+    let elements = &controller.snake.elements;
+    let mut cells = Vec::new();
+
+    if let (Some(head), Some(tail)) = (elements.front(), elements.back()) {
+        if let Some(side) = side_between(head, &motion.head_from) {
+            cells.push((head.clone(), entering_glyph(side, phase)));
+        }
+
+        if !elements.contains(&motion.tail_from) {
+            if let Some(side) = side_between(&motion.tail_from, tail) {
+                cells.push((motion.tail_from.clone(), leaving_glyph(side, phase)));
+            }
+        }
+    }
+
+    cells
+}
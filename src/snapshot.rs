@@ -0,0 +1,160 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{score_label, time_label, Controller};
+
+pub const HEAD: char = '@';
+pub const BODY: char = '#';
+pub const APPLE: char = 'o';
+pub const OBSTACLE: char = 'X';
+pub const PORTAL: char = '*';
+pub const EMPTY: char = ' ';
+
+/// Where `s` on the end screen saves the board if no --snapshot path was given.
+pub const DEFAULT_PATH: &str = "rusty-snake-snapshot.txt";
+
+/// Rasterizes the board, border included, into one string per row. Every canvas cell is a
+/// single character, so the grid is `width + 2` columns by `height + 2` rows.
+pub fn rasterize(controller: &Controller) -> Vec<String> {
+    let width = controller.bounds.width() as usize;
+    let height = controller.bounds.height() as usize;
+
+    let mut cells = vec![vec![EMPTY; width]; height];
+
+    for obstacle in &controller.obstacles {
+        let (x, y) = obstacle.0;
+        if controller.bounds.contains(obstacle) {
+            cells[y as usize][x as usize] = OBSTACLE;
+        }
+    }
+
+    if let Some((a, b)) = &controller.portals {
+        for portal in &[a, b] {
+            let (x, y) = portal.0;
+            if controller.bounds.contains(portal) {
+                cells[y as usize][x as usize] = PORTAL;
+            }
+        }
+    }
+
+    for apple in &controller.apples {
+        let (x, y) = apple.position.0;
+        if controller.bounds.contains(&apple.position) {
+            cells[y as usize][x as usize] = APPLE;
+        }
+    }
+
+    // The head is drawn last so that it stays visible when it overlaps the body or an apple.
+    for (index, element) in controller.snake.elements.iter().enumerate().rev() {
+        let (x, y) = element.0;
+        if controller.bounds.contains(element) {
+            cells[y as usize][x as usize] = if index == 0 { HEAD } else { BODY };
+        }
+    }
+
+    let edge = format!("+{}+", "-".repeat(width));
+
+    let mut grid = Vec::with_capacity(height + 2);
+    grid.push(edge.clone());
+    grid.extend(
+        cells
+            .into_iter()
+            .map(|row| format!("|{}|", row.into_iter().collect::<String>())),
+    );
+    grid.push(edge);
+
+    grid
+}
+
+/// The board followed by a footer with the score, the snake's length, how long the run lasted,
+/// the seed it was played with and the date it was saved on.
+pub fn snapshot_text(controller: &Controller, seed: Option<u64>, date: &str) -> String {
+    let mut text = rasterize(controller).join("\n");
+
+    let seed = match seed {
+        Some(seed) => seed.to_string(),
+        None => String::from("-"),
+    };
+
+    text.push_str(&format!(
+        "\nScore: {}  Length: {}  Time: {}  Ticks: {}  Seed: {}  Date: {}\n",
+        score_label(controller.score),
+        controller.snake.elements.len(),
+        time_label(controller.stats.elapsed),
+        controller.stats.ticks,
+        seed,
+        date
+    ));
+
+    text
+}
+
+/// Where the final board of a round goes, if anywhere: --snapshot (`flag`) saves every round,
+/// `s` on the end screen only the current one.
+pub fn snapshot_path(flag: Option<&Path>, controller: &Controller) -> Option<PathBuf> {
+    match flag {
+        Some(path) => Some(path.to_path_buf()),
+        None if controller.snapshot_requested => Some(PathBuf::from(DEFAULT_PATH)),
+        None => None,
+    }
+}
+
+/// Writes a snapshot to `path`. An existing file is only replaced if `force` is set.
+pub fn write_snapshot(path: &Path, text: &str, force: bool) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+
+    options.open(path)?.write_all(text.as_bytes())
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let (year, month, day) = civil_from_days((unix_secs() / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The current time in UTC as `YYYY-MM-DD HH:MM`.
+pub fn timestamp() -> String {
+    let secs = unix_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs % 86_400 / 3600,
+        secs % 3600 / 60
+    )
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// Converts days since 1970-01-01 into a proleptic Gregorian date, following Howard Hinnant's
+// `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,76 @@
+// Progressive speed-up for --ramp: the game ticks faster as the score grows, up to a cap. The
+// ramp only ever adds to the --speed a run starts at, so a cap below it changes nothing.
+
+use std::str::FromStr;
+
+use crate::Controller;
+
+// The ramp takes another step every this many points.
+pub const RAMP_EVERY: u32 = 5;
+
+// An exponential step makes the game this much faster, in percent.
+const EXPONENTIAL_STEP: f64 = 10.0;
+
+// Fastest a ramp gets without --max-speed, in ticks per second.
+pub const DEFAULT_RAMP_CAP: u16 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    // One more tick per second each step.
+    Linear,
+    // `EXPONENTIAL_STEP` percent faster each step.
+    Exponential,
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(curve: &str) -> Result<Self, Self::Err> {
+        match curve {
+            "linear" => Ok(Curve::Linear),
+            "exponential" => Ok(Curve::Exponential),
+            _ => Err(format!(
+                "Unknown ramp `{}`, expected linear or exponential.",
+                curve
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ramp {
+    pub curve: Curve,
+    // In ticks per second.
+    pub cap: u16,
+}
+
+impl Ramp {
+    pub fn new(curve: Curve) -> Self {
+        Ramp {
+            curve,
+            cap: DEFAULT_RAMP_CAP,
+        }
+    }
+
+    /// The tick rate of a game that started at `base` ticks per second once it scored `score`.
+    pub fn ticks_per_sec(&self, base: u16, score: u32) -> u16 {
+        let steps = score / RAMP_EVERY;
+        let ramped = match self.curve {
+            Curve::Linear => f64::from(base) + f64::from(steps),
+            Curve::Exponential => {
+                f64::from(base) * (1.0 + EXPONENTIAL_STEP / 100.0).powf(f64::from(steps))
+            }
+        };
+
+        (ramped.round().min(f64::from(self.cap)) as u16).max(base)
+    }
+}
+
+/// How many ticks make a second right now, before effects. This is what the game loop paces
+/// itself by; durations in seconds go by the rate the run started at.
+pub fn ticks_per_sec(controller: &Controller) -> u16 {
+    match &controller.ramp {
+        Some(ramp) => ramp.ticks_per_sec(controller.ticks_per_sec, controller.score),
+        None => controller.ticks_per_sec,
+    }
+}
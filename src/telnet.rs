@@ -0,0 +1,309 @@
+// Serving the game to telnet clients for --telnet, one game per connection. The client is asked
+// to leave echoing to the server, to send keys as they are typed and to report its window size,
+// and what it sends back is turned into the same events crossterm reads from a local terminal.
+// The game draws to the connection like it would to stdout.
+//
+// SSH works by way of sshd: a `ForceCommand rusty-snake` for the snake user runs a local game
+// on the session's terminal, which needs nothing from here.
+
+use std::io::{self, BufWriter, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+const ESC: u8 = 0x1b;
+
+/// The options every connection starts with: the server echoes, characters go through one at a
+/// time and the client tells its window size.
+pub const NEGOTIATION: [u8; 9] = [IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS];
+
+/// The window size of clients that don't tell theirs.
+pub const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+// How long a new connection waits for the client to tell its window size before the game
+// starts without it.
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Turns the bytes a telnet client sends into key presses, and window size reports into
+/// `Event::Resize`. Sequences split across reads are put together.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    pending: Vec<u8>,
+}
+
+// What the start of the input amounts to.
+enum Token {
+    // Not all of it has arrived yet.
+    Incomplete,
+    // This many bytes that mean nothing to the game.
+    Skip(usize),
+    Event(usize, Event),
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.pending.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        let mut start = 0;
+
+        while start < self.pending.len() {
+            match token(&self.pending[start..]) {
+                Token::Incomplete => break,
+                Token::Skip(length) => start += length,
+                Token::Event(length, event) => {
+                    events.push(event);
+                    start += length;
+                }
+            }
+        }
+
+        self.pending.drain(..start);
+        events
+    }
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn token(bytes: &[u8]) -> Token {
+    match bytes[0] {
+        IAC => command(bytes),
+        ESC => escape(bytes),
+        // Telnet sends Enter as CR LF or CR NUL.
+        b'\r' => match bytes.get(1) {
+            Some(b'\n') | Some(0) => Token::Event(2, key(KeyCode::Enter)),
+            _ => Token::Event(1, key(KeyCode::Enter)),
+        },
+        b'\n' => Token::Event(1, key(KeyCode::Enter)),
+        b'\t' => Token::Event(1, key(KeyCode::Tab)),
+        0x08 | 0x7f => Token::Event(1, key(KeyCode::Backspace)),
+        // Ctrl-A to Ctrl-Z.
+        byte @ 0x01..=0x1a => Token::Event(
+            1,
+            Event::Key(KeyEvent::new(
+                KeyCode::Char((b'a' + byte - 1) as char),
+                KeyModifiers::CONTROL,
+            )),
+        ),
+        byte if byte < 0x20 => Token::Skip(1),
+        byte => {
+            let length = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            if bytes.len() < length {
+                return Token::Incomplete;
+            }
+
+            match std::str::from_utf8(&bytes[..length])
+                .ok()
+                .and_then(|text| text.chars().next())
+            {
+                Some(c) => Token::Event(length, key(KeyCode::Char(c))),
+                None => Token::Skip(1),
+            }
+        }
+    }
+}
+
+// An IAC command. Only the window size is of interest, every other option is left as it is.
+fn command(bytes: &[u8]) -> Token {
+    match bytes.get(1) {
+        None => Token::Incomplete,
+        Some(&SB) => {
+            let end = match bytes.windows(2).position(|pair| pair == [IAC, SE]) {
+                Some(end) => end,
+                None => return Token::Incomplete,
+            };
+
+            match &bytes[2..end] {
+                [NAWS, width_high, width_low, height_high, height_low] => Token::Event(
+                    end + 2,
+                    Event::Resize(
+                        u16::from_be_bytes([*width_high, *width_low]),
+                        u16::from_be_bytes([*height_high, *height_low]),
+                    ),
+                ),
+                _ => Token::Skip(end + 2),
+            }
+        }
+        Some(&verb) if (WILL..=DONT).contains(&verb) => match bytes.len() {
+            0..=2 => Token::Incomplete,
+            _ => Token::Skip(3),
+        },
+        Some(_) => Token::Skip(2),
+    }
+}
+
+// Arrow keys come as `ESC [ A` or `ESC O A`. A lone escape is the Esc key, since clients send
+// sequences in one piece.
+fn escape(bytes: &[u8]) -> Token {
+    match bytes.get(1) {
+        Some(b'[') | Some(b'O') => (),
+        _ => return Token::Event(1, key(KeyCode::Esc)),
+    }
+
+    // Parameters, if any, and then a final byte.
+    let end = match bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+    {
+        Some(index) => index + 2,
+        None => return Token::Incomplete,
+    };
+    let code = match bytes[end] {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        _ => return Token::Skip(end + 1),
+    };
+
+    Token::Event(end + 1, key(code))
+}
+
+/// Where a game served over telnet draws to. Dropping it hangs up.
+pub struct Connection {
+    stream: BufWriter<TcpStream>,
+    // Until the client tells.
+    size: Arc<Mutex<Option<(u16, u16)>>>,
+}
+
+impl Connection {
+    /// Negotiates with the client on the other end of `stream` and forwards what it types to
+    /// the returned receiver, until either side hangs up.
+    pub fn open(stream: TcpStream) -> io::Result<(Connection, Receiver<Event>)> {
+        stream.set_nodelay(true)?;
+        let mut reader = stream.try_clone()?;
+        let mut writer = BufWriter::new(stream);
+        writer.write_all(&NEGOTIATION)?;
+        writer.flush()?;
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let size = Arc::new(Mutex::new(None));
+        let mut decoder = Decoder::new();
+
+        // The game is laid out for the window from the first frame on, if the client says
+        // soon enough how big it is.
+        let start = Instant::now();
+        while size.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+            let left = match NEGOTIATION_TIMEOUT.checked_sub(start.elapsed()) {
+                Some(left) if left > Duration::ZERO => left,
+                _ => break,
+            };
+            reader.set_read_timeout(Some(left))?;
+            if !receive(&mut reader, &mut decoder, &event_tx, &size) {
+                break;
+            }
+        }
+        reader.set_read_timeout(None)?;
+
+        let reader_size = Arc::clone(&size);
+        let _ = thread::spawn(
+            move || {
+                while receive(&mut reader, &mut decoder, &event_tx, &reader_size) {}
+            },
+        );
+
+        Ok((
+            Connection {
+                stream: writer,
+                size,
+            },
+            event_rx,
+        ))
+    }
+
+    /// The client's window size, in columns and rows.
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .unwrap_or(DEFAULT_SIZE)
+    }
+}
+
+// Reads once from the client, returning whether it is still there.
+fn receive(
+    reader: &mut TcpStream,
+    decoder: &mut Decoder,
+    events: &Sender<Event>,
+    size: &Mutex<Option<(u16, u16)>>,
+) -> bool {
+    let mut buffer = [0; 256];
+    let read = match reader.read(&mut buffer) {
+        Ok(0) => return false,
+        Ok(read) => read,
+        // Including running out of time while negotiating.
+        Err(_) => return false,
+    };
+
+    for event in decoder.feed(&buffer[..read]) {
+        if let Event::Resize(width, height) = event {
+            *size.lock().unwrap_or_else(|e| e.into_inner()) = Some((width, height));
+        }
+        if events.send(event).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.stream.flush();
+        let _ = self.stream.get_ref().shutdown(Shutdown::Both);
+    }
+}
+
+/// Accepts telnet clients on `listener` forever, and hands each to `play` on a thread of its
+/// own.
+pub fn serve(
+    listener: TcpListener,
+    play: impl Fn(Connection, Receiver<Event>) + Send + Sync + 'static,
+) {
+    let play = Arc::new(play);
+
+    for stream in listener.incoming().flatten() {
+        let play = Arc::clone(&play);
+
+        let _ = thread::spawn(move || {
+            if let Ok((connection, events)) = Connection::open(stream) {
+                play(connection, events);
+            }
+        });
+    }
+}
@@ -0,0 +1,373 @@
+// Colors for --theme. A theme says what color each kind of thing on screen is drawn in, so the
+// drawing code asks for `theme.snake` or `theme.highlight` instead of naming a color. Emoji keep
+// their own colors whatever the theme says.
+//
+// Besides the built-in themes, every `<name>.toml` in the `themes` directory next to the config
+// file is a theme called `<name>`. It gives colors as RGB, and what it leaves out stays classic:
+//
+//     snake = "#00ff88"
+//     second-snake = "#ff00aa"
+//     highlight = "#ffd700"
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crossterm::style::Color;
+
+use crate::json::Value;
+use crate::toml;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub snake: Color,
+    // The snake of the second player, or of the computer.
+    pub second_snake: Color,
+    // The heads of both snakes.
+    pub head: Color,
+    // Only shows on apples drawn with --apple-char.
+    pub apple: Color,
+    pub border: Color,
+    pub grid: Color,
+    pub ghost: Color,
+    pub obstacle: Color,
+    pub portal: Color,
+    pub logo: Color,
+    // Selected menu entries, the countdown and whatever else of the HUD should stand out.
+    pub highlight: Color,
+    // Hints and labels that should stay out of the way.
+    pub muted: Color,
+    // Warnings, like the hunger bar or what the snake died of.
+    pub alert: Color,
+}
+
+pub const CLASSIC: Theme = Theme {
+    snake: Color::Red,
+    second_snake: Color::Blue,
+    head: Color::Yellow,
+    apple: Color::Reset,
+    border: Color::Reset,
+    grid: Color::DarkGrey,
+    ghost: Color::DarkGrey,
+    obstacle: Color::Grey,
+    portal: Color::Magenta,
+    logo: Color::DarkRed,
+    highlight: Color::Yellow,
+    muted: Color::DarkGrey,
+    alert: Color::Red,
+};
+
+pub const MATRIX: Theme = Theme {
+    snake: Color::Green,
+    second_snake: Color::White,
+    head: Color::White,
+    apple: Color::Green,
+    border: Color::DarkGreen,
+    grid: Color::DarkGreen,
+    ghost: Color::DarkGreen,
+    obstacle: Color::DarkGreen,
+    portal: Color::White,
+    logo: Color::Green,
+    highlight: Color::Green,
+    muted: Color::DarkGreen,
+    alert: Color::White,
+};
+
+pub const MONOCHROME: Theme = Theme {
+    snake: Color::White,
+    second_snake: Color::Grey,
+    head: Color::Grey,
+    apple: Color::White,
+    border: Color::Grey,
+    grid: Color::DarkGrey,
+    ghost: Color::DarkGrey,
+    obstacle: Color::Grey,
+    portal: Color::White,
+    logo: Color::White,
+    highlight: Color::White,
+    muted: Color::DarkGrey,
+    alert: Color::White,
+};
+
+pub const PASTEL: Theme = Theme {
+    snake: Color::Rgb {
+        r: 255,
+        g: 179,
+        b: 186,
+    },
+    second_snake: Color::Rgb {
+        r: 186,
+        g: 225,
+        b: 255,
+    },
+    head: Color::Rgb {
+        r: 255,
+        g: 255,
+        b: 186,
+    },
+    apple: Color::Rgb {
+        r: 255,
+        g: 223,
+        b: 186,
+    },
+    border: Color::Rgb {
+        r: 186,
+        g: 255,
+        b: 201,
+    },
+    grid: Color::Rgb {
+        r: 90,
+        g: 90,
+        b: 105,
+    },
+    ghost: Color::Rgb {
+        r: 140,
+        g: 140,
+        b: 155,
+    },
+    obstacle: Color::Rgb {
+        r: 200,
+        g: 200,
+        b: 215,
+    },
+    portal: Color::Rgb {
+        r: 220,
+        g: 190,
+        b: 255,
+    },
+    logo: Color::Rgb {
+        r: 255,
+        g: 179,
+        b: 186,
+    },
+    highlight: Color::Rgb {
+        r: 255,
+        g: 255,
+        b: 186,
+    },
+    muted: Color::Rgb {
+        r: 140,
+        g: 140,
+        b: 155,
+    },
+    alert: Color::Rgb {
+        r: 255,
+        g: 140,
+        b: 150,
+    },
+};
+
+/// The built-in themes by the names --theme and the config file know them by.
+pub const THEMES: [(&str, Theme); 4] = [
+    ("classic", CLASSIC),
+    ("matrix", MATRIX),
+    ("monochrome", MONOCHROME),
+    ("pastel", PASTEL),
+];
+
+// The names theme files give the colors by, in the order of `Theme`'s fields.
+pub const ELEMENTS: [&str; 13] = [
+    "snake",
+    "second-snake",
+    "head",
+    "apple",
+    "border",
+    "grid",
+    "ghost",
+    "obstacle",
+    "portal",
+    "logo",
+    "highlight",
+    "muted",
+    "alert",
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        CLASSIC
+    }
+}
+
+impl Theme {
+    /// Reads a theme file. Every element it doesn't mention keeps its classic color.
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        let document = toml::parse(toml)?;
+        let mut theme = CLASSIC;
+
+        if let Value::Object(fields) = &document {
+            for (key, value) in fields {
+                let slot = match key.as_str() {
+                    "snake" => &mut theme.snake,
+                    "second-snake" => &mut theme.second_snake,
+                    "head" => &mut theme.head,
+                    "apple" => &mut theme.apple,
+                    "border" => &mut theme.border,
+                    "grid" => &mut theme.grid,
+                    "ghost" => &mut theme.ghost,
+                    "obstacle" => &mut theme.obstacle,
+                    "portal" => &mut theme.portal,
+                    "logo" => &mut theme.logo,
+                    "highlight" => &mut theme.highlight,
+                    "muted" => &mut theme.muted,
+                    "alert" => &mut theme.alert,
+                    _ => {
+                        return Err(format!(
+                            "Unknown element `{}`, themes can color {}.",
+                            key,
+                            ELEMENTS.join(", ")
+                        ))
+                    }
+                };
+
+                *slot = value
+                    .as_str()
+                    .and_then(parse_color)
+                    .ok_or(format!("`{}` must be a color like \"#ff8800\".", key))?;
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Parses `#rrggbb`.
+pub fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+
+    Some(Color::Rgb {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+// How dark the tail of a shaded snake is, compared to its head.
+const TAIL_BRIGHTNESS: f64 = 0.4;
+
+/// Whether the terminal shows 24-bit color, going by what it puts in `$COLORTERM`.
+pub fn truecolor(colorterm: Option<&str>) -> bool {
+    matches!(colorterm, Some("truecolor") | Some("24bit"))
+}
+
+/// The RGB value of `color`, with the named colors as xterm shows them. The terminal's own
+/// default and palette entries have none.
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Black => Some((0, 0, 0)),
+        Color::DarkRed => Some((205, 0, 0)),
+        Color::Red => Some((255, 0, 0)),
+        Color::DarkGreen => Some((0, 205, 0)),
+        Color::Green => Some((0, 255, 0)),
+        Color::DarkYellow => Some((205, 205, 0)),
+        Color::Yellow => Some((255, 255, 0)),
+        Color::DarkBlue => Some((0, 0, 238)),
+        Color::Blue => Some((92, 92, 255)),
+        Color::DarkMagenta => Some((205, 0, 205)),
+        Color::Magenta => Some((255, 0, 255)),
+        Color::DarkCyan => Some((0, 205, 205)),
+        Color::Cyan => Some((0, 255, 255)),
+        Color::DarkGrey => Some((127, 127, 127)),
+        Color::Grey => Some((229, 229, 229)),
+        Color::White => Some((255, 255, 255)),
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::Reset | Color::AnsiValue(_) => None,
+    }
+}
+
+/// The shade of segment `index` of a snake `length` segments long and drawn in `color`: the
+/// color itself at the head, fading linearly to `TAIL_BRIGHTNESS` of it at the tail. Colors
+/// without an RGB value stay as they are.
+pub fn gradient(color: Color, index: usize, length: usize) -> Color {
+    let (r, g, b) = match rgb(color) {
+        Some(rgb) => rgb,
+        None => return color,
+    };
+
+    let along = if length > 1 {
+        index.min(length - 1) as f64 / (length - 1) as f64
+    } else {
+        0.0
+    };
+    let brightness = 1.0 - along * (1.0 - TAIL_BRIGHTNESS);
+    let shade = |channel: u8| (f64::from(channel) * brightness).round() as u8;
+
+    Color::Rgb {
+        r: shade(r),
+        g: shade(g),
+        b: shade(b),
+    }
+}
+
+/// The theme files in `dir`, by name. Without the directory there are none. A file that isn't
+/// a valid theme is an error naming the file.
+pub fn load_dir(dir: &Path) -> io::Result<Vec<(String, Theme)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut themes = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name)
+                if path
+                    .extension()
+                    .is_some_and(|extension| extension == "toml") =>
+            {
+                String::from(name)
+            }
+            _ => continue,
+        };
+
+        let theme = Theme::from_toml(&fs::read_to_string(&path)?).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}.toml: {}", name, e))
+        })?;
+        themes.push((name, theme));
+    }
+
+    // Directories list in no particular order, the menu should always show the same one.
+    themes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(themes)
+}
+
+/// The built-in themes followed by the `user` ones, which take over built-in ones of the same
+/// name.
+pub fn all(user: &[(String, Theme)]) -> Vec<(String, Theme)> {
+    let mut themes: Vec<(String, Theme)> = THEMES
+        .iter()
+        .map(|(name, theme)| (String::from(*name), *theme))
+        .collect();
+
+    for (name, theme) in user {
+        match themes.iter_mut().find(|(known, _)| known == name) {
+            Some((_, known)) => *known = *theme,
+            None => themes.push((name.clone(), *theme)),
+        }
+    }
+
+    themes
+}
+
+/// The theme called `name` among `themes`.
+pub fn find(themes: &[(String, Theme)], name: &str) -> Result<Theme, String> {
+    themes
+        .iter()
+        .find(|(known, _)| known == name)
+        .map(|(_, theme)| *theme)
+        .ok_or_else(|| {
+            let names: Vec<&str> = themes.iter().map(|(known, _)| known.as_str()).collect();
+            format!(
+                "Unknown theme `{}`, pick one of {}.",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// The built-in theme called `name`.
+pub fn named(name: &str) -> Result<Theme, String> {
+    find(&all(&[]), name)
+}
@@ -0,0 +1,147 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+// If the loop falls further behind than this, the missed ticks are dropped instead of replayed,
+// so a stalled terminal can't snowball into ever longer catch-up bursts.
+pub const MAX_CATCH_UP_TICKS: u32 = 5;
+
+/// Schedules logic ticks on absolute deadlines so that the game speed doesn't depend on how
+/// long drawing takes.
+#[derive(Debug)]
+pub struct FixedTimestep {
+    next_tick: Instant,
+}
+
+impl FixedTimestep {
+    pub fn new(first_tick: Instant) -> Self {
+        FixedTimestep {
+            next_tick: first_tick,
+        }
+    }
+
+    pub fn next_tick(&self) -> Instant {
+        self.next_tick
+    }
+
+    /// Returns how many ticks are due at `now` and moves the schedule past them.
+    pub fn due_ticks(&mut self, now: Instant, interval: Duration) -> u32 {
+        let mut due = 0;
+
+        while self.next_tick <= now && due < MAX_CATCH_UP_TICKS {
+            self.next_tick += interval;
+            due += 1;
+        }
+
+        if self.next_tick <= now {
+            self.next_tick = now + interval;
+        }
+
+        due
+    }
+
+    /// Sleeps until the next deadline and returns the number of ticks due by then.
+    pub fn wait(&mut self, interval: Duration) -> u32 {
+        let now = Instant::now();
+
+        if self.next_tick > now {
+            thread::sleep(self.next_tick - now);
+        }
+
+        self.due_ticks(Instant::now(), interval)
+    }
+
+    /// Like `wait`, but returns after `max_wait` at the latest so that frames can be drawn in
+    /// between ticks.
+    pub fn wait_at_most(&mut self, interval: Duration, max_wait: Duration) -> u32 {
+        let now = Instant::now();
+
+        if self.next_tick > now {
+            thread::sleep((self.next_tick - now).min(max_wait));
+        }
+
+        self.due_ticks(Instant::now(), interval)
+    }
+
+    /// How much of the current tick has passed at `now`, from 0 right after a tick to 1 at the
+    /// next deadline.
+    pub fn progress(&self, now: Instant, interval: Duration) -> f64 {
+        if interval == Duration::default() {
+            return 1.0;
+        }
+
+        let remaining = self.next_tick.saturating_duration_since(now);
+        (1.0 - remaining.as_secs_f64() / interval.as_secs_f64()).clamp(0.0, 1.0)
+    }
+}
+
+/// Measures the actual tick rate over one second windows and the time the last frame took.
+#[derive(Debug)]
+pub struct TimingStats {
+    window_start: Instant,
+    window_ticks: u32,
+    pub ticks_per_sec: f64,
+    pub frame_time: Duration,
+}
+
+impl TimingStats {
+    pub fn new(now: Instant) -> Self {
+        TimingStats {
+            window_start: now,
+            window_ticks: 0,
+            ticks_per_sec: 0.0,
+            frame_time: Duration::default(),
+        }
+    }
+
+    pub fn record_ticks(&mut self, now: Instant, ticks: u32) {
+        self.window_ticks += ticks;
+
+        let elapsed = now - self.window_start;
+        if elapsed >= Duration::from_secs(1) {
+            self.ticks_per_sec = self.window_ticks as f64 / elapsed.as_secs_f64();
+            self.window_start = now;
+            self.window_ticks = 0;
+        }
+    }
+}
+
+// Weight of the newest tick interval in the running average, which keeps the display readable
+// while still following speed changes within a second or so.
+const TICK_RATE_SMOOTHING: f64 = 0.1;
+
+/// Estimates the tick rate from the time between consecutive ticks.
+#[derive(Debug, Default)]
+pub struct TickRateMeter {
+    last_tick: Option<Instant>,
+    average_interval: Option<Duration>,
+}
+
+impl TickRateMeter {
+    pub fn new() -> Self {
+        TickRateMeter::default()
+    }
+
+    pub fn record_tick(&mut self, now: Instant) {
+        if let Some(last_tick) = self.last_tick {
+            let interval = now.saturating_duration_since(last_tick);
+
+            self.average_interval = Some(match self.average_interval {
+                Some(average) => {
+                    average.mul_f64(1.0 - TICK_RATE_SMOOTHING)
+                        + interval.mul_f64(TICK_RATE_SMOOTHING)
+                }
+                None => interval,
+            });
+        }
+
+        self.last_tick = Some(now);
+    }
+
+    // Zero until two ticks have been recorded.
+    pub fn ticks_per_sec(&self) -> f64 {
+        match self.average_interval {
+            Some(interval) if interval > Duration::default() => 1.0 / interval.as_secs_f64(),
+            _ => 0.0,
+        }
+    }
+}
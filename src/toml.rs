@@ -0,0 +1,197 @@
+// The part of TOML the config file needs: `[table]` headers, `key = value` pairs, comments,
+// strings, integers, booleans and single-line arrays. Documents are returned as JSON values so
+// that the rest of the code reads them the same way.
+
+use crate::json::Value;
+
+/// Parses a TOML document into an object with one nested object per table.
+pub fn parse(toml: &str) -> Result<Value, String> {
+    let mut root: Vec<(String, Value)> = Vec::new();
+    let mut table: Option<String> = None;
+
+    for (index, line) in toml.lines().enumerate() {
+        let at_line = |e: String| format!("Line {}: {}", index + 1, e);
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .map(str::trim)
+                .filter(|name| is_bare_key(name))
+                .ok_or_else(|| at_line(format!("Invalid table header `{}`.", line)))?;
+
+            if root.iter().any(|(key, _)| key == name) {
+                return Err(at_line(format!("`{}` is defined twice.", name)));
+            }
+
+            root.push((String::from(name), Value::Object(Vec::new())));
+            table = Some(String::from(name));
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(equals) => (line[..equals].trim(), line[equals + 1..].trim()),
+            None => {
+                return Err(at_line(format!(
+                    "Expected `key = value`, found `{}`.",
+                    line
+                )))
+            }
+        };
+
+        if !is_bare_key(key) {
+            return Err(at_line(format!("Invalid key `{}`.", key)));
+        }
+
+        let value = parse_value(value).map_err(at_line)?;
+
+        let fields = match &table {
+            Some(name) => match root.iter_mut().find(|(key, _)| key == name) {
+                Some((_, Value::Object(fields))) => fields,
+                _ => unreachable!("Every table header adds its table."),
+            },
+            None => &mut root,
+        };
+
+        if fields.iter().any(|(existing, _)| existing == key) {
+            return Err(at_line(format!("`{}` is defined twice.", key)));
+        }
+        fields.push((String::from(key), value));
+    }
+
+    Ok(Value::Object(root))
+}
+
+// A `#` starts a comment unless it is inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '#') => return &line[..index],
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), c) if c == open && !(open == '"' && escaped(line, index)) => quote = None,
+            _ => (),
+        }
+    }
+
+    line
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    match value {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ if value.starts_with('[') => {
+            let items = value
+                .strip_suffix(']')
+                .ok_or(format!("Unterminated array `{}`.", value))?[1..]
+                .trim();
+
+            split_items(items)?
+                .into_iter()
+                .map(parse_value)
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        }
+        _ if value.starts_with('"') => parse_basic_string(value).map(Value::String),
+        _ if value.starts_with('\'') => value[1..]
+            .strip_suffix('\'')
+            .filter(|text| !text.contains('\''))
+            .map(|text| Value::String(String::from(text)))
+            .ok_or(format!("Invalid string {}.", value)),
+        _ => value
+            .replace('_', "")
+            .parse::<i64>()
+            .map(|number| Value::Number(number as f64))
+            .map_err(|_| format!("Invalid value `{}`.", value)),
+    }
+}
+
+// Splits the inside of an array at the commas that aren't part of a string. A trailing comma is
+// allowed, as in TOML.
+fn split_items(items: &str) -> Result<Vec<&str>, String> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (index, c) in items.char_indices() {
+        match (quote, c) {
+            (None, ',') => {
+                parts.push(items[start..index].trim());
+                start = index + 1;
+            }
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), c) if c == open && !(open == '"' && escaped(items, index)) => quote = None,
+            _ => (),
+        }
+    }
+
+    let last = items[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(String::from("Empty item in array."));
+    }
+
+    Ok(parts)
+}
+
+// Whether the quote at `index` is preceded by an odd number of backslashes.
+fn escaped(text: &str, index: usize) -> bool {
+    text[..index]
+        .chars()
+        .rev()
+        .take_while(|&c| c == '\\')
+        .count()
+        % 2
+        == 1
+}
+
+fn parse_basic_string(value: &str) -> Result<String, String> {
+    let mut chars = value.chars().skip(1);
+    let mut text = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => text.push(match chars.next() {
+                Some('"') => '"',
+                Some('\\') => '\\',
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(format!("Invalid escape `\\u{}`.", hex))?
+                }
+                _ => return Err(format!("Invalid escape in {}.", value)),
+            }),
+            Some(c) => text.push(c),
+            None => return Err(format!("Unterminated string {}.", value)),
+        }
+    }
+
+    if chars.next().is_some() {
+        return Err(format!("Unexpected data after {}.", value));
+    }
+
+    Ok(text)
+}
@@ -0,0 +1,204 @@
+// The game for the browser frontend in web/. A `WebGame` runs the same logic as the terminal,
+// one tick at a time through `headless::GameState`, and keeps the board as one byte per cell
+// for the frontend to paint onto a canvas.
+//
+// With the `wasm` feature the crate also exports a few plain functions around a single game,
+// which is all the frontend needs from WebAssembly, without any bindings generator:
+//
+//     cargo rustc --lib --release --features wasm --target wasm32-unknown-unknown \
+//         --crate-type cdylib
+//
+// and then serve web/ with target/wasm32-unknown-unknown/release/rusty_snake.wasm next to it.
+
+use std::time::Duration;
+
+use crate::effects;
+use crate::headless::{GameState, StepResult};
+use crate::rng::GameRng;
+use crate::speed;
+use crate::{
+    place_snake, AppleType, CanvasSpace, Controller, Direction, GameConfig, StartPosition,
+};
+
+/// What a cell of the board shows, as the frontend tells them apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum Cell {
+    Empty = 0,
+    Snake = 1,
+    Head = 2,
+    Apple = 3,
+    Golden = 4,
+    Poison = 5,
+    Obstacle = 6,
+}
+
+/// The direction a frontend key code stands for: 0 to 3 are up, down, left and right.
+pub fn direction(code: u32) -> Option<Direction> {
+    match code {
+        0 => Some(Direction::Up),
+        1 => Some(Direction::Down),
+        2 => Some(Direction::Left),
+        3 => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+pub struct WebGame {
+    state: GameState,
+    // The last turn asked for since the previous tick.
+    turn: Option<Direction>,
+    cells: Vec<u8>,
+}
+
+impl WebGame {
+    /// A new run on the default board. Browsers have no entropy to offer the game, so the
+    /// frontend passes in a seed.
+    pub fn new(seed: u64) -> Self {
+        let mut controller = Controller::new();
+        controller.rng = GameRng::seed_from_u64(seed);
+        // A centered start always fits the default board.
+        let _ = place_snake(
+            &mut controller,
+            &StartPosition::Center,
+            GameConfig::default().start_length,
+        );
+
+        let mut game = WebGame {
+            state: GameState::new(controller),
+            turn: None,
+            cells: Vec::new(),
+        };
+        game.update_cells();
+        game
+    }
+
+    pub fn controller(&self) -> &Controller {
+        &self.state.controller
+    }
+
+    pub fn turn(&mut self, direction: Direction) {
+        self.turn = Some(direction);
+    }
+
+    pub fn tick(&mut self) -> StepResult {
+        let result = self.state.step(self.turn.take());
+        self.update_cells();
+        result
+    }
+
+    /// How long to wait before the next tick.
+    pub fn interval(&self) -> Duration {
+        let controller = self.controller();
+        effects::tick_interval(speed::ticks_per_sec(controller), &controller.active_effects)
+    }
+
+    /// The board row by row, one `Cell` per byte.
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    fn update_cells(&mut self) {
+        let controller = &self.state.controller;
+        let width = controller.bounds.width() as usize;
+        let cells = &mut self.cells;
+        cells.clear();
+        cells.resize(
+            width * controller.bounds.height() as usize,
+            Cell::Empty as u8,
+        );
+
+        let mut paint = |position: &CanvasSpace, cell: Cell| {
+            let (x, y) = position.0;
+            if let Some(slot) = cells.get_mut(y as usize * width + x as usize) {
+                *slot = cell as u8;
+            }
+        };
+
+        for obstacle in &controller.obstacles {
+            paint(obstacle, Cell::Obstacle);
+        }
+        for apple in &controller.apples {
+            let cell = match apple.apple_type {
+                AppleType::Fresh(_) => Cell::Apple,
+                AppleType::Golden => Cell::Golden,
+                AppleType::Poison => Cell::Poison,
+            };
+            paint(&apple.position, cell);
+        }
+        for (index, element) in controller.snake.elements.iter().enumerate() {
+            paint(element, if index == 0 { Cell::Head } else { Cell::Snake });
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod exports {
+    use std::cell::RefCell;
+
+    use super::{direction, WebGame};
+    use crate::headless::StepResult;
+    use crate::GameScreen;
+
+    thread_local! {
+        static GAME: RefCell<WebGame> = RefCell::new(WebGame::new(0));
+    }
+
+    /// Starts over with the seed `high << 32 | low`, since JavaScript numbers can't hold a u64.
+    #[no_mangle]
+    pub extern "C" fn snake_new(high: u32, low: u32) {
+        let seed = u64::from(high) << 32 | u64::from(low);
+        GAME.with(|game| *game.borrow_mut() = WebGame::new(seed));
+    }
+
+    /// Asks for a turn, see `direction` for the codes. Others are ignored.
+    #[no_mangle]
+    pub extern "C" fn snake_turn(code: u32) {
+        if let Some(direction) = direction(code) {
+            GAME.with(|game| game.borrow_mut().turn(direction));
+        }
+    }
+
+    /// Plays one tick: 0 if the snake moved, 1 if it ate, 2 for a won run and 3 for a lost one.
+    #[no_mangle]
+    pub extern "C" fn snake_tick() -> u32 {
+        GAME.with(|game| match game.borrow_mut().tick() {
+            StepResult::Moved => 0,
+            StepResult::AteApple => 1,
+            StepResult::Won => 2,
+            StepResult::Lost(_) => 3,
+        })
+    }
+
+    #[no_mangle]
+    pub extern "C" fn snake_interval_ms() -> u32 {
+        GAME.with(|game| game.borrow().interval().as_millis() as u32)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn snake_width() -> u32 {
+        GAME.with(|game| game.borrow().controller().bounds.width())
+    }
+
+    #[no_mangle]
+    pub extern "C" fn snake_height() -> u32 {
+        GAME.with(|game| game.borrow().controller().bounds.height())
+    }
+
+    #[no_mangle]
+    pub extern "C" fn snake_score() -> u32 {
+        GAME.with(|game| game.borrow().controller().score)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn snake_is_over() -> bool {
+        GAME.with(|game| game.borrow().controller().screen == GameScreen::GameOver)
+    }
+
+    /// Where the `snake_width` by `snake_height` cells start in memory. They move when the game
+    /// starts over, so the frontend looks them up after every tick.
+    #[no_mangle]
+    pub extern "C" fn snake_cells() -> *const u8 {
+        GAME.with(|game| game.borrow().cells().as_ptr())
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use rusty_snake::achievements::{Death, Progress, ACHIEVEMENTS};
+use rusty_snake::effects::{apply_effect, Effect};
+use rusty_snake::{CanvasSpace, Controller, GameScreen, TICKS_PER_SEC};
+
+fn unlocks(id: &str, controller: &Controller) -> bool {
+    let achievement = ACHIEVEMENTS
+        .iter()
+        .find(|achievement| achievement.id == id)
+        .unwrap();
+
+    (achievement.unlocked_by)(controller)
+}
+
+fn snake_of_length(length: i32) -> Controller {
+    let mut controller = Controller::new();
+    controller.snake.elements = (0..length).map(|x| CanvasSpace((x, 0))).collect();
+    controller
+}
+
+#[test]
+fn predicates_match_their_descriptions() {
+    let mut controller = Controller::new();
+    assert!(ACHIEVEMENTS
+        .iter()
+        .all(|achievement| !(achievement.unlocked_by)(&controller)));
+
+    controller.stats.apples_eaten = 1;
+    assert!(unlocks("first-blood", &controller));
+
+    assert!(!unlocks("centurion", &snake_of_length(99)));
+    assert!(unlocks("centurion", &snake_of_length(100)));
+
+    controller.score = 20;
+    assert!(!unlocks("speed-demon", &controller));
+    apply_effect(
+        &mut controller.active_effects,
+        Effect::SpeedUp,
+        TICKS_PER_SEC,
+    );
+    assert!(unlocks("speed-demon", &controller));
+
+    controller.stats.time_since_apple = Duration::from_secs(59);
+    assert!(!unlocks("pacifist", &controller));
+    controller.stats.time_since_apple = Duration::from_secs(60);
+    assert!(unlocks("pacifist", &controller));
+    controller.screen = GameScreen::GameOver;
+    assert!(!unlocks("pacifist", &controller));
+
+    let mut controller = snake_of_length(30);
+    controller.stats.death = Some(Death::Wall);
+    assert!(!unlocks("ouroboros", &controller));
+    controller.stats.death = Some(Death::SelfCollision);
+    assert!(unlocks("ouroboros", &controller));
+}
+
+#[test]
+fn achievements_unlock_once() {
+    let mut progress = Progress::default();
+    let mut controller = Controller::new();
+    controller.stats.apples_eaten = 1;
+
+    let unlocked = progress.unlock_new(&controller, "2021-03-04");
+    assert_eq!(unlocked.len(), 1);
+    assert_eq!(unlocked[0].id, "first-blood");
+
+    assert!(progress.unlock_new(&controller, "2021-03-05").is_empty());
+    assert!(progress.is_unlocked("first-blood"));
+}
+
+#[test]
+fn progress_json_round_trip() {
+    let mut progress = Progress::default();
+    let mut controller = Controller::new();
+    controller.stats.apples_eaten = 1;
+    progress.unlock_new(&controller, "2021-03-04");
+
+    assert_eq!(Progress::from_json(&progress.to_json()), Ok(progress));
+}
@@ -0,0 +1,88 @@
+use std::sync::mpsc;
+
+use crossterm::event::{Event, KeyCode};
+use rusty_snake::ai::{self, Skill};
+use rusty_snake::bot::Registry;
+use rusty_snake::{
+    continue_game_logic, handle_events, Apple, AppleType, CanvasBounds, CanvasSpace, Controller,
+    Direction, GameScreen, Player,
+};
+
+fn against_ai(second: &[(i32, i32)], apple: (i32, i32)) -> Controller {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(10, 10);
+    controller.snake.elements = vec![CanvasSpace((0, 0))].into();
+    controller.snake.current_direction = Direction::Right;
+    let mut player = Player::new(second.iter().map(|&cell| CanvasSpace(cell)).collect());
+    player.ai = Some(Skill::Hard);
+    controller.second = Some(player);
+    controller.apples = vec![Apple::new(CanvasSpace(apple), AppleType::Fresh('🍎'))];
+    controller
+}
+
+#[test]
+fn heads_for_the_apple_around_obstacles() {
+    let mut controller = against_ai(&[(5, 5), (5, 6)], (5, 2));
+    assert_eq!(ai::turn(&mut controller), Some(Direction::Up));
+
+    // A wall right in front makes it go around.
+    controller.obstacles = vec![CanvasSpace((5, 4))];
+    let turn = ai::turn(&mut controller);
+    assert!(turn == Some(Direction::Left) || turn == Some(Direction::Right));
+
+    // Poison isn't worth going for, even when it is closer.
+    controller.obstacles.clear();
+    controller.apples = vec![
+        Apple::new(CanvasSpace((5, 4)), AppleType::Poison),
+        Apple::new(CanvasSpace((2, 5)), AppleType::Fresh('🍎')),
+    ];
+    assert_eq!(ai::turn(&mut controller), Some(Direction::Left));
+}
+
+#[test]
+fn without_a_way_to_an_apple_it_picks_the_roomier_side() {
+    // The wall at x = 7 leaves little room on the right, and the apple is sealed off.
+    let mut controller = against_ai(&[(8, 5), (8, 6)], (0, 9));
+    controller.obstacles = (0..10)
+        .map(|y| CanvasSpace((7, y)))
+        .chain((0..7).map(|x| CanvasSpace((x, 8))))
+        .collect();
+    controller
+        .obstacles
+        .retain(|cell| *cell != CanvasSpace((7, 5)));
+
+    assert_eq!(ai::turn(&mut controller), Some(Direction::Left));
+}
+
+#[test]
+fn the_computer_eats_the_apple_it_plays_for() {
+    let mut controller = against_ai(&[(5, 5), (5, 6)], (5, 3));
+
+    continue_game_logic(&mut controller);
+    continue_game_logic(&mut controller);
+
+    let second = controller.second.as_ref().unwrap();
+    assert_eq!(second.snake.elements[0], CanvasSpace((5, 3)));
+    assert_eq!(second.score, 1);
+    assert_eq!(controller.score, 0);
+}
+
+#[test]
+fn the_autopilot_plays_the_player_snake_and_any_key_ends_it() {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(10, 10);
+    controller.snake.elements = vec![CanvasSpace((5, 5))].into();
+    controller.apples = vec![Apple::new(CanvasSpace((8, 5)), AppleType::Fresh('🍎'))];
+    controller.demo = true;
+    controller.pilot = Some(Registry::default().create("greedy").unwrap());
+
+    for _ in 0..3 {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.score, 1);
+
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx.send(Event::Key(KeyCode::Up.into())).unwrap();
+    handle_events(&mut controller, &event_rx);
+    assert_eq!(controller.screen, GameScreen::Closed);
+}
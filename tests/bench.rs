@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+use rusty_snake::bench::{run, BENCH_SNAKE_LENGTH};
+use rusty_snake::Controller;
+
+#[test]
+fn times_every_tick_of_the_run() {
+    let report = run(
+        Duration::from_millis(200),
+        BENCH_SNAKE_LENGTH,
+        Controller::new,
+    );
+
+    assert!(report.ticks > 0);
+    assert!(report.min <= report.avg() && report.avg() <= report.max);
+    assert!(report.ticks_per_sec() > 0.0);
+}
@@ -0,0 +1,74 @@
+use rusty_snake::board::{place_layout, Layout};
+use rusty_snake::rng::GameRng;
+use rusty_snake::{
+    continue_game_logic, place_snake, validate_game_config, CanvasBounds, Controller, GameConfig,
+    StartPosition,
+};
+
+const LAYOUTS: [Layout; 3] = [Layout::Cross, Layout::Pillars, Layout::Scatter];
+
+#[test]
+fn layouts_stay_on_the_board_and_clear_of_the_snake() {
+    let bounds = CanvasBounds::new(30, 20);
+
+    for &layout in &LAYOUTS {
+        let mut controller = Controller::new();
+        controller.bounds = bounds;
+        controller.rng = GameRng::seed_from_u64(3);
+        place_snake(&mut controller, &StartPosition::Center, 5).unwrap();
+        place_layout(&mut controller, layout);
+
+        assert!(!controller.obstacles.is_empty(), "{:?}", layout);
+        assert!(controller
+            .obstacles
+            .iter()
+            .all(|wall| bounds.contains(wall) && !controller.snake.elements.contains(wall)));
+
+        // Apples never spawn on a wall.
+        for _ in 0..50 {
+            continue_game_logic(&mut controller);
+            for apple in &controller.apples {
+                assert!(!controller.obstacles.contains(&apple.position));
+            }
+            controller.apples.clear();
+            controller.spawn_apple_next_tick = true;
+        }
+    }
+}
+
+#[test]
+fn scatter_moves_its_walls_but_not_their_number() {
+    let bounds = CanvasBounds::new(30, 20);
+    let walls = |seed| Layout::Scatter.walls(&bounds, &mut GameRng::seed_from_u64(seed));
+
+    assert_eq!(walls(1).len(), 30);
+    assert_eq!(walls(1), walls(1));
+    assert_ne!(walls(1), walls(2));
+    assert_eq!(walls(2).len(), 30);
+}
+
+#[test]
+fn layouts_count_towards_the_board_check() {
+    assert_eq!("pillars".parse(), Ok(Layout::Pillars));
+    assert!("maze".parse::<Layout>().is_err());
+
+    let config = GameConfig {
+        bounds: CanvasBounds::new(4, 4),
+        start_length: 1,
+        layout: Some(Layout::Cross),
+        ..GameConfig::default()
+    };
+    assert_eq!(
+        Layout::Cross
+            .walls(&config.bounds, &mut GameRng::seed_from_u64(0))
+            .len(),
+        3
+    );
+    assert_eq!(validate_game_config(&config), Ok(()));
+
+    let crowded = GameConfig {
+        bounds: CanvasBounds::new(2, 2),
+        ..config
+    };
+    assert!(validate_game_config(&crowded).is_err());
+}
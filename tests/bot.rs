@@ -0,0 +1,70 @@
+use std::sync::mpsc;
+
+use crossterm::event::{Event, KeyCode};
+use rusty_snake::bot::{BoardView, Registry, SnakeController};
+use rusty_snake::{
+    continue_game_logic, handle_events, CanvasBounds, CanvasSpace, Controller, Direction,
+    GameScreen,
+};
+
+// Goes down until it is about to hit the bottom, then left.
+struct Plummet;
+
+impl SnakeController for Plummet {
+    fn next_move(&mut self, view: &BoardView) -> Direction {
+        let (x, y) = view.snake().elements[0].0;
+        if view.is_blocked(&CanvasSpace((x, y + 1))) {
+            Direction::Left
+        } else {
+            Direction::Down
+        }
+    }
+}
+
+fn playing() -> Controller {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(10, 10);
+    controller.snake.elements = vec![CanvasSpace((5, 5))].into();
+    controller.screen = GameScreen::Playing;
+    controller
+}
+
+#[test]
+fn a_registered_controller_steers_the_snake() {
+    let mut registry = Registry::default();
+    registry.register("plummet", || Box::new(Plummet));
+    assert_eq!(registry.names(), vec!["keyboard", "greedy", "plummet"]);
+
+    let mut controller = playing();
+    controller.pilot = Some(registry.create("plummet").unwrap());
+
+    for _ in 0..5 {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.snake.elements[0], CanvasSpace((4, 9)));
+    assert_eq!(controller.snake.current_direction, Direction::Left);
+}
+
+#[test]
+fn unknown_bots_are_rejected() {
+    let error = Registry::default().create("skynet").unwrap_err();
+    assert!(error.contains("keyboard, greedy"), "{}", error);
+}
+
+#[test]
+fn the_keyboard_controller_follows_the_arrow_keys() {
+    let mut controller = playing();
+    controller.pilot = Some(Registry::default().create("keyboard").unwrap());
+
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx.send(Event::Key(KeyCode::Up.into())).unwrap();
+    handle_events(&mut controller, &event_rx);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.elements[0], CanvasSpace((5, 4)));
+
+    // Turning back onto the neck is ignored, whoever asks for it.
+    event_tx.send(Event::Key(KeyCode::Down.into())).unwrap();
+    handle_events(&mut controller, &event_rx);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.current_direction, Direction::Up);
+}
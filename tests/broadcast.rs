@@ -0,0 +1,72 @@
+use rusty_snake::broadcast::{Broadcaster, FrameSnapshot, CLIENT_BACKLOG};
+use rusty_snake::rng::GameRng;
+use rusty_snake::{
+    place_second_player, place_snake, CanvasBounds, CanvasSpace, Controller, StartPosition,
+    CANVAS_HEIGHT, CANVAS_WIDTH,
+};
+
+fn two_player_game() -> Controller {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(20, 12).with_inset(1);
+    controller.rng = GameRng::seed_from_u64(5);
+    controller.obstacles = vec![CanvasSpace((3, 3)), CanvasSpace((4, 3))];
+    place_snake(&mut controller, &StartPosition::Center, 3).unwrap();
+    place_second_player(&mut controller, 3).unwrap();
+    controller
+}
+
+#[test]
+fn frames_survive_the_line_format() {
+    let controller = two_player_game();
+    let frame = FrameSnapshot::capture(7, &controller);
+
+    let line = frame.to_string();
+    assert_eq!(line.parse::<FrameSnapshot>(), Ok(frame.clone()));
+
+    let mut shown = Controller::new();
+    frame.apply(&mut shown);
+    assert_eq!(shown.bounds, controller.bounds);
+    assert_eq!(shown.obstacles, controller.obstacles);
+    assert_eq!(shown.snake.elements, controller.snake.elements);
+    assert_eq!(
+        shown.second.unwrap().snake.elements,
+        controller.second.unwrap().snake.elements
+    );
+
+    assert!("7 0 0 - 1,1 20,12".parse::<FrameSnapshot>().is_err());
+}
+
+#[test]
+fn frames_without_a_board_are_on_the_default_one() {
+    let frame: FrameSnapshot = "3 2 0 1,1,🍎 5,5;4,5".parse().unwrap();
+
+    assert_eq!(
+        frame.bounds,
+        CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT)
+    );
+    assert!(frame.obstacles.is_empty());
+    assert!(frame.second.is_empty());
+    assert_eq!(frame.snake, vec![CanvasSpace((5, 5)), CanvasSpace((4, 5))]);
+}
+
+#[test]
+fn lagging_spectators_lose_frames_instead_of_blocking() {
+    let broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+    let lagging = broadcaster.subscribe();
+    let controller = two_player_game();
+
+    // Nobody reads, so everything past the backlog is dropped without waiting.
+    for tick in 0..10 {
+        broadcaster.send(&FrameSnapshot::capture(tick, &controller));
+    }
+
+    let ticks: Vec<u64> = lagging
+        .try_iter()
+        .map(|line| line.parse::<FrameSnapshot>().unwrap().tick)
+        .collect();
+    assert_eq!(ticks, (0..CLIENT_BACKLOG as u64).collect::<Vec<_>>());
+
+    // Once it caught up, it gets new frames again.
+    broadcaster.send(&FrameSnapshot::capture(10, &controller));
+    assert_eq!(lagging.try_iter().count(), 1);
+}
@@ -0,0 +1,67 @@
+use std::io::Write;
+
+use rusty_snake::cast::CastWriter;
+use rusty_snake::json::{self, Value};
+use rusty_snake::snapshot::rasterize;
+use rusty_snake::{continue_game_logic, CanvasBounds, CanvasSpace, Controller, Direction};
+
+#[test]
+fn scripted_game_records_a_valid_cast() {
+    let mut terminal = Vec::new();
+    let mut cast = Vec::new();
+
+    {
+        let mut writer = CastWriter::new(&mut terminal, &mut cast, (80, 24), "test run").unwrap();
+
+        let mut controller = Controller::new();
+        controller.bounds = CanvasBounds::new(6, 4);
+        controller.snake.elements = vec![CanvasSpace((0, 1))].into_iter().collect();
+        controller.snake.current_direction = Direction::Right;
+
+        for _ in 0..3 {
+            continue_game_logic(&mut controller);
+
+            write!(writer, "\x1b[2J{}\r\n", rasterize(&controller).join("\r\n")).unwrap();
+            writer.flush().unwrap();
+        }
+
+        // Never flushed, but still recorded once the writer goes away.
+        write!(writer, "bye").unwrap();
+    }
+
+    let cast = String::from_utf8(cast).unwrap();
+    let lines: Vec<Value> = cast
+        .lines()
+        .map(|line| json::parse(line).unwrap())
+        .collect();
+
+    let header = &lines[0];
+    assert_eq!(header.get("version"), Some(&Value::Number(2.0)));
+    assert_eq!(header.get("width").and_then(Value::as_u32), Some(80));
+    assert_eq!(header.get("height").and_then(Value::as_u32), Some(24));
+    assert_eq!(
+        header.get("title").and_then(Value::as_str),
+        Some("test run")
+    );
+    assert!(header.get("timestamp").and_then(Value::as_f64).is_some());
+
+    assert_eq!(lines.len(), 1 + 4);
+
+    let mut last_time = 0.0;
+    let mut replayed = String::new();
+
+    for event in &lines[1..] {
+        let event = event.as_array().unwrap();
+        assert_eq!(event.len(), 3);
+
+        let time = event[0].as_f64().unwrap();
+        assert!(time >= last_time);
+        last_time = time;
+
+        assert_eq!(event[1].as_str(), Some("o"));
+        replayed.push_str(event[2].as_str().unwrap());
+    }
+
+    assert_eq!(replayed.as_bytes(), &terminal[..]);
+    assert!(replayed.ends_with("bye"));
+}
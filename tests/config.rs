@@ -0,0 +1,85 @@
+use crossterm::event::KeyCode;
+use rusty_snake::config::Config;
+use rusty_snake::json::Value;
+use rusty_snake::keys::{Action, KeyBindings};
+use rusty_snake::toml;
+
+#[test]
+fn toml_subset_parses_into_tables() {
+    let document = toml::parse(
+        "# Rusty snake\nspeed = 1_5 # fast\nname = \"a # b\"\n\n[keys]\nup = ['w', \"k\",]\n",
+    )
+    .unwrap();
+
+    assert_eq!(document.get("speed"), Some(&Value::Number(15.0)));
+    assert_eq!(document.get("name").and_then(Value::as_str), Some("a # b"));
+    assert_eq!(
+        document.get("keys").and_then(|keys| keys.get("up")),
+        Some(&Value::Array(vec![
+            Value::String(String::from("w")),
+            Value::String(String::from("k")),
+        ]))
+    );
+
+    assert!(toml::parse("speed = 15\nspeed = 16").is_err());
+    assert!(toml::parse("speed").is_err());
+    assert!(toml::parse("name = \"unterminated").is_err());
+}
+
+#[test]
+fn config_reads_every_setting() {
+    let config = Config::from_toml(
+        "width = 60\nheight = 30\nspeed = 15\napple-char = \"🍒\"\ncolor = false\n\
+         theme = \"pastel\"\ncolorblind = true\nsound = true\n\n\
+         [keys]\nleft = [\"a\", \"h\"]\nquit = \"Esc\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(config.width, Some(60));
+    assert_eq!(config.height, Some(30));
+    assert_eq!(config.speed, Some(15));
+    assert_eq!(config.apple_char.as_deref(), Some("🍒"));
+    assert_eq!(config.snake_char, None);
+    assert_eq!(config.color, Some(false));
+    assert_eq!(config.theme.as_deref(), Some("pastel"));
+    assert_eq!(config.colorblind, Some(true));
+    assert_eq!(config.sound, Some(true));
+    assert_eq!(
+        config.keys,
+        vec![
+            (KeyCode::Char('a'), Action::Left),
+            (KeyCode::Char('h'), Action::Left),
+            (KeyCode::Esc, Action::Quit),
+        ]
+    );
+
+    assert_eq!(Config::from_toml(""), Ok(Config::default()));
+    assert!(Config::from_toml("widht = 60").is_err());
+    assert!(Config::from_toml("width = -1").is_err());
+    assert!(Config::from_toml("sound = \"on\"").is_err());
+    assert!(Config::from_toml("[keys]\njump = \"j\"").is_err());
+    assert_eq!(
+        Config::from_toml("[keybindings]\nboost = \"b\"")
+            .unwrap()
+            .keys,
+        vec![(KeyCode::Char('b'), Action::Sprint)]
+    );
+    assert!(Config::from_toml("online-leaderboard = \"https://scores.example.com\"").is_err());
+    assert_eq!(
+        Config::from_toml("online-leaderboard = \"http://scores.example.com\"")
+            .unwrap()
+            .online_leaderboard
+            .map(|endpoint| endpoint.host),
+        Some(String::from("scores.example.com"))
+    );
+}
+
+#[test]
+fn bound_keys_take_over_from_their_defaults() {
+    let mut keys = KeyBindings::default();
+    keys.bind(KeyCode::Char('h'), Action::Left);
+
+    assert_eq!(keys.action(KeyCode::Char('h')), Some(Action::Left));
+    assert_eq!(keys.action(KeyCode::Left), Some(Action::Left));
+    assert_eq!(keys.action(KeyCode::Char('?')), Some(Action::Help));
+}
@@ -0,0 +1,26 @@
+use rusty_snake::difficulty::{Difficulty, DIFFICULTIES};
+
+#[test]
+fn harder_presets_are_faster_and_smaller() {
+    let presets: Vec<_> = DIFFICULTIES.iter().map(|d| d.preset()).collect();
+
+    for pair in presets.windows(2) {
+        assert!(pair[0].ticks_per_sec < pair[1].ticks_per_sec);
+        let area = |(width, height): (u16, u16)| u32::from(width) * u32::from(height);
+        assert!(area(pair[0].canvas_size) >= area(pair[1].canvas_size));
+    }
+
+    assert!(Difficulty::Easy.preset().wrap);
+    assert!(Difficulty::Normal.preset().ramp.is_none());
+    assert!(Difficulty::Insane.preset().ramp.is_some());
+}
+
+#[test]
+fn difficulties_parse_and_step_within_the_list() {
+    assert_eq!("hard".parse(), Ok(Difficulty::Hard));
+    assert!("nightmare".parse::<Difficulty>().is_err());
+
+    assert_eq!(Difficulty::Normal.step(1), Difficulty::Hard);
+    assert_eq!(Difficulty::Easy.step(-1), Difficulty::Easy);
+    assert_eq!(Difficulty::Insane.step(1), Difficulty::Insane);
+}
@@ -0,0 +1,70 @@
+use crossterm::event::KeyCode;
+use rusty_snake::editor::{Editor, EditorInput};
+use rusty_snake::level::{Level, Tile};
+use rusty_snake::{CanvasBounds, CanvasSpace};
+
+fn keys(editor: &mut Editor, codes: &[KeyCode]) -> Vec<Option<EditorInput>> {
+    codes.iter().map(|&code| editor.handle_key(code)).collect()
+}
+
+#[test]
+fn edits_round_trip_through_the_level_format() {
+    let mut editor = Editor::new(Level::empty(CanvasBounds::new(4, 3)));
+    editor.cursor = CanvasSpace((0, 0));
+
+    keys(
+        &mut editor,
+        &[
+            KeyCode::Char(' '),
+            KeyCode::Right,
+            KeyCode::Char('x'),
+            KeyCode::Down,
+            KeyCode::Char('s'),
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Char(' '),
+            KeyCode::Down,
+            KeyCode::Down,
+            KeyCode::Char(' '),
+            KeyCode::Char(' '),
+        ],
+    );
+
+    // The cursor stops at the edges, and a second space takes the wall away again.
+    assert_eq!(editor.cursor, CanvasSpace((3, 2)));
+    assert_eq!(editor.level.tile(&CanvasSpace((3, 2))), Tile::Empty);
+    assert!(editor.modified);
+
+    let text = editor.level.to_string();
+    assert_eq!(text, "#x..\n.S.#\n....\n");
+    assert_eq!(text.parse(), Ok(editor.level));
+}
+
+#[test]
+fn the_start_moves_and_unsaved_changes_need_a_second_quit() {
+    let mut editor = Editor::new(Level::empty(CanvasBounds::new(5, 5)));
+    assert_eq!(
+        editor.handle_key(KeyCode::Char('q')),
+        Some(EditorInput::Quit)
+    );
+
+    keys(
+        &mut editor,
+        &[KeyCode::Char('s'), KeyCode::Left, KeyCode::Char('s')],
+    );
+    assert_eq!(editor.level.spawn, Some(CanvasSpace((1, 1))));
+
+    assert_eq!(editor.handle_key(KeyCode::Char('q')), None);
+    assert!(editor.confirm_quit);
+    assert_eq!(editor.handle_key(KeyCode::Up), None);
+    assert!(!editor.confirm_quit);
+    assert_eq!(
+        keys(&mut editor, &[KeyCode::Esc, KeyCode::Char('q')]),
+        vec![None, Some(EditorInput::Quit)]
+    );
+    assert_eq!(
+        editor.handle_key(KeyCode::Char('w')),
+        Some(EditorInput::Save)
+    );
+}
@@ -0,0 +1,76 @@
+use rusty_snake::effects::{apply_effect, tick_effects, tick_interval, ActiveEffect, Effect};
+use rusty_snake::TICKS_PER_SEC;
+
+#[test]
+fn effect_lasts_ten_seconds_of_its_own_ticks() {
+    assert_eq!(
+        Effect::SpeedUp.duration_ticks(TICKS_PER_SEC),
+        15 * TICKS_PER_SEC as u32
+    );
+    assert_eq!(
+        ActiveEffect::new(Effect::SlowDown, TICKS_PER_SEC).remaining_secs(TICKS_PER_SEC),
+        10
+    );
+}
+
+#[test]
+fn effect_expires_after_its_duration() {
+    let mut effects = Vec::new();
+    apply_effect(&mut effects, Effect::SlowDown, TICKS_PER_SEC);
+
+    for _ in 1..Effect::SlowDown.duration_ticks(TICKS_PER_SEC) {
+        tick_effects(&mut effects);
+    }
+    assert_eq!(effects.len(), 1);
+    assert_eq!(effects[0].remaining_ticks, 1);
+
+    tick_effects(&mut effects);
+    assert!(effects.is_empty());
+}
+
+#[test]
+fn new_effect_replaces_the_active_one() {
+    let mut effects = Vec::new();
+    apply_effect(&mut effects, Effect::SpeedUp, TICKS_PER_SEC);
+    tick_effects(&mut effects);
+
+    apply_effect(&mut effects, Effect::SlowDown, TICKS_PER_SEC);
+
+    assert_eq!(
+        effects,
+        vec![ActiveEffect::new(Effect::SlowDown, TICKS_PER_SEC)]
+    );
+}
+
+#[test]
+fn tick_interval_follows_the_active_effect() {
+    let normal = tick_interval(TICKS_PER_SEC, &[]);
+    let fast = tick_interval(
+        TICKS_PER_SEC,
+        &[ActiveEffect::new(Effect::SpeedUp, TICKS_PER_SEC)],
+    );
+    let slow = tick_interval(
+        TICKS_PER_SEC,
+        &[ActiveEffect::new(Effect::SlowDown, TICKS_PER_SEC)],
+    );
+
+    assert!(fast < normal);
+    assert!(slow > normal);
+    assert_eq!(normal.as_millis(), 1000 / TICKS_PER_SEC as u128);
+}
+
+#[test]
+fn a_faster_game_keeps_effects_as_long_in_seconds() {
+    let fast = 2 * TICKS_PER_SEC;
+    let effect = ActiveEffect::new(Effect::SpeedUp, fast);
+
+    assert_eq!(
+        effect.remaining_ticks,
+        2 * Effect::SpeedUp.duration_ticks(TICKS_PER_SEC)
+    );
+    assert_eq!(effect.remaining_secs(fast), 10);
+    assert_eq!(
+        tick_interval(fast, &[]) * 2,
+        tick_interval(TICKS_PER_SEC, &[])
+    );
+}
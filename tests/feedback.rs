@@ -0,0 +1,29 @@
+use rusty_snake::feedback::{Cue, Feedback, TerminalBell};
+
+fn bells_per_tick(cue: Cue, ticks: usize) -> Vec<usize> {
+    let mut bell = TerminalBell::new();
+    bell.cue(cue);
+
+    (0..ticks)
+        .map(|_| {
+            let mut output = Vec::new();
+            bell.tick(&mut output).unwrap();
+            output.iter().filter(|&&byte| byte == 0x07).count()
+        })
+        .collect()
+}
+
+#[test]
+fn apple_rings_once() {
+    assert_eq!(bells_per_tick(Cue::AppleEaten, 3), vec![1, 0, 0]);
+}
+
+#[test]
+fn bonus_rings_twice_in_quick_succession() {
+    assert_eq!(bells_per_tick(Cue::BonusCollected, 3), vec![1, 1, 0]);
+}
+
+#[test]
+fn game_over_rings_three_times_over_300ms() {
+    assert_eq!(bells_per_tick(Cue::GameOver, 5), vec![1, 0, 1, 1, 0]);
+}
@@ -0,0 +1,1384 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use rusty_snake::achievements::Death;
+use rusty_snake::effects::{self, Effect};
+use rusty_snake::feedback::Cue;
+use rusty_snake::rng::GameRng;
+use rusty_snake::speed::{Curve, Ramp, RAMP_EVERY};
+use rusty_snake::{
+    add_score, canvas_max_x, canvas_max_y, click_direction, compute_new_head, continue_game_logic,
+    count_down, countdown_secs, golden_remaining_secs, handle_events, maybe_spawn_obstacle,
+    place_snake, random_cell, score_label, start_countdown, starting_direction, starting_snake,
+    take_buffered_turn, ticks_until_hungry, time_label, validate_game_config, winner, Apple,
+    AppleType, CanvasBounds, CanvasSpace, Controller, Direction, GameConfig, GameMode, GameScreen,
+    Player, StartPosition, TerminalSpace, CANVAS_HEIGHT, CANVAS_WIDTH, COUNTDOWN_SECS,
+    GOLDEN_EVERY, GOLDEN_POINTS, GOLDEN_SECONDS, HUNGER_TICKS, POISON_PENALTY, POISON_SEGMENTS,
+    POISON_TICKS, SHRINK_SECONDS, TICKS_PER_SEC,
+};
+
+fn controller_with_snake(elements: &[(i32, i32)], direction: Direction) -> Controller {
+    let mut controller = Controller::new();
+    controller.snake.elements = elements.iter().map(|&cell| CanvasSpace(cell)).collect();
+    controller.snake.current_direction = direction;
+    // Park the apple in a corner so that random placement doesn't interfere.
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Fresh('🍎'))];
+    controller
+}
+
+fn park_apple(controller: &mut Controller) {
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Fresh('🍎'))];
+}
+
+fn press(controller: &mut Controller, code: KeyCode) {
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx.send(Event::Key(code.into())).unwrap();
+    handle_events(controller, &event_rx);
+}
+
+fn head(controller: &Controller) -> (i32, i32) {
+    controller.snake.elements[0].0
+}
+
+// How long a tick lasts at the default speed.
+fn tick() -> Duration {
+    Duration::from_secs(1) / u32::from(TICKS_PER_SEC)
+}
+
+#[test]
+fn snake_moves_in_current_direction() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5)], Direction::Right);
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(
+        controller.snake.elements,
+        vec![CanvasSpace((6, 5)), CanvasSpace((5, 5))]
+    );
+    assert_ne!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn the_countdown_sends_the_snake_off_away_from_its_tail() {
+    let mut controller = controller_with_snake(&[(5, 5), (5, 6), (5, 7)], Direction::Stop);
+    controller.ticks_per_sec = 4;
+    start_countdown(&mut controller);
+    assert_eq!(countdown_secs(&controller), COUNTDOWN_SECS);
+
+    for _ in 0..COUNTDOWN_SECS * 4 - 1 {
+        count_down(&mut controller);
+        assert_eq!(controller.screen, GameScreen::Countdown);
+    }
+    assert_eq!(countdown_secs(&controller), 1);
+
+    count_down(&mut controller);
+    assert_eq!(controller.screen, GameScreen::Playing);
+    assert_eq!(controller.snake.current_direction, Direction::Up);
+}
+
+#[test]
+fn turns_during_the_countdown_wait_for_the_first_tick() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Stop);
+    start_countdown(&mut controller);
+
+    press(&mut controller, KeyCode::Down);
+    press(&mut controller, KeyCode::Char(' '));
+    assert_eq!(controller.sprint_ticks, 0);
+    while controller.screen == GameScreen::Countdown {
+        count_down(&mut controller);
+    }
+
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (5, 6));
+}
+
+#[test]
+fn the_first_turn_of_the_countdown_picks_the_starting_direction() {
+    // Left is the opposite of the way this snake would set off on its own.
+    let mut controller = controller_with_snake(&[(6, 5)], Direction::Stop);
+    start_countdown(&mut controller);
+
+    press(&mut controller, KeyCode::Left);
+    while controller.screen == GameScreen::Countdown {
+        count_down(&mut controller);
+    }
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (5, 5));
+}
+
+#[test]
+fn snakes_dont_set_off_into_a_wall() {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(6, 6);
+    let single = controller_with_snake(&[(5, 2)], Direction::Stop).snake;
+    assert_eq!(starting_direction(&controller, &single), Direction::Left);
+
+    let against_wall = controller_with_snake(&[(5, 2), (4, 2)], Direction::Stop).snake;
+    assert_eq!(
+        starting_direction(&controller, &against_wall),
+        Direction::Up
+    );
+
+    // Off the edge is fine on a board that wraps around.
+    controller.wrap = true;
+    assert_eq!(
+        starting_direction(&controller, &against_wall),
+        Direction::Right
+    );
+}
+
+#[test]
+fn arrow_keys_turn_the_snake() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+
+    press(&mut controller, KeyCode::Up);
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.snake.current_direction, Direction::Up);
+    assert_eq!(head(&controller), (5, 4));
+}
+
+#[test]
+fn quick_turns_are_played_on_the_ticks_after() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5)], Direction::Right);
+
+    // All within one tick. Down would reverse the snake once it goes up, so it is dropped.
+    let (event_tx, event_rx) = mpsc::channel();
+    for code in [KeyCode::Up, KeyCode::Down, KeyCode::Left] {
+        event_tx.send(Event::Key(code.into())).unwrap();
+    }
+    handle_events(&mut controller, &event_rx);
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (5, 4));
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (4, 4));
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (3, 4));
+    assert!(controller.turns.is_empty());
+}
+
+#[test]
+fn snake_dies_at_the_wall() {
+    let max_x = canvas_max_x(CANVAS_WIDTH) as i32;
+    let mut controller = controller_with_snake(&[(max_x, 5)], Direction::Right);
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn eating_an_apple_grows_the_snake() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.score, 1);
+    assert_eq!(controller.snake.elements.len(), 3);
+    assert_eq!(controller.apples.len(), 1);
+}
+
+#[test]
+fn poison_shrinks_the_snake_and_costs_points() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5), (2, 5)], Direction::Right);
+    controller.score = 10;
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Poison)];
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.score, 10 - POISON_PENALTY);
+    assert_eq!(controller.snake.elements.len(), 4 - POISON_SEGMENTS);
+    assert_eq!(head(&controller), (6, 5));
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    // A snake that is only a head has nothing left to give.
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Poison)];
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Poisoned));
+}
+
+#[test]
+fn poison_rots_away_when_left_alone() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Down);
+    controller.bounds = CanvasBounds::new(10, 100);
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Poison)];
+
+    for _ in 1..POISON_TICKS {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.apples[0].apple_type, AppleType::Poison);
+
+    continue_game_logic(&mut controller);
+    assert!(controller
+        .apples
+        .iter()
+        .all(|apple| apple.position != CanvasSpace((0, 0)) || apple.age == 0));
+}
+
+#[test]
+fn every_fifth_apple_is_followed_by_a_golden_one() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.stats.apples_eaten = GOLDEN_EVERY - 1;
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+
+    // Eaten, flashed and replaced.
+    continue_game_logic(&mut controller);
+    continue_game_logic(&mut controller);
+    continue_game_logic(&mut controller);
+    let position = controller.apples[0].position.clone();
+    assert_eq!(controller.apples[0].apple_type, AppleType::Golden);
+    assert!(!controller.golden_due);
+    assert_eq!(golden_remaining_secs(&controller), Some(GOLDEN_SECONDS));
+
+    // Worth five apples when eaten right away.
+    let score = controller.score;
+    controller.snake.elements = vec![CanvasSpace((position.0 .0 - 1, position.0 .1))].into();
+    controller.snake.current_direction = Direction::Right;
+    controller.stats.time_since_apple = Duration::from_secs(60);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.score, score + GOLDEN_POINTS);
+}
+
+#[test]
+fn golden_apples_disappear_after_a_few_seconds() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Down);
+    controller.bounds = CanvasBounds::new(10, 100);
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Golden)];
+    let lifetime = GOLDEN_SECONDS * u32::from(TICKS_PER_SEC);
+
+    for _ in 1..lifetime {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(golden_remaining_secs(&controller), Some(1));
+
+    continue_game_logic(&mut controller);
+    assert_eq!(golden_remaining_secs(&controller), None);
+    assert!(controller
+        .apples
+        .iter()
+        .all(|apple| apple.apple_type != AppleType::Golden));
+}
+
+#[test]
+fn eaten_apple_flashes_for_one_tick() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.apples[0].flash_ticks, 1);
+    assert_eq!(controller.apples[0].position, CanvasSpace((6, 5)));
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.apples.len(), 1);
+    assert_eq!(controller.apples[0].flash_ticks, 0);
+    assert_eq!(controller.score, 1);
+}
+
+#[test]
+fn snake_dies_when_biting_itself() {
+    // Moving up from (5, 5) runs into the body at (5, 4).
+    let mut controller =
+        controller_with_snake(&[(5, 5), (6, 5), (6, 4), (5, 4), (4, 4)], Direction::Up);
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn sprint_moves_two_cells_per_tick() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+
+    press(&mut controller, KeyCode::Char(' '));
+    continue_game_logic(&mut controller);
+
+    assert_eq!(head(&controller), (7, 5));
+}
+
+#[test]
+fn sprint_stops_after_dying_on_the_first_step() {
+    let mut controller =
+        controller_with_snake(&[(5, 5), (6, 5), (6, 4), (5, 4), (4, 4)], Direction::Up);
+
+    press(&mut controller, KeyCode::Char(' '));
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(head(&controller), (5, 4));
+}
+
+#[test]
+fn canvas_bounds_saturate_on_tiny_canvases() {
+    assert_eq!(canvas_max_x(46), 21);
+    assert_eq!(canvas_max_y(46), 20);
+
+    for size in 0..6 {
+        assert_eq!(canvas_max_x(size), 0);
+    }
+    for size in 0..8 {
+        assert_eq!(canvas_max_y(size), 0);
+    }
+}
+
+#[test]
+fn random_cells_fit_tiny_canvases() {
+    let mut rng = GameRng::seed_from_u64(1);
+
+    for _ in 0..100 {
+        assert_eq!(random_cell(2, 2, &mut rng), CanvasSpace((0, 0)));
+        assert_eq!(random_cell(0, 0, &mut rng), CanvasSpace((0, 0)));
+    }
+}
+
+#[test]
+fn canvas_cells_map_to_two_terminal_columns() {
+    let bounds = CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT);
+    let origin = TerminalSpace::canvas_origin((100, 50), &bounds);
+    assert_eq!(origin, TerminalSpace((27, 14)));
+
+    assert_eq!(
+        TerminalSpace::from_canvas(&CanvasSpace((0, 0)), &origin),
+        TerminalSpace((28, 15))
+    );
+    assert_eq!(
+        TerminalSpace::from_canvas(&CanvasSpace((3, 2)), &origin),
+        TerminalSpace((34, 17))
+    );
+}
+
+#[test]
+fn fitted_boards_leave_room_for_the_border_and_the_hud() {
+    let bounds = CanvasBounds::fit_terminal((80, 24));
+    assert_eq!((bounds.width(), bounds.height()), (39, 20));
+
+    // One row for the score above the border, one for messages below it.
+    let origin = TerminalSpace::canvas_origin((80, 24), &bounds);
+    assert_eq!(origin, TerminalSpace((0, 1)));
+    assert_eq!(
+        TerminalSpace::from_canvas(&CanvasSpace((38, 19)), &origin),
+        TerminalSpace((77, 21))
+    );
+
+    let tiny = CanvasBounds::fit_terminal((3, 2));
+    assert_eq!((tiny.width(), tiny.height()), (1, 1));
+}
+
+#[test]
+fn boards_need_a_terminal_that_shows_the_score_above_them() {
+    let bounds = CanvasBounds::from_canvas_size(CANVAS_WIDTH, CANVAS_HEIGHT);
+    assert_eq!(bounds.terminal_size(), (46, 24));
+    assert_eq!(
+        TerminalSpace::canvas_origin((46, 24), &bounds),
+        TerminalSpace((0, 1))
+    );
+
+    for height in 1..30 {
+        let bounds = CanvasBounds::new(10, height);
+        let (columns, rows) = bounds.terminal_size();
+        let origin = TerminalSpace::canvas_origin((columns, rows), &bounds);
+        assert!(origin.0 .1 >= 1, "{}", height);
+        assert!(origin.0 .1 + height + 2 <= u32::from(rows), "{}", height);
+        assert!(
+            TerminalSpace::canvas_origin((columns, rows - 1), &bounds)
+                .0
+                 .1
+                < 1
+        );
+    }
+
+    // Fitted boards leave room for messages below them as well.
+    let (columns, rows) = CanvasBounds::fit_terminal((80, 24)).terminal_size();
+    assert!(columns <= 80 && rows <= 24);
+}
+
+#[test]
+fn length_two_snake_cannot_reverse_into_itself() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5)], Direction::Right);
+
+    press(&mut controller, KeyCode::Left);
+    continue_game_logic(&mut controller);
+
+    assert_ne!(controller.screen, GameScreen::GameOver);
+    assert_eq!(head(&controller), (6, 5));
+}
+
+#[test]
+fn turning_back_after_eating_hits_the_grown_body() {
+    let turn_back = |controller: &mut Controller| {
+        for code in &[KeyCode::Up, KeyCode::Left, KeyCode::Down] {
+            press(controller, *code);
+            continue_game_logic(controller);
+        }
+    };
+
+    // Four segments are just short enough to chase their own tail around a U-turn...
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5), (2, 5)], Direction::Right);
+    continue_game_logic(&mut controller);
+    turn_back(&mut controller);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    // ...but after eating, the tail stays put for a tick and the head runs into it.
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5), (2, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+    continue_game_logic(&mut controller);
+    park_apple(&mut controller);
+    assert_eq!(controller.snake.elements.len(), 5);
+
+    turn_back(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(head(&controller), (5, 5));
+}
+
+#[test]
+fn snake_can_circle_a_tight_loop() {
+    let mut controller = controller_with_snake(&[(5, 5), (6, 5), (6, 6), (5, 6)], Direction::Down);
+
+    for code in [KeyCode::Right, KeyCode::Up, KeyCode::Left, KeyCode::Down]
+        .iter()
+        .cycle()
+        .take(8)
+    {
+        continue_game_logic(&mut controller);
+        assert_ne!(controller.screen, GameScreen::GameOver);
+        press(&mut controller, *code);
+    }
+
+    assert_eq!(controller.snake.elements.len(), 4);
+}
+
+#[test]
+fn controller_displays_as_a_plain_text_board() {
+    let mut controller = controller_with_snake(&[(1, 0), (0, 0), (0, 1)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((3, 1)), AppleType::Fresh('🍎'))];
+
+    let board = controller.to_string();
+    let rows: Vec<&str> = board.lines().collect();
+
+    assert_eq!(rows.len(), canvas_max_y(CANVAS_HEIGHT) as usize + 1);
+    assert!(rows
+        .iter()
+        .all(|row| row.len() == canvas_max_x(CANVAS_WIDTH) as usize + 1));
+    assert!(rows[0].starts_with("SS....."));
+    assert!(rows[1].starts_with("S..A..."));
+    assert_eq!(board.matches('S').count(), 3);
+    assert!(rows[2].chars().all(|cell| cell == '.'));
+}
+
+#[test]
+fn controller_display_shows_every_entity() {
+    let mut controller = controller_with_snake(&[(0, 0)], Direction::Right);
+    controller.bounds = CanvasBounds::new(6, 2);
+    controller.apples = vec![Apple::new(CanvasSpace((1, 0)), AppleType::Poison)];
+    controller.obstacles = vec![CanvasSpace((2, 0))];
+    controller.portals = Some((CanvasSpace((3, 0)), CanvasSpace((3, 1))));
+    controller.pickup = Some((CanvasSpace((4, 0)), Effect::SpeedUp));
+    let second = Player::new(vec![CanvasSpace((0, 1)), CanvasSpace((1, 1))].into());
+    controller.second = Some(second);
+
+    assert_eq!(controller.to_string(), "SA#P*.\nss.P..\n");
+}
+
+#[test]
+fn head_can_walk_along_every_border_cell() {
+    let mut controller = controller_with_snake(&[(0, 0)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((5, 5)), AppleType::Fresh('🍎'))];
+    let (width, height) = (
+        controller.bounds.width() as i32,
+        controller.bounds.height() as i32,
+    );
+    let mut visited = vec![head(&controller)];
+
+    let legs = [
+        (KeyCode::Right, width - 1),
+        (KeyCode::Down, height - 1),
+        (KeyCode::Left, width - 1),
+        (KeyCode::Up, height - 1),
+    ];
+
+    for &(code, steps) in legs.iter() {
+        press(&mut controller, code);
+
+        for _ in 0..steps {
+            continue_game_logic(&mut controller);
+            assert_ne!(
+                controller.screen,
+                GameScreen::GameOver,
+                "died at {:?}",
+                head(&controller)
+            );
+            visited.push(head(&controller));
+        }
+    }
+
+    assert_eq!(head(&controller), (0, 0));
+    assert!(visited.contains(&(width - 1, 0)));
+    assert!(visited.contains(&(width - 1, height - 1)));
+    assert!(visited.contains(&(0, height - 1)));
+    assert_eq!(visited.len() as i32, 2 * (width + height) - 3);
+
+    // One more step leaves the board.
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn apples_only_spawn_inside_the_bounds() {
+    let bounds = CanvasBounds::new(3, 2);
+
+    assert_eq!(bounds.iter_cells().count(), 6);
+    assert!(bounds.iter_cells().all(|cell| bounds.contains(&cell)));
+    assert!(!bounds.contains(&CanvasSpace((3, 0))));
+    assert!(!bounds.contains(&CanvasSpace((0, 2))));
+
+    let mut rng = GameRng::seed_from_u64(2);
+    for _ in 0..1000 {
+        assert!(bounds.contains(&bounds.random_cell(&mut rng)));
+    }
+}
+
+#[test]
+fn score_saturates_and_shows_max() {
+    let mut controller = Controller::new();
+    controller.score = u32::MAX - 1;
+
+    add_score(&mut controller, 5);
+
+    assert_eq!(controller.score, u32::MAX);
+    assert_eq!(score_label(controller.score), "MAX!");
+    assert_eq!(score_label(42), "42");
+}
+
+#[test]
+fn game_logic_raises_cues() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+    // A random pickup on the way would raise a cue of its own.
+    controller.pickup = Some((CanvasSpace((0, 0)), Effect::SlowDown));
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.cues, vec![Cue::AppleEaten]);
+    controller.cues.clear();
+    park_apple(&mut controller);
+
+    press(&mut controller, KeyCode::Up);
+    for _ in 0..6 {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.cues, vec![Cue::GameOver]);
+}
+
+#[test]
+fn golden_apples_ring_like_a_bonus() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Golden)];
+    controller.pickup = Some((CanvasSpace((0, 0)), Effect::SlowDown));
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.cues, vec![Cue::BonusCollected]);
+}
+
+#[test]
+fn stats_record_apples_and_cause_of_death() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.apples_eaten, 1);
+    assert_eq!(controller.stats.ticks_since_apple, 0);
+
+    park_apple(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.ticks, 2);
+    assert_eq!(controller.stats.ticks_since_apple, 1);
+
+    let mut controller =
+        controller_with_snake(&[(5, 5), (5, 6), (6, 6), (6, 5), (6, 4)], Direction::Right);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.death, Some(Death::SelfCollision));
+
+    let mut controller = controller_with_snake(&[(0, 5)], Direction::Left);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.death, Some(Death::Wall));
+}
+
+#[test]
+fn any_direction_starts_a_resting_snake() {
+    for (code, direction) in &[
+        (KeyCode::Up, Direction::Up),
+        (KeyCode::Down, Direction::Down),
+        (KeyCode::Left, Direction::Left),
+        (KeyCode::Right, Direction::Right),
+    ] {
+        let mut controller = controller_with_snake(&[(5, 5), (5, 6)], Direction::Stop);
+
+        press(&mut controller, *code);
+        continue_game_logic(&mut controller);
+
+        assert_eq!(&controller.snake.current_direction, direction);
+    }
+}
+
+#[test]
+fn obstacles_spawn_on_every_kth_point_on_free_cells() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.obstacles_every = Some(2);
+
+    maybe_spawn_obstacle(&mut controller);
+    assert!(controller.obstacles.is_empty());
+
+    controller.score = 1;
+    maybe_spawn_obstacle(&mut controller);
+    assert!(controller.obstacles.is_empty());
+
+    controller.score = 2;
+    maybe_spawn_obstacle(&mut controller);
+    assert_eq!(controller.obstacles.len(), 1);
+    assert!(!controller.snake.elements.contains(&controller.obstacles[0]));
+    assert_ne!(controller.obstacles[0], CanvasSpace((0, 0)));
+}
+
+#[test]
+fn obstacles_stop_at_eighty_percent_coverage() {
+    let mut controller = controller_with_snake(&[(0, 1)], Direction::Right);
+    controller.bounds = CanvasBounds::new(5, 2);
+    controller.obstacles_every = Some(1);
+    controller.score = 1;
+
+    for _ in 0..20 {
+        maybe_spawn_obstacle(&mut controller);
+    }
+
+    assert_eq!(
+        controller.obstacles.len() + controller.snake.elements.len(),
+        8
+    );
+}
+
+#[test]
+fn hitting_an_obstacle_ends_the_run() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.obstacles.push(CanvasSpace((6, 5)));
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Obstacle));
+}
+
+#[test]
+fn invincible_snakes_pass_through_obstacles_and_themselves() {
+    // Heading up into its own body, with an obstacle behind that.
+    let mut controller =
+        controller_with_snake(&[(5, 5), (6, 5), (6, 4), (5, 4), (4, 4)], Direction::Up);
+    controller.obstacles.push(CanvasSpace((5, 3)));
+    effects::apply_effect(
+        &mut controller.active_effects,
+        Effect::Invincible,
+        TICKS_PER_SEC,
+    );
+
+    continue_game_logic(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (5, 3));
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    // The edge of the board still ends the run.
+    controller.snake.elements = vec![CanvasSpace((5, 0))].into();
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Wall));
+}
+
+#[test]
+fn filling_the_board_wins_the_game() {
+    let mut controller = controller_with_snake(&[(0, 0)], Direction::Right);
+    controller.bounds = CanvasBounds::new(3, 1);
+    controller.apples = vec![Apple::new(CanvasSpace((1, 0)), AppleType::Fresh('🍎'))];
+
+    continue_game_logic(&mut controller);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    controller.apples = vec![Apple::new(CanvasSpace((2, 0)), AppleType::Fresh('🍎'))];
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.snake.elements.len(), 3);
+    assert!(controller.won);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, None);
+    assert!(controller.cues.contains(&Cue::Victory));
+}
+
+#[test]
+fn new_head_is_one_step_ahead_or_none_at_the_wall() {
+    let bounds = CanvasBounds::new(3, 3);
+    let mut controller = controller_with_snake(&[(1, 0)], Direction::Right);
+
+    assert_eq!(
+        compute_new_head(&controller.snake, &bounds),
+        Some(CanvasSpace((2, 0)))
+    );
+
+    controller.snake.current_direction = Direction::Up;
+    assert_eq!(compute_new_head(&controller.snake, &bounds), None);
+
+    controller.snake.elements[0] = CanvasSpace((2, 0));
+    controller.snake.current_direction = Direction::Right;
+    assert_eq!(compute_new_head(&controller.snake, &bounds), None);
+}
+
+#[test]
+fn elapsed_time_follows_the_tick_length() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Stop);
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.elapsed, Duration::ZERO);
+
+    controller.snake.current_direction = Direction::Right;
+    continue_game_logic(&mut controller);
+    assert_eq!(
+        controller.stats.elapsed,
+        effects::tick_interval(TICKS_PER_SEC, &[])
+    );
+
+    effects::apply_effect(
+        &mut controller.active_effects,
+        Effect::SpeedUp,
+        TICKS_PER_SEC,
+    );
+    let sped_up = effects::tick_interval(TICKS_PER_SEC, &controller.active_effects);
+    continue_game_logic(&mut controller);
+    assert_eq!(
+        controller.stats.elapsed,
+        effects::tick_interval(TICKS_PER_SEC, &[]) + sped_up
+    );
+    assert!(sped_up < effects::tick_interval(TICKS_PER_SEC, &[]));
+}
+
+#[test]
+fn time_label_shows_minutes_and_seconds() {
+    assert_eq!(time_label(Duration::from_millis(59_900)), "00:59");
+    assert_eq!(time_label(Duration::from_secs(754)), "12:34");
+    assert_eq!(time_label(Duration::from_secs(6000)), "100:00");
+}
+
+// Eats an apple placed `gap` cells ahead of the head of a snake moving right, one per tick.
+fn eat_apple_after(controller: &mut Controller, gap: i32) {
+    let (x, y) = head(controller);
+    controller.apples = vec![Apple::new(
+        CanvasSpace((x + gap, y)),
+        AppleType::Fresh('🍎'),
+    )];
+
+    for _ in 0..gap {
+        continue_game_logic(controller);
+    }
+}
+
+fn combo_controller() -> Controller {
+    let mut controller = controller_with_snake(&[(0, 5)], Direction::Right);
+    // Wide enough for a long gap between apples.
+    controller.bounds = CanvasBounds::new(64, 10);
+    // Keep random pickups and their point bonuses out of the way.
+    controller.pickup = Some((CanvasSpace((0, 0)), Effect::SlowDown));
+    controller
+}
+
+#[test]
+fn quick_second_apple_doubles_its_points() {
+    let mut controller = combo_controller();
+
+    eat_apple_after(&mut controller, 1);
+    assert_eq!(controller.score, 1);
+    assert_eq!(controller.combo, 1);
+
+    eat_apple_after(&mut controller, 10);
+    assert_eq!(controller.combo, 2);
+    assert_eq!(controller.score, 3);
+    assert_eq!(controller.stats.best_combo, 2);
+}
+
+#[test]
+fn combo_resets_after_the_window_lapses() {
+    let mut controller = combo_controller();
+
+    eat_apple_after(&mut controller, 1);
+    eat_apple_after(&mut controller, 5);
+    assert_eq!(controller.combo, 2);
+
+    eat_apple_after(&mut controller, 31);
+    assert_eq!(controller.combo, 1);
+    assert_eq!(controller.score, 1 + 2 + 1);
+    assert_eq!(controller.stats.best_combo, 2);
+}
+
+#[test]
+fn next_apple_spawns_on_the_only_free_cell() {
+    let mut controller = controller_with_snake(
+        &[
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+        ],
+        Direction::Stop,
+    );
+    controller.bounds = CanvasBounds::new(3, 3);
+    controller.apples.clear();
+    controller.spawn_apple_next_tick = true;
+
+    continue_game_logic(&mut controller);
+
+    assert!(!controller.spawn_apple_next_tick);
+    assert_eq!(controller.apples[0].position, CanvasSpace((2, 2)));
+}
+
+#[test]
+fn eaten_apple_is_replaced_on_the_following_tick() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+
+    continue_game_logic(&mut controller);
+    assert!(!controller.spawn_apple_next_tick);
+
+    continue_game_logic(&mut controller);
+    let apple = &controller.apples[0].position;
+    assert_ne!(*apple, CanvasSpace((6, 5)));
+    assert!(!controller.snake.elements.contains(apple));
+}
+
+fn click(controller: &mut Controller, column: u16, row: u16) {
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx
+        .send(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
+        .unwrap();
+    handle_events(controller, &event_rx);
+}
+
+#[test]
+fn click_direction_follows_the_larger_axis() {
+    let origin = TerminalSpace((10, 5));
+    // The head's cell covers columns 21 and 22 of row 11.
+    let head = CanvasSpace((5, 5));
+
+    assert_eq!(click_direction(&head, (22, 11), &origin), None);
+    assert_eq!(
+        click_direction(&head, (30, 13), &origin),
+        Some(Direction::Right)
+    );
+    assert_eq!(
+        click_direction(&head, (19, 11), &origin),
+        Some(Direction::Left)
+    );
+    assert_eq!(
+        click_direction(&head, (23, 2), &origin),
+        Some(Direction::Up)
+    );
+    assert_eq!(
+        click_direction(&head, (0, 40), &origin),
+        Some(Direction::Down)
+    );
+}
+
+#[test]
+fn clicks_only_steer_with_mouse_enabled() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+
+    click(&mut controller, 22, 0);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.current_direction, Direction::Right);
+
+    controller.mouse_origin = Some(TerminalSpace((0, 0)));
+    click(&mut controller, 14, 0);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.current_direction, Direction::Up);
+}
+
+#[test]
+fn a_following_snake_heads_for_the_mouse_cursor() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5)], Direction::Right);
+    controller.mouse_origin = Some(TerminalSpace((0, 0)));
+    controller.follow_cursor = true;
+
+    // Up and to the left. Left would reverse the snake, so it goes up first.
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx
+        .send(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 1,
+            row: 4,
+            modifiers: KeyModifiers::NONE,
+        }))
+        .unwrap();
+    handle_events(&mut controller, &event_rx);
+    assert_eq!(controller.cursor, Some((1, 4)));
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (5, 4));
+
+    take_buffered_turn(&mut controller);
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (4, 4));
+}
+
+fn cells(elements: &VecDeque<CanvasSpace>) -> Vec<(i32, i32)> {
+    elements.iter().map(|cell| cell.0).collect()
+}
+
+#[test]
+fn starting_snake_trails_behind_the_head_where_there_is_room() {
+    let bounds = CanvasBounds::new(5, 5);
+
+    let middle = starting_snake(&bounds, &[], &CanvasSpace((2, 2)), 3).unwrap();
+    assert_eq!(cells(&middle), vec![(2, 2), (1, 2), (0, 2)]);
+
+    let left_edge = starting_snake(&bounds, &[], &CanvasSpace((0, 0)), 3).unwrap();
+    assert_eq!(cells(&left_edge), vec![(0, 0), (1, 0), (2, 0)]);
+
+    let blocked = [CanvasSpace((3, 4))];
+    let corner = starting_snake(&bounds, &blocked, &CanvasSpace((4, 4)), 3).unwrap();
+    assert_eq!(cells(&corner), vec![(4, 4), (4, 3), (4, 2)]);
+
+    assert_eq!(starting_snake(&bounds, &[], &CanvasSpace((0, 0)), 6), None);
+}
+
+#[test]
+fn placing_the_snake_rejects_starts_off_the_board() {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(5, 5);
+
+    assert!(place_snake(&mut controller, &StartPosition::At(CanvasSpace((5, 0))), 1).is_err());
+    assert!(place_snake(&mut controller, &StartPosition::Center, 6).is_err());
+
+    place_snake(&mut controller, &StartPosition::Random, 5).unwrap();
+    assert_eq!(controller.snake.elements.len(), 5);
+    assert!(controller
+        .snake
+        .elements
+        .iter()
+        .all(|cell| controller.bounds.contains(cell)));
+
+    assert_eq!("3,4".parse(), Ok(StartPosition::At(CanvasSpace((3, 4)))));
+    assert!("3".parse::<StartPosition>().is_err());
+}
+
+#[test]
+fn first_move_cannot_turn_into_the_body() {
+    let mut controller = controller_with_snake(&[(2, 2), (1, 2), (0, 2)], Direction::Stop);
+
+    press(&mut controller, KeyCode::Left);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.current_direction, Direction::Stop);
+
+    press(&mut controller, KeyCode::Up);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.current_direction, Direction::Up);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn spawned_apples_stay_on_the_board() {
+    let mut controller = Controller::new();
+
+    for _ in 0..10_000 {
+        controller.apples.clear();
+        controller.spawn_apple_next_tick = true;
+        continue_game_logic(&mut controller);
+
+        let (x, y) = controller.apples[0].position.0;
+        assert!(x >= 0 && x as u32 <= canvas_max_x(CANVAS_WIDTH));
+        assert!(y >= 0 && y as u32 <= canvas_max_y(CANVAS_HEIGHT));
+    }
+}
+
+#[test]
+fn a_plain_tick_only_moves_the_snake() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5)], Direction::Right);
+    controller.spawn_apple_next_tick = false;
+    let before = controller.clone();
+
+    continue_game_logic(&mut controller);
+
+    let mut expected = before;
+    expected.snake.elements = vec![CanvasSpace((6, 5)), CanvasSpace((5, 5))].into();
+    expected.stats.ticks = 1;
+    expected.stats.ticks_since_apple = 1;
+    expected.apples[0].age = 1;
+    expected.stats.elapsed = effects::tick_interval(TICKS_PER_SEC, &[]);
+    expected.stats.time_since_apple = effects::tick_interval(TICKS_PER_SEC, &[]);
+    assert_eq!(controller, expected);
+}
+
+#[test]
+fn eating_changes_exactly_the_expected_state() {
+    let mut controller = controller_with_snake(&[(5, 5)], Direction::Right);
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+    controller.pickup = Some((CanvasSpace((0, 0)), Effect::SlowDown));
+    controller.spawn_apple_next_tick = false;
+    let before = controller.clone();
+
+    continue_game_logic(&mut controller);
+
+    let mut expected = before;
+    expected.snake.elements = vec![CanvasSpace((6, 5)), CanvasSpace((5, 5))].into();
+    expected.score = 1;
+    expected.cues = vec![Cue::AppleEaten];
+    expected.stats.ticks = 1;
+    expected.stats.elapsed = effects::tick_interval(TICKS_PER_SEC, &[]);
+    expected.stats.apples_eaten = 1;
+    expected.stats.best_combo = 1;
+    expected.apples[0].age = 1;
+    expected.apples[0].flash_ticks = 1;
+    assert_eq!(controller, expected);
+}
+
+fn hungry_controller(elements: &[(i32, i32)]) -> Controller {
+    let mut controller = controller_with_snake(elements, Direction::Right);
+    controller.bounds = CanvasBounds::new(200, 10);
+    controller.game_mode = GameMode::Hunger;
+    controller.pickup = Some((CanvasSpace((0, 0)), Effect::SlowDown));
+    controller
+}
+
+#[test]
+fn hungry_snake_shrinks_and_starves() {
+    let mut controller = hungry_controller(&[(2, 5), (1, 5)]);
+    controller.score = 5;
+
+    for _ in 0..HUNGER_TICKS {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.snake.elements.len(), 1);
+    assert_eq!(controller.score, 4);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    for _ in 0..HUNGER_TICKS {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Starved));
+    assert_eq!(controller.cues, vec![Cue::GameOver]);
+}
+
+#[test]
+fn eating_resets_the_hunger_timer() {
+    let mut controller = hungry_controller(&[(2, 5), (1, 5)]);
+
+    for _ in 0..HUNGER_TICKS - 1 {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(ticks_until_hungry(&controller), 1);
+
+    eat_apple_after(&mut controller, 1);
+    assert_eq!(ticks_until_hungry(&controller), HUNGER_TICKS);
+    assert_eq!(controller.snake.elements.len(), 3);
+}
+
+#[test]
+fn snake_passes_through_a_portal() {
+    let mut controller =
+        controller_with_snake(&[(4, 5), (3, 5), (2, 5), (1, 5), (0, 5)], Direction::Right);
+    controller.portals = Some((CanvasSpace((5, 5)), CanvasSpace((10, 2))));
+
+    continue_game_logic(&mut controller);
+    assert_eq!(
+        cells(&controller.snake.elements),
+        vec![(10, 2), (4, 5), (3, 5), (2, 5), (1, 5)]
+    );
+
+    for _ in 0..4 {
+        continue_game_logic(&mut controller);
+    }
+    assert_ne!(controller.screen, GameScreen::GameOver);
+    assert_eq!(
+        cells(&controller.snake.elements),
+        vec![(14, 2), (13, 2), (12, 2), (11, 2), (10, 2)]
+    );
+}
+
+#[test]
+fn portal_exit_blocked_by_the_body_bites() {
+    let mut controller = controller_with_snake(&[(5, 5), (5, 6), (5, 7), (5, 8)], Direction::Right);
+    controller.portals = Some((CanvasSpace((6, 5)), CanvasSpace((5, 7))));
+
+    continue_game_logic(&mut controller);
+
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::SelfCollision));
+}
+
+#[test]
+fn capped_snake_stops_growing_and_scores_double() {
+    let mut controller = combo_controller();
+    controller.max_length = Some(2);
+
+    eat_apple_after(&mut controller, 1);
+    assert_eq!(controller.snake.elements.len(), 2);
+    assert_eq!(controller.score, 1);
+
+    // Far enough apart that no combo applies.
+    eat_apple_after(&mut controller, 35);
+    assert_eq!(controller.snake.elements.len(), 2);
+    assert_eq!(controller.score, 3);
+}
+
+#[test]
+fn rejects_boards_without_room_for_snake_and_apple() {
+    assert_eq!(validate_game_config(&GameConfig::default()), Ok(()));
+
+    let tiny = GameConfig {
+        bounds: CanvasBounds::new(1, 1),
+        ..GameConfig::default()
+    };
+    assert!(validate_game_config(&tiny).is_err());
+
+    let crowded = GameConfig {
+        bounds: CanvasBounds::new(3, 1),
+        start_length: 1,
+        obstacles: vec![CanvasSpace((2, 0)), CanvasSpace((2, 0))],
+        ..GameConfig::default()
+    };
+    assert!(validate_game_config(&crowded).is_err());
+
+    let roomy = GameConfig {
+        bounds: CanvasBounds::new(4, 1),
+        ..crowded
+    };
+    assert_eq!(validate_game_config(&roomy), Ok(()));
+}
+
+#[test]
+fn cells_off_the_board_are_negative_not_wrapped() {
+    let bounds = CanvasBounds::new(10, 10);
+    assert!(!bounds.contains(&CanvasSpace((-1, 0))));
+    assert!(!bounds.contains(&CanvasSpace((0, -1))));
+    assert!(!bounds.contains(&CanvasSpace((10, 0))));
+    assert!(bounds.contains(&CanvasSpace((9, 9))));
+
+    let mut controller = Controller::new();
+    let start = "-1,3".parse::<StartPosition>().unwrap();
+    assert!(place_snake(&mut controller, &start, 1).is_err());
+}
+
+#[test]
+fn the_same_seed_spawns_the_same_apples() {
+    let apples = |seed: u64| {
+        let mut controller = Controller::new();
+        controller.rng = GameRng::seed_from_u64(seed);
+        place_snake(&mut controller, &StartPosition::Random, 3).unwrap();
+
+        let mut apples = vec![controller.snake.elements[0].clone()];
+        for _ in 0..20 {
+            controller.apples.clear();
+            controller.spawn_apple_next_tick = true;
+            continue_game_logic(&mut controller);
+            apples.push(controller.apples[0].position.clone());
+        }
+        apples
+    };
+
+    assert_eq!(apples(42), apples(42));
+    assert_ne!(apples(42), apples(43));
+}
+
+#[test]
+fn several_apples_fill_up_and_are_replaced_one_at_a_time() {
+    let mut controller = controller_with_snake(&[(5, 5), (4, 5)], Direction::Right);
+    controller.apple_count = 3;
+    controller.apples = vec![Apple::new(CanvasSpace((6, 5)), AppleType::Fresh('🍎'))];
+    controller.spawn_apple_next_tick = true;
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.apples.len(), 3);
+    assert_eq!(controller.score, 1);
+    let others: Vec<CanvasSpace> = controller.apples[1..]
+        .iter()
+        .map(|apple| apple.position.clone())
+        .collect();
+    assert_ne!(others[0], others[1]);
+    assert!(others
+        .iter()
+        .all(|apple| !controller.snake.elements.contains(apple)));
+
+    // The eaten apple goes when its flash ends, and only it is replaced.
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.apples.len(), 3);
+    assert!(controller
+        .apples
+        .iter()
+        .all(|apple| apple.position != CanvasSpace((6, 5))));
+    for other in &others {
+        assert!(controller
+            .apples
+            .iter()
+            .any(|apple| &apple.position == other));
+    }
+}
+
+#[test]
+fn wrapping_brings_the_snake_back_in_on_the_other_side() {
+    let mut controller = controller_with_snake(&[(0, 5), (1, 5)], Direction::Left);
+    controller.bounds = CanvasBounds::new(10, 10);
+    controller.wrap = true;
+
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (9, 5));
+    assert_eq!(controller.screen, GameScreen::Playing);
+
+    controller.snake.elements = vec![CanvasSpace((3, 0))].into();
+    controller.snake.current_direction = Direction::Up;
+    continue_game_logic(&mut controller);
+    assert_eq!(head(&controller), (3, 9));
+}
+
+#[test]
+fn the_border_closes_in_and_crushes_what_it_catches() {
+    let mut controller = controller_with_snake(&[(10, 10), (9, 10)], Direction::Right);
+    controller.bounds = CanvasBounds::new(20, 20);
+    controller.game_mode = GameMode::Shrink;
+    controller.stats.elapsed = Duration::from_secs(SHRINK_SECONDS) - tick();
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.bounds.inset(), 1);
+    assert_eq!(controller.bounds.open_size(), (18, 18));
+    assert_eq!(controller.score, 1);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+    // The parked apple was in the corner that just closed.
+    assert!(controller.apples.is_empty());
+    assert!(!controller.bounds.contains(&CanvasSpace((0, 0))));
+    assert_eq!(controller.bounds.iter_cells().count(), 18 * 18);
+
+    continue_game_logic(&mut controller);
+    assert!(controller
+        .apples
+        .iter()
+        .all(|apple| controller.bounds.contains(&apple.position)));
+
+    let mut controller = controller_with_snake(&[(1, 5), (1, 4)], Direction::Down);
+    controller.bounds = CanvasBounds::new(20, 20);
+    controller.game_mode = GameMode::Shrink;
+    controller.stats.elapsed = Duration::from_secs(SHRINK_SECONDS * 2) - tick();
+    controller.bounds = controller.bounds.shrunk();
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Crushed));
+}
+
+#[test]
+fn surviving_a_shrinking_arena_saturates_the_score() {
+    let mut controller = controller_with_snake(&[(10, 10), (9, 10)], Direction::Right);
+    controller.bounds = CanvasBounds::new(20, 20);
+    controller.game_mode = GameMode::Shrink;
+    controller.stats.elapsed = Duration::from_secs(1) - tick();
+    controller.score = u32::MAX;
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.score, u32::MAX);
+}
+
+#[test]
+fn a_faster_game_does_not_shrink_the_arena_sooner() {
+    let mut controller = controller_with_snake(&[(2, 10), (1, 10)], Direction::Right);
+    controller.bounds = CanvasBounds::new(40, 20);
+    controller.game_mode = GameMode::Shrink;
+    // Twice the default speed, for the whole second left before the border closes.
+    controller.ramp = Some(Ramp::new(Curve::Linear));
+    controller.score = u32::from(TICKS_PER_SEC) * RAMP_EVERY;
+    controller.stats.elapsed = Duration::from_secs(SHRINK_SECONDS - 1);
+
+    for _ in 1..2 * TICKS_PER_SEC {
+        continue_game_logic(&mut controller);
+    }
+    assert_eq!(controller.bounds.inset(), 0);
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.bounds.inset(), 1);
+    assert_eq!(
+        controller.stats.elapsed,
+        Duration::from_secs(SHRINK_SECONDS)
+    );
+}
+
+fn two_players(first: &[(i32, i32)], second: &[(i32, i32)]) -> Controller {
+    let mut controller = controller_with_snake(first, Direction::Right);
+    controller.second = Some(Player::new(
+        second.iter().map(|&cell| CanvasSpace(cell)).collect(),
+    ));
+    controller
+}
+
+#[test]
+fn wasd_steers_the_second_snake_and_arrows_the_first() {
+    let mut controller = two_players(&[(5, 5), (4, 5)], &[(5, 15), (4, 15)]);
+    controller.snake.current_direction = Direction::Stop;
+
+    press(&mut controller, KeyCode::Char('w'));
+    press(&mut controller, KeyCode::Down);
+    continue_game_logic(&mut controller);
+
+    assert_eq!(head(&controller), (5, 6));
+    let second = controller.second.as_ref().unwrap();
+    assert_eq!(second.snake.elements[0], CanvasSpace((5, 14)));
+    assert_eq!(second.snake.current_direction, Direction::Up);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+}
+
+#[test]
+fn running_into_the_other_snake_loses() {
+    let mut controller = two_players(&[(5, 5), (4, 5)], &[(6, 7), (6, 8)]);
+    controller.second.as_mut().unwrap().snake.current_direction = Direction::Up;
+
+    continue_game_logic(&mut controller);
+    assert_ne!(controller.screen, GameScreen::GameOver);
+
+    // The second snake moves up into the first one's tail at (6, 5).
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(
+        controller.second.as_ref().unwrap().death,
+        Some(Death::Rival)
+    );
+    assert_eq!(controller.stats.death, None);
+    assert_eq!(winner(&controller), Some(1));
+}
+
+#[test]
+fn a_head_on_collision_goes_to_the_higher_score() {
+    let mut controller = two_players(&[(5, 5), (4, 5)], &[(7, 5), (8, 5)]);
+    controller.second.as_mut().unwrap().snake.current_direction = Direction::Left;
+    controller.second.as_mut().unwrap().score = 3;
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.screen, GameScreen::GameOver);
+    assert_eq!(controller.stats.death, Some(Death::Rival));
+    assert_eq!(winner(&controller), Some(2));
+
+    controller.score = 3;
+    assert_eq!(winner(&controller), None);
+}
@@ -0,0 +1,61 @@
+use std::fs;
+
+use rusty_snake::ghost::Ghost;
+use rusty_snake::{continue_game_logic, Apple, AppleType, CanvasSpace, Controller, Direction};
+
+fn moving_controller() -> Controller {
+    let mut controller = Controller::new();
+    controller.snake.elements = vec![CanvasSpace((5, 5))].into_iter().collect();
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Fresh('🍎'))];
+    controller
+}
+
+#[test]
+fn ghost_follows_the_recorded_moves_and_stays_at_the_end() {
+    let mut controller = moving_controller();
+    let mut ghost = Ghost::default();
+
+    // Ticks spent waiting for the first key press aren't recorded.
+    continue_game_logic(&mut controller);
+    ghost.record(&controller);
+    assert!(ghost.frames.is_empty());
+
+    controller.snake.current_direction = Direction::Right;
+    for _ in 0..3 {
+        continue_game_logic(&mut controller);
+        ghost.record(&controller);
+    }
+    assert_eq!(ghost.frames.len(), 3);
+
+    let mut live = moving_controller();
+    assert_eq!(ghost.snake_at(&live), None);
+
+    live.stats.ticks = 2;
+    assert_eq!(ghost.snake_at(&live), Some(&[CanvasSpace((7, 5))][..]));
+
+    live.stats.ticks = 10;
+    assert_eq!(ghost.snake_at(&live), Some(&[CanvasSpace((8, 5))][..]));
+}
+
+#[test]
+fn ghost_round_trips_through_a_file() {
+    let mut controller = moving_controller();
+    controller.snake.current_direction = Direction::Down;
+    controller.score = 3;
+
+    let mut ghost = Ghost::default();
+    for _ in 0..2 {
+        continue_game_logic(&mut controller);
+        ghost.record(&controller);
+    }
+
+    let path = std::env::temp_dir().join(format!("rusty-snake-ghost-{}.txt", std::process::id()));
+    ghost.save(&path).unwrap();
+
+    let loaded = Ghost::load(&path).unwrap().unwrap();
+    assert_eq!(loaded, ghost);
+    assert_eq!(loaded.score(), 3);
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(Ghost::load(&path).unwrap(), None);
+}
@@ -0,0 +1,117 @@
+use rusty_snake::effects::Effect;
+use rusty_snake::glyphs::{
+    display_width, unicode_locale, RenderConfig, ASCII, DEFAULT_SNAKE, GRID_SHADES, UNICODE,
+};
+use rusty_snake::{AppleType, CanvasSpace, Direction};
+
+#[test]
+fn display_width_counts_terminal_columns() {
+    assert_eq!(display_width("██"), 2);
+    assert_eq!(display_width("🍎"), 2);
+    assert_eq!(display_width("⚡"), 2);
+    assert_eq!(display_width("##"), 2);
+    assert_eq!(display_width("蛇"), 2);
+    assert_eq!(display_width("e\u{301}e\u{301}"), 2);
+    assert_eq!(display_width("#"), 1);
+    assert_eq!(display_width("🍎🍎"), 4);
+}
+
+#[test]
+fn render_config_defaults_to_the_classic_glyphs() {
+    let config = RenderConfig::new(None, None).unwrap();
+
+    assert_eq!(config.snake, DEFAULT_SNAKE);
+    assert_eq!(config.apple, None);
+    assert_eq!(config, RenderConfig::default());
+}
+
+#[test]
+fn render_config_rejects_glyphs_of_the_wrong_width() {
+    assert!(RenderConfig::new(Some(String::from("[]")), Some(String::from("()"))).is_ok());
+    assert!(RenderConfig::new(Some(String::from("#")), None).is_err());
+    assert!(RenderConfig::new(None, Some(String::from("🍎🍏"))).is_err());
+}
+
+#[test]
+fn grid_shades_alternate_per_cell() {
+    assert_eq!(UNICODE.grid_shade(&CanvasSpace((0, 0))), GRID_SHADES[0]);
+    assert_eq!(UNICODE.grid_shade(&CanvasSpace((1, 0))), GRID_SHADES[1]);
+    assert_eq!(UNICODE.grid_shade(&CanvasSpace((1, 1))), GRID_SHADES[0]);
+    assert!(GRID_SHADES.iter().all(|shade| display_width(shade) == 2));
+}
+
+#[test]
+fn the_ascii_charset_fills_whole_cells_with_ascii() {
+    let cells = [
+        ASCII.snake,
+        ASCII.obstacle,
+        ASCII.no_apples,
+        ASCII.portal,
+        ASCII.grid[0],
+        ASCII.grid[1],
+        &ASCII.apple(AppleType::Fresh('🍎')),
+        &ASCII.apple(AppleType::Poison),
+        &ASCII.apple(AppleType::Golden),
+        &ASCII.effect(Effect::SpeedUp),
+    ];
+
+    for cell in &cells {
+        assert!(cell.is_ascii(), "{:?}", cell);
+        assert_eq!(display_width(cell), 2, "{:?}", cell);
+    }
+    assert!(ASCII.border.iter().all(char::is_ascii));
+    assert!(ASCII.logo.is_ascii());
+    assert_eq!(ASCII.apple(AppleType::Fresh('🍏')), "@ ");
+    assert_eq!(UNICODE.apple(AppleType::Fresh('🍏')), "🍏");
+}
+
+#[test]
+fn colorblind_glyphs_tell_snakes_and_apples_apart_by_shape() {
+    for charset in &[UNICODE, ASCII] {
+        let [first, second] = charset.patterns;
+        assert!(first.iter().all(|glyph| !second.contains(glyph)));
+
+        let apples = [
+            charset.marked_apple(AppleType::Fresh('🍎')),
+            charset.marked_apple(AppleType::Poison),
+            charset.marked_apple(AppleType::Golden),
+        ];
+        assert_eq!(charset.marked_apple(AppleType::Fresh('🍏')), apples[0]);
+        assert!(apples[0] != apples[1] && apples[1] != apples[2] && apples[0] != apples[2]);
+
+        for glyph in first.iter().chain(&second).chain(&apples) {
+            assert_eq!(display_width(glyph), 2, "{:?}", glyph);
+        }
+    }
+}
+
+#[test]
+fn heads_point_where_the_snake_is_going() {
+    assert_eq!(UNICODE.head(Direction::Up), Some("▲▲"));
+    assert_eq!(ASCII.head(Direction::Right), Some("#>"));
+    assert_eq!(UNICODE.head(Direction::Stop), None);
+
+    for charset in &[UNICODE, ASCII] {
+        for head in &charset.heads {
+            assert_eq!(display_width(head), 2, "{:?}", head);
+        }
+    }
+}
+
+#[test]
+fn bars_fill_up_to_the_fraction() {
+    assert_eq!(ASCII.bar(0.5, 4), "##--");
+    assert_eq!(ASCII.bar(0.1, 4), "#---");
+    assert_eq!(UNICODE.bar(2.0, 3), "███");
+    assert_eq!(UNICODE.bar(0.0, 2), "░░");
+}
+
+#[test]
+fn only_utf8_locales_show_unicode() {
+    assert!(unicode_locale(None, None, Some("en_US.UTF-8")));
+    assert!(unicode_locale(None, Some("C.utf8"), None));
+    assert!(!unicode_locale(None, None, Some("C")));
+    assert!(!unicode_locale(Some("POSIX"), None, Some("en_US.UTF-8")));
+    assert!(unicode_locale(Some(""), None, Some("de_DE.UTF-8")));
+    assert!(unicode_locale(None, None, None));
+}
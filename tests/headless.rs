@@ -0,0 +1,49 @@
+use rusty_snake::achievements::Death;
+use rusty_snake::headless::{GameState, StepResult};
+use rusty_snake::{Apple, AppleType, CanvasBounds, CanvasSpace, Controller, Direction};
+
+fn game_on_a_strip(width: u32) -> GameState {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(width, 1);
+    controller.snake.elements = vec![CanvasSpace((0, 0))].into_iter().collect();
+    controller.apples = vec![Apple::new(CanvasSpace((2, 0)), AppleType::Fresh('🍎'))];
+    controller.spawn_apple_next_tick = false;
+    controller.pickup = None;
+    GameState::new(controller)
+}
+
+#[test]
+fn steps_report_moves_apples_and_the_end_of_the_run() {
+    let mut game = game_on_a_strip(8);
+
+    assert_eq!(game.step(None), StepResult::Moved);
+    assert_eq!(game.controller.snake.elements[0], CanvasSpace((0, 0)));
+
+    assert_eq!(game.step(Some(Direction::Right)), StepResult::Moved);
+    assert_eq!(game.step(None), StepResult::AteApple);
+    assert_eq!(game.controller.score, 1);
+
+    let mut result = StepResult::Moved;
+    for _ in 0..20 {
+        result = game.step(None);
+        if !matches!(result, StepResult::Moved | StepResult::AteApple) {
+            break;
+        }
+    }
+
+    assert_eq!(result, StepResult::Lost(Some(Death::Wall)));
+    assert_eq!(game.step(Some(Direction::Up)), result);
+    assert!(game.controller.cues.is_empty());
+}
+
+#[test]
+fn illegal_turns_are_ignored() {
+    let mut game = game_on_a_strip(8);
+    game.controller.snake.elements = vec![CanvasSpace((1, 0)), CanvasSpace((0, 0))]
+        .into_iter()
+        .collect();
+    game.controller.snake.current_direction = Direction::Right;
+
+    game.step(Some(Direction::Left));
+    assert_eq!(game.controller.snake.current_direction, Direction::Right);
+}
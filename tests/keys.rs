@@ -0,0 +1,147 @@
+use std::sync::mpsc;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use rusty_snake::keys::{Action, KeyBindings};
+use rusty_snake::leaderboard::NamePrompt;
+use rusty_snake::{continue_game_logic, handle_events, CanvasSpace, Controller, GameScreen};
+
+fn press(controller: &mut Controller, code: KeyCode) {
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx.send(Event::Key(code.into())).unwrap();
+    handle_events(controller, &event_rx);
+}
+
+#[test]
+fn help_lists_every_action_with_all_of_its_keys() {
+    let keys = KeyBindings::default();
+    let lines = keys.help_lines();
+
+    assert_eq!(keys.action(KeyCode::Char('h')), Some(Action::Help));
+    assert_eq!(keys.action(KeyCode::Char('x')), None);
+
+    assert_eq!(lines.len(), 11);
+    assert!(lines[0].starts_with("↑/w/k "));
+    assert!(lines[0].ends_with("  Turn up"));
+    assert!(lines.iter().any(|line| line.starts_with("?/h")));
+    assert!(lines.iter().any(|line| line.starts_with("Space")));
+}
+
+#[test]
+fn help_opens_and_any_key_closes_it() {
+    let mut controller = Controller::new();
+
+    press(&mut controller, KeyCode::Char('?'));
+    assert!(controller.show_help);
+    assert!(controller.last_event.is_none());
+
+    press(&mut controller, KeyCode::Char('q'));
+    assert!(!controller.show_help);
+    assert_ne!(controller.screen, GameScreen::Closed);
+    assert!(controller.last_event.is_none());
+}
+
+#[test]
+fn ctrl_c_quits_even_while_typing_a_name() {
+    let mut controller = Controller::new();
+    controller.name_prompt = Some(NamePrompt::new());
+
+    let (event_tx, event_rx) = mpsc::channel();
+    event_tx
+        .send(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )))
+        .unwrap();
+    handle_events(&mut controller, &event_rx);
+
+    assert_eq!(controller.screen, GameScreen::Closed);
+    assert_eq!(controller.name_prompt.as_ref().unwrap().name(), "anonymous");
+}
+
+#[test]
+fn pause_ignores_turns_until_resumed() {
+    let mut controller = Controller::new();
+
+    press(&mut controller, KeyCode::Char('p'));
+    assert_eq!(controller.screen, GameScreen::Paused);
+
+    press(&mut controller, KeyCode::Up);
+    assert!(controller.last_event.is_none());
+
+    press(&mut controller, KeyCode::Esc);
+    assert_ne!(controller.screen, GameScreen::Paused);
+    assert!(controller.last_event.is_none());
+
+    controller.screen = GameScreen::GameOver;
+    press(&mut controller, KeyCode::Char('p'));
+    assert_ne!(controller.screen, GameScreen::Paused);
+}
+
+#[test]
+fn restart_only_works_on_the_end_screen() {
+    let mut controller = Controller::new();
+
+    press(&mut controller, KeyCode::Char('r'));
+    assert!(!controller.restart_requested);
+
+    controller.screen = GameScreen::GameOver;
+    press(&mut controller, KeyCode::Char('r'));
+    assert!(controller.restart_requested);
+}
+
+#[test]
+fn wasd_and_hjkl_turn_the_snake_like_the_arrow_keys() {
+    let keys = KeyBindings::default();
+
+    for (code, action) in &[
+        ('w', Action::Up),
+        ('k', Action::Up),
+        ('a', Action::Left),
+        ('h', Action::Left),
+        ('s', Action::Down),
+        ('j', Action::Down),
+        ('d', Action::Right),
+        ('l', Action::Right),
+    ] {
+        assert_eq!(keys.playing_action(KeyCode::Char(*code)), Some(*action));
+    }
+    // Nowhere else, though.
+    assert_eq!(keys.action(KeyCode::Char('s')), Some(Action::Snapshot));
+    assert_eq!(keys.playing_action(KeyCode::Char('q')), Some(Action::Quit));
+
+    let mut controller = Controller::new();
+    controller.snake.elements = vec![CanvasSpace((5, 5))].into();
+    press(&mut controller, KeyCode::Char('j'));
+    assert!(!controller.show_help);
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.snake.elements[0], CanvasSpace((5, 6)));
+
+    controller.screen = GameScreen::GameOver;
+    press(&mut controller, KeyCode::Char('s'));
+    assert!(controller.snapshot_requested);
+}
+
+#[test]
+fn binding_a_movement_key_takes_it_over() {
+    let mut keys = KeyBindings::default();
+    keys.bind(KeyCode::Char('w'), Action::Quit);
+
+    assert_eq!(keys.playing_action(KeyCode::Char('w')), Some(Action::Quit));
+    assert_eq!(keys.playing_action(KeyCode::Char('k')), Some(Action::Up));
+}
+
+#[test]
+fn every_key_of_an_action_is_listed() {
+    let mut keys = KeyBindings::default();
+    assert_eq!(
+        keys.keys(Action::Up),
+        vec![KeyCode::Up, KeyCode::Char('w'), KeyCode::Char('k')]
+    );
+
+    keys.bind(KeyCode::Char('k'), Action::Quit);
+    assert_eq!(keys.keys(Action::Up), vec![KeyCode::Up, KeyCode::Char('w')]);
+    assert_eq!(
+        keys.keys(Action::Quit),
+        vec![KeyCode::Char('q'), KeyCode::Char('k')]
+    );
+}
@@ -0,0 +1,113 @@
+use crossterm::event::KeyCode;
+use rusty_snake::leaderboard::{
+    leaderboard_table, Entry, Leaderboard, NamePrompt, PromptState, MAX_ENTRIES,
+};
+
+fn entry(name: &str, score: u32) -> Entry {
+    Entry {
+        name: String::from(name),
+        score,
+        date: String::from("2021-03-04 12:30"),
+        board: None,
+        completed: false,
+    }
+}
+
+#[test]
+fn keeps_the_top_ten_by_score() {
+    let mut leaderboard = Leaderboard::default();
+
+    for score in 1..=12 {
+        leaderboard.insert(entry("player", score));
+    }
+
+    assert_eq!(leaderboard.entries.len(), MAX_ENTRIES);
+    assert_eq!(leaderboard.entries[0].score, 12);
+    assert_eq!(leaderboard.entries[MAX_ENTRIES - 1].score, 3);
+
+    assert!(!leaderboard.qualifies(3));
+    assert!(leaderboard.qualifies(4));
+    assert!(!Leaderboard::default().qualifies(0));
+}
+
+#[test]
+fn json_round_trip() {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.insert(entry("Ada \"the snake\"", 20));
+    leaderboard.insert(Entry {
+        board: Some((40, 20)),
+        completed: true,
+        ..entry("Grace\\", 10)
+    });
+
+    let json = leaderboard.to_json();
+
+    assert_eq!(Leaderboard::from_json(&json), Ok(leaderboard));
+}
+
+#[test]
+fn reads_hand_edited_json() {
+    let json = r#"[ {"date": "2021-01-01", "score": 5, "name": "Béa", "extra": 1},
+        {"name": "Cy", "score": 9, "date": "2021-01-02"} ]"#;
+
+    let leaderboard = Leaderboard::from_json(json).unwrap();
+
+    assert_eq!(leaderboard.entries[0].name, "Cy");
+    assert_eq!(leaderboard.entries[1].name, "Béa");
+    assert_eq!(leaderboard.entries[0].board, None);
+    assert!(Leaderboard::from_json("[{\"name\": \"x\"}]").is_err());
+    assert!(Leaderboard::from_json(r#"[{"name": "x", "score": 1, "board": [3]}]"#).is_err());
+}
+
+#[test]
+fn record_adds_to_the_file() {
+    let path = std::env::temp_dir().join(format!(
+        "rusty-snake-leaderboard-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    Leaderboard::record(&path, entry("Ada", 5)).unwrap();
+    let updated = Leaderboard::record(&path, entry("Grace", 8)).unwrap();
+
+    assert_eq!(updated.entries[0].name, "Grace");
+    assert_eq!(Leaderboard::load(&path).unwrap(), updated);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn name_prompt_edits_and_submits() {
+    let mut prompt = NamePrompt::new();
+
+    for code in [
+        KeyCode::Char('A'),
+        KeyCode::Char('x'),
+        KeyCode::Backspace,
+        KeyCode::Char('l'),
+        KeyCode::Enter,
+        KeyCode::Char('z'),
+    ]
+    .iter()
+    {
+        prompt.handle_key(*code);
+    }
+
+    assert_eq!(prompt.state, PromptState::Submitted);
+    assert_eq!(prompt.name(), "Al");
+    assert_eq!(NamePrompt::new().name(), "anonymous");
+}
+
+#[test]
+fn table_lists_ranks() {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.insert(entry("Ada", 20));
+
+    let table = leaderboard_table(&leaderboard);
+
+    assert_eq!(table.lines().count(), 2);
+    assert!(table.lines().nth(1).unwrap().starts_with("  1  Ada "));
+
+    leaderboard.entries[0].board = Some((40, 20));
+    assert!(leaderboard_table(&leaderboard).contains(" 40x20 "));
+}
@@ -0,0 +1,61 @@
+use rusty_snake::level::Level;
+use rusty_snake::{continue_game_logic, CanvasBounds, CanvasSpace, Controller};
+
+const MAP: &str = "; A box with a start and a corner without apples.
+#####
+#S..#
+#.xx
+#####
+";
+
+#[test]
+fn maps_become_walls_a_start_and_cells_without_apples() {
+    let level: Level = MAP.parse().unwrap();
+
+    assert_eq!(level.bounds, CanvasBounds::new(5, 4));
+    assert_eq!(level.walls.len(), 5 + 2 + 1 + 5);
+    assert!(level.walls.contains(&CanvasSpace((4, 1))));
+    assert!(!level.walls.contains(&CanvasSpace((4, 2))));
+    assert_eq!(level.spawn, Some(CanvasSpace((1, 1))));
+    assert_eq!(
+        level.no_apples,
+        vec![CanvasSpace((2, 2)), CanvasSpace((3, 2))]
+    );
+}
+
+#[test]
+fn apples_keep_off_the_forbidden_cells() {
+    let level: Level = MAP.parse().unwrap();
+
+    for seed in 0..20 {
+        let mut controller = Controller::new();
+        controller.rng = rusty_snake::rng::GameRng::seed_from_u64(seed);
+        controller.bounds = level.bounds;
+        controller.obstacles = level.walls.clone();
+        controller.no_apples = level.no_apples.clone();
+        controller.snake.elements = vec![CanvasSpace((1, 1))].into();
+
+        continue_game_logic(&mut controller);
+
+        let apple = &controller.apples[0].position;
+        assert!(!controller.obstacles.contains(apple));
+        assert!(!controller.no_apples.contains(apple));
+    }
+}
+
+#[test]
+fn errors_point_at_the_line() {
+    assert_eq!(
+        "##\n#?\n".parse::<Level>(),
+        Err(String::from(
+            "Line 2, column 2: Unknown cell `?`, expected #, ., S or x."
+        ))
+    );
+    assert_eq!(
+        "S.\n; comment\n.S\n".parse::<Level>(),
+        Err(String::from(
+            "Line 3: A second start, the first one is on line 1."
+        ))
+    );
+    assert!("; only a comment\n".parse::<Level>().is_err());
+}
@@ -0,0 +1,31 @@
+use crossterm::event::KeyCode;
+use rusty_snake::menu::{MainMenuItem, Menu, MenuInput, MAIN_MENU};
+
+#[test]
+fn arrows_move_the_selection_around() {
+    let mut menu = Menu::new(&MAIN_MENU);
+
+    assert_eq!(menu.selected(), MainMenuItem::Play);
+    assert_eq!(menu.handle_key(KeyCode::Up), None);
+    assert_eq!(menu.selected(), MainMenuItem::Quit);
+
+    menu.handle_key(KeyCode::Down);
+    menu.handle_key(KeyCode::Down);
+    assert_eq!(
+        menu.handle_key(KeyCode::Enter),
+        Some(MenuInput::Choose(MainMenuItem::Options))
+    );
+}
+
+#[test]
+fn other_keys_are_handed_back() {
+    let mut menu = Menu::new(&MAIN_MENU);
+
+    assert_eq!(
+        menu.handle_key(KeyCode::Right),
+        Some(MenuInput::Adjust(MainMenuItem::Play, 1))
+    );
+    assert_eq!(menu.handle_key(KeyCode::Esc), Some(MenuInput::Back));
+    assert_eq!(menu.handle_key(KeyCode::Char('x')), None);
+    assert_eq!(MainMenuItem::HighScores.label(), "High Scores");
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use rusty_snake::net::{Board, Client, Peer, Update};
+use rusty_snake::rng::GameRng;
+use rusty_snake::{
+    continue_game_logic, place_second_player, place_snake, CanvasBounds, CanvasSpace, Controller,
+    Direction, StartPosition,
+};
+
+fn two_player_game() -> Controller {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(20, 20);
+    controller.rng = GameRng::seed_from_u64(5);
+    place_snake(&mut controller, &StartPosition::Center, 3).unwrap();
+    place_second_player(&mut controller, 3).unwrap();
+    controller.snake.current_direction = Direction::Up;
+    controller.second.as_mut().unwrap().snake.current_direction = Direction::Right;
+    controller
+}
+
+#[test]
+fn steps_rebuild_the_hosts_board() {
+    let mut controller = two_player_game();
+    let mut sent = Board::capture(0, &controller);
+    let mut received: Board = Update::Full(sent.clone()).to_string().parse().map_or_else(
+        |e: String| panic!("{}", e),
+        |update| match update {
+            Update::Full(board) => board,
+            Update::Step(_) => panic!("Expected the whole board."),
+        },
+    );
+
+    for tick in 1..8 {
+        continue_game_logic(&mut controller);
+        let board = Board::capture(tick, &controller);
+
+        let line = sent.diff(&board).to_string();
+        assert!(line.starts_with("step "), "{}", line);
+        match line.parse().unwrap() {
+            Update::Step(step) => received.apply(&step),
+            Update::Full(board) => received = board,
+        }
+
+        assert_eq!(received, board);
+        sent = board;
+    }
+}
+
+#[test]
+fn anything_but_a_move_sends_the_whole_board() {
+    let controller = two_player_game();
+    let before = Board::capture(0, &controller);
+
+    let mut restarted = controller.clone();
+    restarted.snake.elements = VecDeque::from(vec![CanvasSpace((1, 1)), CanvasSpace((1, 2))]);
+    let after = Board::capture(1, &restarted);
+    assert_eq!(before.diff(&after), Update::Full(after.clone()));
+
+    let line = before.diff(&after).to_string();
+    assert_eq!(line.parse::<Update>(), Ok(Update::Full(after)));
+    assert!("step 1 0".parse::<Update>().is_err());
+}
+
+#[test]
+fn the_board_shows_both_snakes() {
+    let controller = two_player_game();
+    let board = Board::capture(3, &controller);
+
+    let mut shown = Controller::new();
+    board.show(&mut shown);
+    assert_eq!(shown.bounds, controller.bounds);
+    assert_eq!(shown.snake.elements, controller.snake.elements);
+    assert_eq!(
+        shown.second.unwrap().snake.elements,
+        controller.second.unwrap().snake.elements
+    );
+}
+
+#[test]
+fn lagging_peers_catch_up_with_the_whole_board() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = Client::join(listener.local_addr().unwrap(), "match").unwrap();
+    let mut peer = Peer::new(listener.accept().unwrap().0).unwrap();
+
+    // Changing walls make every update a whole board, far more than the connection holds while
+    // nobody reads. Sending never waits for the client.
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(100, 100);
+    for tick in 0..200 {
+        controller.obstacles = controller.bounds.iter_cells().skip(tick % 2).collect();
+        assert!(peer.send(Board::capture(tick as u64, &controller)));
+    }
+
+    // From here on only steps are due, which carry no walls. The client only ends up with the
+    // right ones if a dropped update is followed by the whole board.
+    let mut view = Controller::new();
+    for tick in 200..1200 {
+        assert!(peer.send(Board::capture(tick, &controller)));
+
+        if client.receive(&mut view) == Ok(Some(tick)) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(view.obstacles, controller.obstacles);
+}
+
+#[test]
+fn checking_on_a_peer_keeps_its_turns() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let peer = Peer::new(listener.accept().unwrap().0).unwrap();
+
+    stream.write_all(b"turn left\n").unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    assert!(peer.is_connected());
+    assert!(peer.is_connected());
+    assert_eq!(peer.turn(), Some(Direction::Left));
+
+    drop(stream);
+    thread::sleep(Duration::from_millis(200));
+    assert!(!peer.is_connected());
+}
@@ -0,0 +1,120 @@
+use rusty_snake::json;
+use rusty_snake::leaderboard::Entry;
+use rusty_snake::online::{response_body, Endpoint, Submission};
+
+fn submission() -> Submission {
+    Submission {
+        entry: Entry {
+            name: String::from("Derek"),
+            score: 42,
+            date: String::from("2026-10-14 12:00"),
+            board: Some((46, 46)),
+            completed: false,
+        },
+        seed: 7,
+        replay: 0xabc,
+    }
+}
+
+#[test]
+fn endpoints_are_plain_http_urls() {
+    assert_eq!(
+        "http://scores.example.com:8080/snake/".parse(),
+        Ok(Endpoint {
+            host: String::from("scores.example.com"),
+            port: 8080,
+            path: String::from("/snake"),
+        })
+    );
+    assert_eq!(
+        "http://localhost"
+            .parse::<Endpoint>()
+            .map(|e| (e.port, e.path)),
+        Ok((80, String::new()))
+    );
+
+    assert!("https://scores.example.com".parse::<Endpoint>().is_err());
+    assert!("scores.example.com".parse::<Endpoint>().is_err());
+    assert!("http://:80".parse::<Endpoint>().is_err());
+    assert!("http://host:port".parse::<Endpoint>().is_err());
+}
+
+#[test]
+fn submissions_are_leaderboard_records_with_the_run_attached() {
+    let record = json::parse(&submission().to_json()).unwrap();
+
+    assert_eq!(
+        record.get("name").and_then(json::Value::as_str),
+        Some("Derek")
+    );
+    assert_eq!(record.get("score").and_then(json::Value::as_u32), Some(42));
+    assert_eq!(record.get("seed").and_then(json::Value::as_u32), Some(7));
+    assert_eq!(
+        record.get("replay").and_then(json::Value::as_str),
+        Some("0000000000000abc")
+    );
+}
+
+#[test]
+fn only_successful_responses_count() {
+    assert_eq!(
+        response_body("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]"),
+        Ok("[]")
+    );
+    assert!(response_body("HTTP/1.0 503 Service Unavailable\r\n\r\nbusy").is_err());
+    assert!(response_body("HTTP/1.0 200 OK\r\n").is_err());
+}
+
+#[cfg(not(feature = "online"))]
+#[test]
+fn without_the_feature_submissions_fail_right_away() {
+    let endpoint = "http://localhost:1".parse().unwrap();
+    let mut status = rusty_snake::online::submit(&endpoint, &submission());
+
+    assert!(matches!(status.poll(), Some(Err(_))));
+    assert!(matches!(status, rusty_snake::online::Status::Done(Err(_))));
+}
+
+#[cfg(feature = "online")]
+#[test]
+fn submits_and_fetches_the_top_ten() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for answer in &[
+            "",
+            "[{\"name\": \"Derek\", \"score\": 42, \"date\": \"2026-10-14\"}]",
+        ] {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).unwrap();
+            requests.push(String::from_utf8_lossy(&request[..read]).into_owned());
+            write!(stream, "HTTP/1.0 200 OK\r\n\r\n{}", answer).unwrap();
+        }
+        requests
+    });
+
+    let endpoint = format!("http://127.0.0.1:{}/snake", port).parse().unwrap();
+    let mut status = rusty_snake::online::submit(&endpoint, &submission());
+
+    let start = Instant::now();
+    let top = loop {
+        if let Some(result) = status.poll() {
+            break result.clone().unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(top.entries[0].score, 42);
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("POST /snake/scores HTTP/1.0"));
+    assert!(requests[0].ends_with(&submission().to_json()));
+    assert!(requests[1].starts_with("GET /snake/scores HTTP/1.0"));
+}
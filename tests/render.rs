@@ -0,0 +1,190 @@
+use crossterm::style::{Color, ContentStyle};
+use rusty_snake::render::{
+    changed_spans, layout_status_row, Align, Frame, Grid, Layer, Screen, Span, StatusField,
+};
+
+fn text(grid: &Grid, y: u16) -> String {
+    grid.row(y)
+        .iter()
+        .map(|cell| cell.symbol.as_str())
+        .collect()
+}
+
+#[test]
+fn text_is_cut_off_at_the_right_edge() {
+    let mut grid = Grid::new(6, 2);
+    grid.put_text(3, 1, "snake", ContentStyle::new());
+
+    assert_eq!(text(&grid, 0), "      ");
+    assert_eq!(text(&grid, 1), "   sna");
+}
+
+#[test]
+fn drawing_over_half_a_wide_character_blanks_the_other_half() {
+    let mut grid = Grid::new(6, 1);
+    grid.put_text(0, 0, "🍎🍎", ContentStyle::new());
+    assert_eq!(text(&grid, 0), "🍎🍎  ");
+    assert!(grid.row(0)[1].symbol.is_empty());
+
+    grid.put_text(1, 0, "x", ContentStyle::new());
+    assert_eq!(text(&grid, 0), " x🍎  ");
+
+    // Wide characters that don't fit are left out.
+    grid.put_text(5, 0, "🍎", ContentStyle::new());
+    assert_eq!(text(&grid, 0), " x🍎  ");
+}
+
+#[test]
+fn only_changed_columns_are_printed_again() {
+    let mut shown = Grid::new(10, 3);
+    shown.put_text(0, 0, "score 1", ContentStyle::new());
+    shown.put_text(0, 2, "🍎", ContentStyle::new());
+
+    let mut next = shown.clone();
+    next.put_text(6, 0, "2", ContentStyle::new());
+    next.put_text(0, 1, "paused", ContentStyle::new().foreground(Color::Red));
+    next.put_text(1, 2, "x", ContentStyle::new());
+
+    assert_eq!(
+        changed_spans(&shown, &next),
+        vec![
+            Span {
+                y: 0,
+                start: 6,
+                end: 7
+            },
+            Span {
+                y: 1,
+                start: 0,
+                end: 6
+            },
+            Span {
+                y: 2,
+                start: 0,
+                end: 2
+            },
+        ]
+    );
+    assert_eq!(changed_spans(&next, &next), vec![]);
+}
+
+#[test]
+fn screens_start_over_when_invalidated_or_resized() {
+    let mut screen = Screen::new();
+    screen.resize(4, 2);
+    screen.next_mut().put_text(0, 0, "ab", ContentStyle::new());
+
+    let (from_scratch, spans) = screen.changes();
+    assert!(from_scratch);
+    assert_eq!(
+        spans,
+        vec![Span {
+            y: 0,
+            start: 0,
+            end: 2
+        }]
+    );
+    screen.presented();
+
+    // The grid keeps what was drawn, like a terminal does.
+    screen.next_mut().put_text(1, 1, "c", ContentStyle::new());
+    assert_eq!(
+        screen.changes(),
+        (
+            false,
+            vec![Span {
+                y: 1,
+                start: 1,
+                end: 2
+            }]
+        )
+    );
+    screen.presented();
+    assert_eq!(screen.changes(), (false, vec![]));
+
+    screen.invalidate();
+    assert!(screen.changes().0);
+    screen.presented();
+
+    screen.resize(4, 2);
+    assert_eq!(screen.changes(), (false, vec![]));
+    screen.resize(5, 2);
+    assert_eq!(screen.changes(), (true, vec![]));
+    assert_eq!(text(screen.next(), 0), "     ");
+}
+
+#[test]
+fn higher_layers_end_up_on_top_whatever_the_drawing_order() {
+    let mut frame = Frame::new();
+    frame.set_layer(Layer::Hud);
+    frame.draw_text(0, 0, "score", ContentStyle::new());
+    frame.set_layer(Layer::Entities);
+    frame.draw_text(2, 0, "##", ContentStyle::new());
+    frame.set_layer(Layer::Board);
+    frame.draw_text(0, 0, "........", ContentStyle::new());
+
+    let mut grid = Grid::new(8, 1);
+    frame.compose(&mut grid);
+    assert_eq!(text(&grid, 0), "score...");
+}
+
+#[test]
+fn frames_only_blank_the_grid_when_cleared() {
+    let mut grid = Grid::new(4, 1);
+    grid.put_text(0, 0, "ab", ContentStyle::new());
+
+    let mut frame = Frame::new();
+    frame.draw_text(3, 0, "c", ContentStyle::new());
+    frame.compose(&mut grid);
+    assert_eq!(text(&grid, 0), "ab c");
+
+    frame.clear();
+    frame.draw_text(1, 0, "d", ContentStyle::new());
+    frame.compose(&mut grid);
+    assert_eq!(text(&grid, 0), " d  ");
+}
+
+fn field(text: &str, align: Align, priority: u8) -> StatusField {
+    StatusField {
+        text: String::from(text),
+        style: ContentStyle::new(),
+        align,
+        priority,
+        cut_off: false,
+    }
+}
+
+fn status_row(fields: Vec<StatusField>, width: u16) -> String {
+    let mut grid = Grid::new(width, 1);
+    for (x, field) in layout_status_row(fields, width) {
+        grid.put_text(x, 0, &field.text, field.style);
+    }
+    text(&grid, 0)
+}
+
+#[test]
+fn status_fields_share_the_row_without_overlapping() {
+    let fields = || {
+        vec![
+            field("0:42", Align::Left, 0),
+            field("Classic", Align::Left, 5),
+            field("$ 3s", Align::Center, 2),
+            StatusField {
+                cut_off: true,
+                ..field("Saved", Align::Center, 1)
+            },
+            field("x2 ###", Align::Right, 4),
+        ]
+    };
+
+    assert_eq!(
+        status_row(fields(), 40),
+        "0:42  Classic  $ 3s  Saved        x2 ###"
+    );
+
+    // The least important fields give way first, and only the message may be cut off.
+    assert_eq!(status_row(fields(), 30), "0:42     $ 3s  Saved    x2 ###");
+    assert_eq!(status_row(fields(), 20), "0:42  $ 3s  Saved   ");
+    assert_eq!(status_row(fields(), 15), "0:42  Saved    ");
+    assert_eq!(status_row(fields(), 10), "0:42  Save");
+}
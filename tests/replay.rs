@@ -0,0 +1,88 @@
+use crossterm::event::{Event, KeyCode};
+use rusty_snake::replay::{Input, Playback, Replay};
+use rusty_snake::rng::GameRng;
+use rusty_snake::save::to_json;
+use rusty_snake::{continue_game_logic, Controller, Direction, GameScreen, SPRINT_TICKS};
+
+// Plays `ticks` ticks of a run, turning and sprinting on a fixed pattern, and records them.
+fn recorded_run(ticks: usize) -> (Controller, Replay) {
+    let mut controller = Controller::new();
+    controller.rng = GameRng::seed_from_u64(7);
+    let mut replay = Replay::default();
+
+    let keys = [KeyCode::Right, KeyCode::Down, KeyCode::Left, KeyCode::Up];
+
+    for tick in 0..ticks {
+        if controller.screen == GameScreen::GameOver {
+            break;
+        }
+
+        if tick % 4 == 0 {
+            controller.last_event = Some(Event::Key(keys[tick / 4 % keys.len()].into()));
+        }
+        if tick % 9 == 0 {
+            controller.sprint_ticks = SPRINT_TICKS;
+        }
+
+        replay.record(&controller);
+        continue_game_logic(&mut controller);
+    }
+
+    (controller, replay)
+}
+
+#[test]
+fn playback_ends_where_the_recording_did() {
+    let (recorded, replay) = recorded_run(200);
+    let replay: Replay = replay.to_string().parse().unwrap();
+
+    let mut controller = replay.controller().unwrap();
+    let mut playback = Playback::new(&replay);
+    while playback.step(&mut controller) {}
+
+    assert!(playback.is_over());
+    assert_eq!(
+        playback.position(),
+        (replay.inputs.len(), replay.inputs.len())
+    );
+    assert_eq!(to_json(&controller), to_json(&recorded));
+    assert!(!playback.step(&mut controller));
+}
+
+#[test]
+fn input_is_run_length_encoded() {
+    let inputs = vec![
+        Input {
+            turn: None,
+            sprint: false,
+        },
+        Input {
+            turn: Some(Direction::Right),
+            sprint: false,
+        },
+        Input {
+            turn: Some(Direction::Right),
+            sprint: false,
+        },
+        Input {
+            turn: Some(Direction::Up),
+            sprint: true,
+        },
+        Input {
+            turn: None,
+            sprint: true,
+        },
+    ];
+    let replay = Replay {
+        start: String::new(),
+        inputs,
+    };
+
+    let text = replay.to_string();
+
+    assert_eq!(text.lines().nth(1), Some(".2Ru*"));
+    assert_eq!(text.parse(), Ok(replay));
+    assert!("rusty-snake replay 1\n3\n".parse::<Replay>().is_err());
+    assert!("rusty-snake replay 1\nX\n".parse::<Replay>().is_err());
+    assert!("something else\n".parse::<Replay>().is_err());
+}
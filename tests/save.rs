@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use rusty_snake::effects::{ActiveEffect, Effect};
+use rusty_snake::save::{from_json, to_json};
+use rusty_snake::speed::{Curve, Ramp};
+use rusty_snake::{Apple, AppleType, CanvasBounds, CanvasSpace, Controller, Direction, GameMode};
+
+#[test]
+fn a_saved_run_loads_back_unchanged() {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(30, 20).shrunk();
+    controller.snake.elements = vec![(5, 4), (4, 4), (3, 4)]
+        .into_iter()
+        .map(CanvasSpace)
+        .collect();
+    controller.snake.current_direction = Direction::Right;
+    controller.apples = vec![Apple::new(CanvasSpace((10, 12)), AppleType::Fresh('🍎'))];
+    controller.pickup = Some((CanvasSpace((1, 2)), Effect::SlowDown));
+    controller.active_effects = vec![ActiveEffect {
+        effect: Effect::SpeedUp,
+        remaining_ticks: 7,
+    }];
+    controller.score = 12;
+    controller.combo = 2;
+    controller.game_mode = GameMode::Hunger;
+    controller.obstacles_every = Some(5);
+    controller.obstacles = vec![CanvasSpace((20, 3))];
+    controller.portals = Some((CanvasSpace((2, 17)), CanvasSpace((27, 1))));
+    controller.max_length = Some(40);
+    controller.wrap = true;
+    controller.ramp = Some(Ramp {
+        curve: Curve::Exponential,
+        cap: 18,
+    });
+    controller.stats.ticks = 321;
+    controller.stats.elapsed = Duration::from_millis(32_100);
+    controller.stats.apples_eaten = 3;
+    controller.stats.best_combo = 2;
+
+    assert_eq!(from_json(&to_json(&controller)), Ok(controller));
+}
+
+#[test]
+fn rejects_other_versions_and_broken_fields() {
+    let json = to_json(&Controller::new());
+
+    assert!(from_json(&json.replace("\"version\": 1", "\"version\": 2")).is_err());
+    assert!(from_json(&json.replace("\"classic\"", "\"zen\"")).is_err());
+    assert!(from_json(&json.replace("\"snake\": [", "\"snake\": [[], ")).is_err());
+    assert!(from_json("{}").is_err());
+}
@@ -0,0 +1,110 @@
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rusty_snake::net::Client;
+use rusty_snake::server::{self, RoomEvent};
+use rusty_snake::{place_second_player, place_snake, Controller, StartPosition};
+
+fn new_game() -> Controller {
+    let mut controller = Controller::new();
+    place_snake(&mut controller, &StartPosition::Center, 3).unwrap();
+    place_second_player(&mut controller, 3).unwrap();
+    controller
+}
+
+// Receives until a board arrives, for up to a few seconds.
+fn first_board(client: &mut Client, controller: &mut Controller) -> bool {
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(5) {
+        if let Ok(Some(_)) = client.receive(controller) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn rooms_start_once_both_seats_are_taken() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&events);
+    thread::spawn(move || {
+        server::serve(listener, new_game, move |room, event| {
+            log.lock().unwrap().push((room.to_string(), event))
+        })
+    });
+
+    let mut first = Client::join(addr, "one").unwrap();
+    let mut elsewhere = Client::join(addr, "two").unwrap();
+    let mut second = Client::join(addr, "one").unwrap();
+
+    let (mut first_view, mut second_view) = (Controller::new(), Controller::new());
+    assert!(first_board(&mut first, &mut first_view));
+    assert!(first_board(&mut second, &mut second_view));
+
+    // Both see themselves as the second snake. Players are seated as their handshakes come
+    // in, so either of them may have the first seat.
+    let game = new_game();
+    let (seated_first, seated_second) = if first_view.snake.elements == game.snake.elements {
+        (second_view, first_view)
+    } else {
+        (first_view, second_view)
+    };
+    assert_eq!(
+        seated_first.snake.elements,
+        game.second.unwrap().snake.elements
+    );
+    assert_eq!(
+        seated_first.second.unwrap().snake.elements,
+        game.snake.elements
+    );
+    assert_eq!(seated_second.snake.elements, game.snake.elements);
+
+    // Alone in its room, the other player is still waiting.
+    let mut waiting = Controller::new();
+    assert_eq!(elsewhere.receive(&mut waiting), Ok(None));
+
+    // What happened goes to the log instead of the output.
+    let events: Vec<RoomEvent> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(room, _)| room == "one")
+        .map(|&(_, event)| event)
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            RoomEvent::Opened,
+            RoomEvent::PlayerJoined,
+            RoomEvent::PlayerJoined,
+            RoomEvent::RoundStarted,
+        ]
+    );
+}
+
+#[test]
+fn spectators_see_the_room_as_it_is() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || server::serve(listener, new_game, |_, _| ()));
+
+    let mut spectator = Client::watch(addr, "match").unwrap();
+    let _first = Client::join(addr, "match").unwrap();
+    let mut view = Controller::new();
+
+    // Watching doesn't take a seat, so a single player can't start the round.
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(spectator.receive(&mut view), Ok(None));
+
+    let _second = Client::join(addr, "match").unwrap();
+    assert!(first_board(&mut spectator, &mut view));
+    assert_eq!(view.snake.elements, new_game().snake.elements);
+    assert!(spectator.is_watching());
+    assert!(spectator.turn(rusty_snake::Direction::Up).is_ok());
+}
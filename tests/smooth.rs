@@ -0,0 +1,65 @@
+use rusty_snake::smooth::{
+    entering_glyph, leaving_glyph, partial_cells, phase, side_between, Motion, Side,
+};
+use rusty_snake::{CanvasSpace, Controller};
+
+fn controller_with_snake(elements: &[(i32, i32)]) -> Controller {
+    let mut controller = Controller::new();
+    controller.snake.elements = elements.iter().map(|&cell| CanvasSpace(cell)).collect();
+    controller
+}
+
+#[test]
+fn sides_only_exist_between_neighbours() {
+    let cell = CanvasSpace((5, 5));
+
+    assert_eq!(side_between(&cell, &CanvasSpace((6, 5))), Some(Side::Right));
+    assert_eq!(side_between(&cell, &CanvasSpace((4, 5))), Some(Side::Left));
+    assert_eq!(side_between(&cell, &CanvasSpace((5, 4))), Some(Side::Up));
+    assert_eq!(side_between(&cell, &CanvasSpace((5, 6))), Some(Side::Down));
+    assert_eq!(side_between(&cell, &CanvasSpace((7, 5))), None);
+    assert_eq!(side_between(&cell, &cell), None);
+}
+
+#[test]
+fn phases_cover_the_tick() {
+    assert_eq!(phase(0.0), 0);
+    assert_eq!(phase(0.3), 1);
+    assert_eq!(phase(0.6), 2);
+    assert_eq!(phase(1.0), 3);
+    assert_eq!(phase(-1.0), 0);
+}
+
+#[test]
+fn head_slides_in_while_tail_slides_out() {
+    // Moved right from (4, 5) to (5, 5), the tail left (2, 5).
+    let controller = controller_with_snake(&[(5, 5), (4, 5), (3, 5)]);
+    let motion = Motion {
+        head_from: CanvasSpace((4, 5)),
+        tail_from: CanvasSpace((2, 5)),
+    };
+
+    assert_eq!(
+        partial_cells(&motion, &controller, 2),
+        vec![
+            (CanvasSpace((5, 5)), entering_glyph(Side::Left, 2)),
+            (CanvasSpace((2, 5)), leaving_glyph(Side::Right, 2)),
+        ]
+    );
+    assert_eq!(entering_glyph(Side::Left, 2), "█ ");
+    assert_eq!(leaving_glyph(Side::Right, 2), " █");
+}
+
+#[test]
+fn grown_or_resting_snakes_are_drawn_whole() {
+    // The tail stayed where it was, because the snake just ate.
+    let controller = controller_with_snake(&[(5, 5), (4, 5)]);
+    let grown = Motion {
+        head_from: CanvasSpace((4, 5)),
+        tail_from: CanvasSpace((4, 5)),
+    };
+    assert_eq!(partial_cells(&grown, &controller, 1).len(), 1);
+
+    let resting = Motion::capture(&controller).unwrap();
+    assert!(partial_cells(&resting, &controller, 1).is_empty());
+}
@@ -0,0 +1,101 @@
+use std::fs;
+
+use crossterm::event::{Event, KeyCode};
+use std::path::{Path, PathBuf};
+
+use rusty_snake::snapshot::{
+    rasterize, snapshot_path, snapshot_text, write_snapshot, DEFAULT_PATH,
+};
+use rusty_snake::{
+    continue_game_logic, handle_events, Apple, AppleType, CanvasBounds, CanvasSpace, Controller,
+    Direction, GameScreen,
+};
+
+fn small_controller() -> Controller {
+    let mut controller = Controller::new();
+    controller.bounds = CanvasBounds::new(5, 3);
+    controller.snake.elements = vec![CanvasSpace((1, 1))].into_iter().collect();
+    controller.snake.current_direction = Direction::Right;
+    controller.apples = vec![Apple::new(CanvasSpace((2, 1)), AppleType::Fresh('🍎'))];
+    controller
+}
+
+#[test]
+fn rasterize_after_scripted_moves() {
+    let mut controller = small_controller();
+
+    // Eat the apple, then turn down. The apple respawns randomly, so it is parked afterwards.
+    continue_game_logic(&mut controller);
+    continue_game_logic(&mut controller);
+    controller.apples = vec![Apple::new(CanvasSpace((0, 0)), AppleType::Fresh('🍎'))];
+
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    event_tx.send(Event::Key(KeyCode::Down.into())).unwrap();
+    handle_events(&mut controller, &event_rx);
+    continue_game_logic(&mut controller);
+
+    assert_eq!(
+        rasterize(&controller),
+        vec![
+            "+-----+", //
+            "|o    |", "|   # |", "|   @ |", "+-----+",
+        ]
+    );
+}
+
+#[test]
+fn snapshot_has_footer() {
+    let mut controller = small_controller();
+    controller.score = 7;
+
+    let text = snapshot_text(&controller, Some(42), "2021-03-04");
+
+    assert!(text.ends_with(
+        "|\n+-----+\nScore: 7  Length: 1  Time: 00:00  Ticks: 0  Seed: 42  Date: 2021-03-04\n"
+    ));
+}
+
+#[test]
+fn snapshot_does_not_overwrite_without_force() {
+    let path = std::env::temp_dir().join(format!("rusty-snake-test-{}.txt", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    write_snapshot(&path, "first", false).unwrap();
+    assert!(write_snapshot(&path, "second", false).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+    write_snapshot(&path, "second", true).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn the_snapshot_flag_saves_a_restarted_round() {
+    let mut controller = small_controller();
+    controller.screen = GameScreen::GameOver;
+
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    event_tx
+        .send(Event::Key(KeyCode::Char('r').into()))
+        .unwrap();
+    handle_events(&mut controller, &event_rx);
+    assert!(controller.restart_requested);
+
+    let flag = Path::new("every-round.txt");
+    assert_eq!(
+        snapshot_path(Some(flag), &controller),
+        Some(flag.to_path_buf())
+    );
+    assert_eq!(snapshot_path(None, &controller), None);
+
+    // `s` saves the round even without the flag.
+    event_tx
+        .send(Event::Key(KeyCode::Char('s').into()))
+        .unwrap();
+    handle_events(&mut controller, &event_rx);
+    assert_eq!(
+        snapshot_path(None, &controller),
+        Some(PathBuf::from(DEFAULT_PATH))
+    );
+}
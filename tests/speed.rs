@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use rusty_snake::speed::{self, Curve, Ramp, RAMP_EVERY};
+use rusty_snake::{continue_game_logic, CanvasSpace, Controller, Direction};
+
+#[test]
+fn ramps_step_with_the_score_up_to_their_cap() {
+    let linear = Ramp {
+        curve: Curve::Linear,
+        cap: 13,
+    };
+    assert_eq!(linear.ticks_per_sec(10, 0), 10);
+    assert_eq!(linear.ticks_per_sec(10, RAMP_EVERY - 1), 10);
+    assert_eq!(linear.ticks_per_sec(10, RAMP_EVERY), 11);
+    assert_eq!(linear.ticks_per_sec(10, 100 * RAMP_EVERY), 13);
+
+    let exponential = Ramp {
+        curve: Curve::Exponential,
+        cap: 60,
+    };
+    assert_eq!(exponential.ticks_per_sec(10, 2 * RAMP_EVERY), 12);
+    assert_eq!(exponential.ticks_per_sec(10, 10 * RAMP_EVERY), 26);
+
+    // A cap below the starting speed never slows the game down.
+    assert_eq!(linear.ticks_per_sec(20, 100), 20);
+
+    assert_eq!("exponential".parse(), Ok(Curve::Exponential));
+    assert!("steep".parse::<Curve>().is_err());
+}
+
+#[test]
+fn ticks_get_shorter_as_the_score_grows() {
+    let mut controller = Controller::new();
+    controller.snake.elements = vec![CanvasSpace((5, 5))].into();
+    controller.snake.current_direction = Direction::Right;
+    controller.ticks_per_sec = 10;
+    controller.ramp = Some(Ramp::new(Curve::Linear));
+    controller.score = 10 * RAMP_EVERY;
+
+    assert_eq!(speed::ticks_per_sec(&controller), 20);
+
+    continue_game_logic(&mut controller);
+    assert_eq!(controller.stats.elapsed, Duration::from_millis(50));
+}
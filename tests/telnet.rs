@@ -0,0 +1,103 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use rusty_snake::telnet::{Connection, Decoder, NEGOTIATION};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[test]
+fn arrow_keys_and_characters_become_key_presses() {
+    let mut decoder = Decoder::new();
+
+    assert_eq!(
+        decoder.feed(b"\x1b[A\x1bOBq\xc3\xa9"),
+        vec![
+            key(KeyCode::Up),
+            key(KeyCode::Down),
+            key(KeyCode::Char('q')),
+            key(KeyCode::Char('é')),
+        ]
+    );
+}
+
+#[test]
+fn sequences_split_across_reads_are_put_together() {
+    let mut decoder = Decoder::new();
+
+    assert_eq!(decoder.feed(b"\x1b["), vec![]);
+    assert_eq!(decoder.feed(b"D"), vec![key(KeyCode::Left)]);
+    assert_eq!(decoder.feed(&[255, 250, 31, 0]), vec![]);
+    assert_eq!(
+        decoder.feed(&[120, 0, 40, 255, 240]),
+        vec![Event::Resize(120, 40)]
+    );
+}
+
+#[test]
+fn negotiation_replies_are_skipped() {
+    let mut decoder = Decoder::new();
+
+    // WILL NAWS, DO ECHO, DO SUPPRESS-GO-AHEAD, then a key.
+    assert_eq!(
+        decoder.feed(&[255, 251, 31, 255, 253, 1, 255, 253, 3, b'w']),
+        vec![key(KeyCode::Char('w'))]
+    );
+}
+
+#[test]
+fn control_keys_are_told_apart() {
+    let mut decoder = Decoder::new();
+
+    assert_eq!(
+        decoder.feed(b"\r\0\r\n\x7f\x1b\x03"),
+        vec![
+            key(KeyCode::Enter),
+            key(KeyCode::Enter),
+            key(KeyCode::Backspace),
+            key(KeyCode::Esc),
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        ]
+    );
+}
+
+#[test]
+fn connections_negotiate_and_forward_keys() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut negotiation = [0; NEGOTIATION.len()];
+        stream.read_exact(&mut negotiation).unwrap();
+        stream
+            .write_all(&[255, 251, 31, 255, 250, 31, 0, 100, 0, 30, 255, 240])
+            .unwrap();
+        stream.write_all(b"\x1b[C").unwrap();
+
+        let mut drawn = String::new();
+        stream.read_to_string(&mut drawn).unwrap();
+        (negotiation, drawn)
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    let (mut connection, events) = Connection::open(stream).unwrap();
+    assert_eq!(connection.size(), (100, 30));
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        received.push(events.recv_timeout(Duration::from_secs(5)).unwrap());
+    }
+    assert_eq!(received, vec![Event::Resize(100, 30), key(KeyCode::Right)]);
+
+    connection.write_all(b"snake").unwrap();
+    drop(connection);
+
+    let (negotiation, drawn) = client.join().unwrap();
+    assert_eq!(negotiation, NEGOTIATION);
+    assert_eq!(drawn, "snake");
+}
@@ -0,0 +1,166 @@
+use std::fs;
+
+use crossterm::style::Color;
+use rusty_snake::theme::{self, Theme, CLASSIC, MATRIX, THEMES};
+
+#[test]
+fn themes_are_found_by_name() {
+    assert_eq!(theme::named("classic"), Ok(CLASSIC));
+    assert_eq!(theme::named("matrix").unwrap().snake, Color::Green);
+    assert_eq!(Theme::default(), CLASSIC);
+
+    for (name, theme) in &THEMES {
+        assert_eq!(theme::named(name).as_ref(), Ok(theme));
+    }
+}
+
+#[test]
+fn unknown_themes_list_the_known_ones() {
+    assert_eq!(
+        theme::named("neon"),
+        Err(String::from(
+            "Unknown theme `neon`, pick one of classic, matrix, monochrome, pastel."
+        ))
+    );
+}
+
+#[test]
+fn theme_files_color_what_they_mention() {
+    let theme = Theme::from_toml(
+        "# Warm\nsnake = \"#ff8800\"\nsecond-snake = \"#00AAff\"\nhead = \"#ffffff\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        theme.snake,
+        Color::Rgb {
+            r: 255,
+            g: 136,
+            b: 0
+        }
+    );
+    assert_eq!(
+        theme.second_snake,
+        Color::Rgb {
+            r: 0,
+            g: 170,
+            b: 255
+        }
+    );
+    assert_eq!(
+        theme.head,
+        Color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255
+        }
+    );
+    assert_eq!(theme.border, CLASSIC.border);
+
+    assert_eq!(
+        Theme::from_toml("snake = \"red\""),
+        Err(String::from("`snake` must be a color like \"#ff8800\"."))
+    );
+    assert!(Theme::from_toml("snake = \"#ff88\"").is_err());
+    assert!(Theme::from_toml("snake = \"#gg8800\"").is_err());
+    assert!(Theme::from_toml("snake = 255").is_err());
+    assert!(Theme::from_toml("snak = \"#ff8800\"")
+        .unwrap_err()
+        .starts_with("Unknown element `snak`, themes can color snake, second-snake, head,"));
+    assert!(Theme::from_toml("snake = \"#ff8800")
+        .unwrap_err()
+        .starts_with("Line 1:"));
+}
+
+#[test]
+fn theme_files_are_loaded_by_name_and_can_replace_built_in_ones() {
+    let dir = std::env::temp_dir().join(format!("rusty-snake-themes-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sunset.toml"), "snake = \"#ff5e00\"\n").unwrap();
+    fs::write(dir.join("matrix.toml"), "snake = \"#00ff00\"\n").unwrap();
+    fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+    let user = theme::load_dir(&dir).unwrap();
+    let names: Vec<&str> = user.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["matrix", "sunset"]);
+
+    let themes = theme::all(&user);
+    assert_eq!(themes.len(), THEMES.len() + 1);
+    assert_eq!(
+        theme::find(&themes, "matrix").unwrap().snake,
+        Color::Rgb { r: 0, g: 255, b: 0 }
+    );
+    assert_eq!(
+        theme::find(&themes, "matrix").unwrap().border,
+        CLASSIC.border
+    );
+    assert_ne!(theme::find(&themes, "matrix"), Ok(MATRIX));
+    assert!(theme::find(&themes, "neon")
+        .unwrap_err()
+        .ends_with("pastel, sunset."));
+
+    fs::write(dir.join("broken.toml"), "snake = \"orange\"\n").unwrap();
+    let error = theme::load_dir(&dir).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "broken.toml: `snake` must be a color like \"#ff8800\"."
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(theme::load_dir(&dir).unwrap(), vec![]);
+}
+
+#[test]
+fn snakes_fade_from_head_to_tail() {
+    assert_eq!(
+        theme::gradient(Color::Red, 0, 5),
+        Color::Rgb { r: 255, g: 0, b: 0 }
+    );
+    assert_eq!(
+        theme::gradient(Color::Red, 2, 5),
+        Color::Rgb { r: 179, g: 0, b: 0 }
+    );
+    assert_eq!(
+        theme::gradient(Color::Red, 4, 5),
+        Color::Rgb { r: 102, g: 0, b: 0 }
+    );
+    assert_eq!(
+        theme::gradient(
+            Color::Rgb {
+                r: 100,
+                g: 200,
+                b: 50
+            },
+            1,
+            2
+        ),
+        Color::Rgb {
+            r: 40,
+            g: 80,
+            b: 20
+        }
+    );
+
+    // A single segment is all head, and colors without RGB stay as they are.
+    assert_eq!(
+        theme::gradient(Color::Blue, 0, 1),
+        Color::Rgb {
+            r: 92,
+            g: 92,
+            b: 255
+        }
+    );
+    assert_eq!(theme::gradient(Color::Reset, 3, 5), Color::Reset);
+    assert_eq!(
+        theme::gradient(Color::AnsiValue(42), 3, 5),
+        Color::AnsiValue(42)
+    );
+}
+
+#[test]
+fn truecolor_is_taken_from_colorterm() {
+    assert!(theme::truecolor(Some("truecolor")));
+    assert!(theme::truecolor(Some("24bit")));
+    assert!(!theme::truecolor(Some("")));
+    assert!(!theme::truecolor(None));
+}
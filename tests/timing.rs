@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use rusty_snake::timing::{FixedTimestep, TickRateMeter, MAX_CATCH_UP_TICKS};
+
+const INTERVAL: Duration = Duration::from_millis(100);
+
+#[test]
+fn no_tick_is_due_before_the_deadline() {
+    let start = Instant::now();
+    let mut timestep = FixedTimestep::new(start + INTERVAL);
+
+    assert_eq!(timestep.due_ticks(start + INTERVAL / 2, INTERVAL), 0);
+    assert_eq!(timestep.due_ticks(start + INTERVAL, INTERVAL), 1);
+    assert_eq!(timestep.next_tick(), start + INTERVAL * 2);
+}
+
+#[test]
+fn late_frames_catch_up_without_drifting() {
+    let start = Instant::now();
+    let mut timestep = FixedTimestep::new(start + INTERVAL);
+
+    // Being 30ms late on the third deadline still keeps the fourth one on schedule.
+    assert_eq!(
+        timestep.due_ticks(start + INTERVAL * 3 + Duration::from_millis(30), INTERVAL),
+        3
+    );
+    assert_eq!(timestep.next_tick(), start + INTERVAL * 4);
+}
+
+#[test]
+fn catch_up_is_capped() {
+    let start = Instant::now();
+    let mut timestep = FixedTimestep::new(start + INTERVAL);
+    let now = start + INTERVAL * 50;
+
+    assert_eq!(timestep.due_ticks(now, INTERVAL), MAX_CATCH_UP_TICKS);
+    assert_eq!(timestep.next_tick(), now + INTERVAL);
+}
+
+#[test]
+fn tick_rate_follows_the_interval_between_ticks() {
+    let start = Instant::now();
+    let mut meter = TickRateMeter::new();
+
+    meter.record_tick(start);
+    assert_eq!(meter.ticks_per_sec(), 0.0);
+
+    for tick in 1..=20 {
+        meter.record_tick(start + INTERVAL * tick);
+    }
+
+    assert!((meter.ticks_per_sec() - 10.0).abs() < 0.01);
+}
+
+#[test]
+fn progress_runs_from_zero_to_one_within_a_tick() {
+    let start = Instant::now();
+    let timestep = FixedTimestep::new(start + INTERVAL);
+
+    assert_eq!(timestep.progress(start, INTERVAL), 0.0);
+    assert!((timestep.progress(start + INTERVAL / 2, INTERVAL) - 0.5).abs() < 1e-9);
+    assert_eq!(timestep.progress(start + INTERVAL * 2, INTERVAL), 1.0);
+}
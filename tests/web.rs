@@ -0,0 +1,52 @@
+use rusty_snake::headless::StepResult;
+use rusty_snake::web::{direction, Cell, WebGame};
+use rusty_snake::Direction;
+
+fn cell_at(game: &WebGame, x: i32, y: i32) -> u8 {
+    let width = game.controller().bounds.width() as usize;
+    game.cells()[y as usize * width + x as usize]
+}
+
+#[test]
+fn cells_show_the_snake_and_the_apples() {
+    let game = WebGame::new(7);
+    let controller = game.controller();
+    let bounds = controller.bounds;
+
+    assert_eq!(game.cells().len() as u32, bounds.width() * bounds.height());
+
+    let (x, y) = controller.snake.elements[0].0;
+    assert_eq!(cell_at(&game, x, y), Cell::Head as u8);
+
+    for apple in &controller.apples {
+        let (x, y) = apple.position.0;
+        assert_ne!(cell_at(&game, x, y), Cell::Empty as u8);
+    }
+}
+
+#[test]
+fn turns_are_played_on_the_next_tick() {
+    let mut game = WebGame::new(7);
+    let (x, y) = game.controller().snake.elements[0].0;
+
+    game.turn(Direction::Left);
+    assert!(matches!(
+        game.tick(),
+        StepResult::Moved | StepResult::AteApple
+    ));
+
+    assert_eq!(game.controller().snake.elements[0].0, (x - 1, y));
+    assert_eq!(cell_at(&game, x - 1, y), Cell::Head as u8);
+}
+
+#[test]
+fn the_same_seed_plays_the_same_board() {
+    assert_eq!(WebGame::new(42).cells(), WebGame::new(42).cells());
+}
+
+#[test]
+fn key_codes_map_to_directions() {
+    assert_eq!(direction(0), Some(Direction::Up));
+    assert_eq!(direction(3), Some(Direction::Right));
+    assert_eq!(direction(4), None);
+}
@@ -0,0 +1,74 @@
+// The browser frontend: runs rusty_snake.wasm, built with the `wasm` feature as described in
+// src/web.rs, and paints its board onto the canvas after every tick.
+
+const CELL_SIZE = 12;
+
+// Indexed by `web::Cell`.
+const COLORS = ["#000", "#c33", "#f55", "#3c3", "#fc3", "#a3c", "#888"];
+
+// Key names to the direction codes of `web::direction`.
+const DIRECTIONS = {
+  ArrowUp: 0, w: 0, k: 0,
+  ArrowDown: 1, s: 1, j: 1,
+  ArrowLeft: 2, a: 2, h: 2,
+  ArrowRight: 3, d: 3, l: 3,
+};
+
+function newSeed() {
+  return [Math.random() * 2 ** 32 >>> 0, Math.random() * 2 ** 32 >>> 0];
+}
+
+async function main() {
+  const { instance } = await WebAssembly.instantiateStreaming(fetch("rusty_snake.wasm"));
+  const game = instance.exports;
+  const canvas = document.getElementById("board");
+  const status = document.getElementById("status");
+  const context = canvas.getContext("2d");
+
+  function draw() {
+    const width = game.snake_width();
+    const height = game.snake_height();
+    canvas.width = width * CELL_SIZE;
+    canvas.height = height * CELL_SIZE;
+
+    const cells = new Uint8Array(game.memory.buffer, game.snake_cells(), width * height);
+    cells.forEach((cell, index) => {
+      context.fillStyle = COLORS[cell];
+      context.fillRect(
+        (index % width) * CELL_SIZE,
+        Math.floor(index / width) * CELL_SIZE,
+        CELL_SIZE,
+        CELL_SIZE,
+      );
+    });
+
+    status.textContent = game.snake_is_over()
+      ? `Game over with ${game.snake_score()} - press r to play again`
+      : `Score ${game.snake_score()}`;
+  }
+
+  function tick() {
+    if (!game.snake_is_over()) {
+      game.snake_tick();
+    }
+    draw();
+    setTimeout(tick, game.snake_interval_ms());
+  }
+
+  document.addEventListener("keydown", (event) => {
+    if (event.key in DIRECTIONS) {
+      game.snake_turn(DIRECTIONS[event.key]);
+      event.preventDefault();
+    } else if (event.key === "r" && game.snake_is_over()) {
+      game.snake_new(...newSeed());
+      draw();
+    }
+  });
+
+  game.snake_new(...newSeed());
+  tick();
+}
+
+main().catch((error) => {
+  document.getElementById("status").textContent = `Could not start the game: ${error}`;
+});